@@ -7,8 +7,8 @@ use powdr_ast::{
         AnalysisASMFile, AssignmentStatement, CallableSymbolDefinitions, DebugDirective,
         DegreeStatement, FunctionBody, FunctionStatements, FunctionSymbol, Instruction,
         InstructionDefinitionStatement, InstructionStatement, Item, LabelStatement,
-        LinkDefinitionStatement, Machine, OperationSymbol, RegisterDeclarationStatement,
-        RegisterTy, Return, SubmachineDeclaration,
+        LinkDefinitionStatement, Machine, OperationSymbol, PcOverrideStatement,
+        RegisterDeclarationStatement, RegisterTy, Return, SubmachineDeclaration,
     },
     parsed::{
         self,
@@ -45,6 +45,7 @@ impl<T: FieldElement> TypeChecker<T> {
         let mut errors = vec![];
 
         let mut degree = None;
+        let mut pc_override = None;
         let mut registers = vec![];
         let mut pil = vec![];
         let mut instructions = vec![];
@@ -68,6 +69,19 @@ impl<T: FieldElement> TypeChecker<T> {
                     };
                     registers.push(RegisterDeclarationStatement { source, name, ty });
                 }
+                MachineStatement::PcOverride(source, flag, target) => {
+                    if pc_override.is_some() {
+                        errors.push(format!(
+                            "Machine {} cannot override the pc update more than once",
+                            ctx
+                        ));
+                    }
+                    pc_override = Some(PcOverrideStatement {
+                        source,
+                        flag,
+                        target,
+                    });
+                }
                 MachineStatement::InstructionDeclaration(source, name, instruction) => {
                     match self.check_instruction(&name, instruction) {
                         Ok(instruction) => instructions.push(InstructionDefinitionStatement {
@@ -197,6 +211,12 @@ impl<T: FieldElement> TypeChecker<T> {
                     ctx, i.name
                 ))
             }
+            if pc_override.is_some() {
+                errors.push(format!(
+                    "Machine {} should not override the pc update as it does not have a pc",
+                    ctx
+                ));
+            }
         } else {
             if latch.is_some() {
                 errors.push(format!(
@@ -222,6 +242,21 @@ impl<T: FieldElement> TypeChecker<T> {
             errors.push(format!("Machine {} cannot have more than one pc", ctx));
         }
 
+        if let Some(pc_override) = &pc_override {
+            let defined = |name: &String| {
+                pil.iter()
+                    .any(|s| s.symbol_definition_names().any(|n| n == name))
+            };
+            for name in [&pc_override.flag, &pc_override.target] {
+                if !defined(name) {
+                    errors.push(format!(
+                        "Machine {} overrides the pc update with `{}`, which is not defined in the machine",
+                        ctx, name
+                    ));
+                }
+            }
+        }
+
         let machine = Machine {
             degree,
             latch,
@@ -231,6 +266,7 @@ impl<T: FieldElement> TypeChecker<T> {
                 .enumerate()
                 .filter_map(|(i, r)| (r.ty.is_pc()).then_some(i))
                 .next(),
+            pc_override,
             registers,
             links,
             instructions,
@@ -374,4 +410,20 @@ mod tests {
             ]),
         );
     }
+
+    #[test]
+    fn pc_override_undefined_target() {
+        let src = r#"
+        machine Main {
+            reg pc[@pc];
+            col witness jump;
+            override_pc jump => target;
+        }"#;
+        expect_check_str(
+            src,
+            Err(vec![
+                "Machine :: overrides the pc update with `target`, which is not defined in the machine",
+            ]),
+        );
+    }
 }
@@ -9,7 +9,8 @@ use powdr_ast::{
     asm_analysis::{
         AssignmentStatement, Batch, DebugDirective, FunctionStatement,
         InstructionDefinitionStatement, InstructionStatement, LabelStatement,
-        LinkDefinitionStatement, Machine, RegisterDeclarationStatement, RegisterTy, Rom,
+        LinkDefinitionStatement, Machine, PcOverrideStatement, RegisterDeclarationStatement,
+        RegisterTy, Rom,
     },
     parsed::{
         asm::{CallableRef, InstructionBody, Params},
@@ -113,6 +114,8 @@ impl<T: FieldElement> ASMPILConverter<T> {
             ),
         ));
 
+        let pc_override = input.pc_override.take();
+
         self.pil.extend(
             self.registers
                 .iter()
@@ -127,6 +130,17 @@ impl<T: FieldElement> ASMPILConverter<T> {
                                 // this may not be optimal for backends which support higher degree constraints
                                 let pc_update_name = format!("{}_update", name);
 
+                                // If the machine overrides the pc update, the prover can bypass the
+                                // update rule in rows where the flag is set and jump to the target instead.
+                                let next_pc = match &pc_override {
+                                    Some(PcOverrideStatement { flag, target, .. }) => {
+                                        (Expression::from(T::one()) - direct_reference(flag))
+                                            * direct_reference(&pc_update_name)
+                                            + direct_reference(flag) * direct_reference(target)
+                                    }
+                                    None => direct_reference(&pc_update_name),
+                                };
+
                                 vec![
                                     PilStatement::PolynomialDefinition(
                                         SourceRef::unknown(),
@@ -139,7 +153,7 @@ impl<T: FieldElement> ASMPILConverter<T> {
                                             lhs,
                                             (Expression::from(T::one())
                                                 - next_reference("first_step"))
-                                                * direct_reference(pc_update_name),
+                                                * next_pc,
                                         ),
                                     ),
                                 ]
@@ -1246,6 +1260,55 @@ mod test {
         powdr_analysis::convert_vms_to_constrained(analyzed, &mut Default::default())
     }
 
+    #[test]
+    fn pc_update() {
+        // Columns named like the ones of a pc override do not change the pc update.
+        let asm = r"
+machine Main {
+  degree 8;
+  reg pc[@pc];
+
+  col witness pc_jump_flag;
+  let pc_jump_target = 4;
+
+  function main {
+    return;
+  }
+}
+";
+        let compiled = parse_analyse_and_compile::<GoldilocksField>(asm).to_string();
+        assert!(
+            compiled.contains("(pc' = ((1 - first_step') * pc_update));"),
+            "{compiled}"
+        );
+    }
+
+    #[test]
+    fn pc_override() {
+        let asm = r"
+machine Main {
+  degree 8;
+  reg pc[@pc];
+
+  col witness pc_jump_flag;
+  let pc_jump_target = 4;
+  override_pc pc_jump_flag => pc_jump_target;
+
+  function main {
+    return;
+  }
+}
+";
+        let compiled = parse_analyse_and_compile::<GoldilocksField>(asm).to_string();
+        assert!(
+            compiled.contains(
+                "(pc' = ((1 - first_step') * (((1 - pc_jump_flag) * pc_update) + (pc_jump_flag * pc_jump_target))));"
+            ),
+            "{compiled}"
+        );
+        assert!(!compiled.contains("override_pc"), "{compiled}");
+    }
+
     #[test]
     #[should_panic(expected = "All lhs params must be assignment registers")]
     fn instr_external_lhs_not_assignment_reg() {
@@ -0,0 +1,77 @@
+//! Helpers for `connect` identities (copy constraints).
+//!
+//! As in pilcom, every cell of the expressions on the left hand side of a
+//! `connect` identity is identified by a field element: The cell of the `j`th
+//! expression in row `i` is identified by `k**j * w**i`, where `w` is a primitive
+//! root of unity whose order is the degree and `k` is a coset shift.
+//! The fixed columns on the right hand side contain, for each cell, the
+//! identifier of the next cell in its cycle of the permutation.
+
+use std::collections::HashMap;
+
+use powdr_number::{log2_exact, DegreeType, FieldElement, KnownField};
+
+/// Generator of the subgroup of order 2**32 of the Goldilocks field.
+const GOLDILOCKS_ROOT_OF_UNITY_2_32: u64 = 7277203076849721926;
+/// The coset shift pilcom uses for the Goldilocks field.
+const GOLDILOCKS_COSET_SHIFT: u64 = 12275445934081160404;
+/// Generator of the subgroup of order 2**28 of the Bn254 scalar field.
+const BN254_ROOT_OF_UNITY_2_28: &str =
+    "1748695177688661943023146337482803886740723238769601073607632802312037301404";
+/// The multiplicative generator of the Bn254 scalar field, used as coset shift.
+const BN254_COSET_SHIFT: u64 = 7;
+
+/// A cell referenced by a `connect` identity: The index of the expression
+/// on the left hand side and the row.
+pub type Cell = (usize, DegreeType);
+
+/// Returns a map from the field elements identifying the cells of `expression_count`
+/// expressions of the given degree to the cells.
+pub fn cell_positions<T: FieldElement>(
+    expression_count: usize,
+    degree: DegreeType,
+) -> Result<HashMap<T, Cell>, String> {
+    let (max_log_degree, root_of_unity, coset_shift) = match T::known_field() {
+        Some(KnownField::GoldilocksField) => (
+            32,
+            T::from(GOLDILOCKS_ROOT_OF_UNITY_2_32),
+            T::from(GOLDILOCKS_COSET_SHIFT),
+        ),
+        Some(KnownField::Bn254Field) => (
+            28,
+            T::from_str(BN254_ROOT_OF_UNITY_2_28).unwrap(),
+            T::from(BN254_COSET_SHIFT),
+        ),
+        None => {
+            return Err(
+                "Connect identities are only supported for the Goldilocks and Bn254 fields."
+                    .to_string(),
+            )
+        }
+    };
+    let log_degree = log2_exact(degree.into())
+        .filter(|log_degree| *log_degree <= max_log_degree)
+        .ok_or_else(|| {
+            format!(
+                "Connect identities require a power of two degree of at most 2**{max_log_degree}, got {degree}."
+            )
+        })?;
+    let root_of_unity = root_of_unity.pow((1u64 << (max_log_degree - log_degree)).into());
+
+    let mut positions = HashMap::with_capacity(expression_count * degree as usize);
+    let mut row_factor = T::one();
+    for row in 0..degree {
+        let mut identifier = row_factor;
+        for expression in 0..expression_count {
+            positions.insert(identifier, (expression, row));
+            identifier = identifier * coset_shift;
+        }
+        row_factor = row_factor * root_of_unity;
+    }
+    assert_eq!(
+        positions.len(),
+        expression_count * degree as usize,
+        "Cell identifiers are not unique."
+    );
+    Ok(positions)
+}
@@ -19,13 +19,32 @@ use super::*;
 
 impl<T: Display> Display for Analyzed<T> {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-        let degree = self.degree.unwrap_or_default();
-        let mut current_namespace = AbsoluteSymbolPath::default();
-        let mut update_namespace = |name: &str, f: &mut Formatter<'_>| {
+        let split_namespace = |name: &str| {
             let mut namespace =
                 AbsoluteSymbolPath::default().join(SymbolPath::from_str(name).unwrap());
             let name = namespace.pop().unwrap();
+            (namespace, name)
+        };
+        // Namespaces without polynomials get the common or the largest degree.
+        let default_degree = self
+            .degree
+            .or_else(|| self.degrees().last().copied())
+            .unwrap_or_default();
+        let namespace_degrees = self
+            .definitions
+            .values()
+            .map(|(symbol, _)| symbol)
+            .chain(self.intermediate_columns.values().map(|(symbol, _)| symbol))
+            .filter_map(|symbol| Some((split_namespace(&symbol.absolute_name).0, symbol.degree?)))
+            .collect::<BTreeMap<_, _>>();
+        let mut current_namespace = AbsoluteSymbolPath::default();
+        let mut update_namespace = |name: &str, f: &mut Formatter<'_>| {
+            let (namespace, name) = split_namespace(name);
             if namespace != current_namespace {
+                let degree = namespace_degrees
+                    .get(&namespace)
+                    .copied()
+                    .unwrap_or(default_degree);
                 current_namespace = namespace;
                 writeln!(
                     f,
@@ -52,7 +71,10 @@ impl<T: Display> Display for Analyzed<T> {
                                 if let Some(length) = symbol.length {
                                     if let PolynomialType::Committed = poly_type {
                                         write!(f, "[{length}]")?;
-                                        assert!(definition.is_none());
+                                        assert!(matches!(
+                                            definition,
+                                            None | Some(FunctionValueDefinition::Query(_))
+                                        ));
                                     } else {
                                         // Do not print an array size, because we will do it as part of the type.
                                         assert!(matches!(
@@ -160,7 +182,10 @@ fn format_outer_function<T: Display>(
 ) -> Result {
     let q = qualifier.map(|s| format!(" {s}")).unwrap_or_default();
     match e {
-        parsed::Expression::LambdaExpression(lambda) if lambda.params.len() == 1 => {
+        // Queries of column arrays also receive the index in the array.
+        parsed::Expression::LambdaExpression(lambda)
+            if lambda.params.len() == 1 || qualifier == Some("query") =>
+        {
             let body = if q.is_empty() {
                 format!("{{ {} }}", lambda.body)
             } else {
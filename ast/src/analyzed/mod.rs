@@ -1,8 +1,10 @@
+pub mod connect;
 mod display;
 pub mod types;
 pub mod visitor;
 
 use core::hash::Hash;
+use itertools::Itertools;
 use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fmt::Display;
 use std::ops::{self, ControlFlow};
@@ -31,7 +33,8 @@ pub enum StatementIdentifier {
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Analyzed<T> {
-    /// The degree of all namespaces, which must match. If there are no namespaces, then `None`.
+    /// The degree of all namespaces. If there are no namespaces or if they
+    /// have different degrees, then `None`.
     pub degree: Option<DegreeType>,
     pub definitions: HashMap<String, (Symbol, Option<FunctionValueDefinition<T>>)>,
     pub public_declarations: HashMap<String, PublicDeclaration>,
@@ -43,9 +46,72 @@ pub struct Analyzed<T> {
 }
 
 impl<T> Analyzed<T> {
-    /// @returns the degree if any. Panics if there is none.
+    /// @returns the degree if any. Panics if there is none or if the
+    /// namespaces have different degrees.
     pub fn degree(&self) -> DegreeType {
-        self.degree.unwrap()
+        self.degree.unwrap_or_else(|| {
+            let degrees = self.degrees();
+            assert!(
+                degrees.len() <= 1,
+                "Expected all namespaces to have the same degree, but found degrees {}.",
+                degrees.iter().format(", ")
+            );
+            panic!("No degree declared.")
+        })
+    }
+    /// Sets the degree of all namespaces to `degree` and resizes the repeated
+    /// parts of fixed columns defined by arrays accordingly.
+    /// Fails without modifying anything if an array does not fit the new degree.
+    pub fn set_degree(&mut self, degree: DegreeType) -> Result<(), String> {
+        for (symbol, value) in self.definitions.values() {
+            if let (Some(_), Some(FunctionValueDefinition::Array(items))) = (symbol.degree, value) {
+                let fixed_size = items
+                    .iter()
+                    .filter(|item| !item.is_repeated())
+                    .map(|item| item.size())
+                    .sum::<DegreeType>();
+                let has_repeated_part = items.iter().any(|item| item.is_repeated());
+                if fixed_size > degree || (!has_repeated_part && fixed_size != degree) {
+                    return Err(format!(
+                        "Fixed column {} has {fixed_size} fixed values, which do not fit degree {degree}.",
+                        symbol.absolute_name
+                    ));
+                }
+            }
+        }
+
+        self.degree = Some(degree);
+        for (symbol, value) in self.definitions.values_mut() {
+            if symbol.degree.is_none() {
+                continue;
+            }
+            symbol.degree = Some(degree);
+            if let Some(FunctionValueDefinition::Array(items)) = value {
+                let fixed_size = items
+                    .iter()
+                    .filter(|item| !item.is_repeated())
+                    .map(|item| item.size())
+                    .sum::<DegreeType>();
+                if let Some(item) = items.iter_mut().find(|item| item.is_repeated()) {
+                    item.size = degree - fixed_size;
+                }
+            }
+        }
+        for (symbol, _) in self.intermediate_columns.values_mut() {
+            if symbol.degree.is_some() {
+                symbol.degree = Some(degree);
+            }
+        }
+        Ok(())
+    }
+    /// @returns the distinct degrees of all polynomials, in ascending order.
+    pub fn degrees(&self) -> BTreeSet<DegreeType> {
+        self.definitions
+            .values()
+            .map(|(symbol, _)| symbol)
+            .chain(self.intermediate_columns.values().map(|(symbol, _)| symbol))
+            .filter_map(|symbol| symbol.degree)
+            .collect()
     }
     /// @returns the number of committed polynomials (with multiplicities for arrays)
     pub fn commitment_count(&self) -> usize {
@@ -425,6 +491,8 @@ pub struct Symbol {
     pub absolute_name: String,
     pub kind: SymbolKind,
     pub length: Option<DegreeType>,
+    /// The degree of the namespace the symbol is declared in, if any.
+    pub degree: Option<DegreeType>,
 }
 
 impl Symbol {
@@ -19,7 +19,8 @@ use super::{
     DebugDirective, DegreeStatement, FunctionBody, FunctionStatement, FunctionStatements,
     Incompatible, IncompatibleSet, Instruction, InstructionDefinitionStatement,
     InstructionStatement, Item, LabelStatement, LinkDefinitionStatement, Machine,
-    RegisterDeclarationStatement, RegisterTy, Return, Rom, SubmachineDeclaration,
+    PcOverrideStatement, RegisterDeclarationStatement, RegisterTy, Return, Rom,
+    SubmachineDeclaration,
 };
 
 impl<T: Display> Display for AnalysisASMFile<T> {
@@ -82,6 +83,7 @@ impl<T: Display> Display for Machine<T> {
         write_items_indented(f, &self.degree)?;
         write_items_indented(f, &self.submachines)?;
         write_items_indented(f, &self.registers)?;
+        write_items_indented(f, &self.pc_override)?;
         write_items_indented(f, &self.instructions)?;
         write_items_indented(f, &self.callable)?;
         write_items_indented(f, &self.pil)?;
@@ -123,6 +125,12 @@ impl Display for DegreeStatement {
     }
 }
 
+impl Display for PcOverrideStatement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "override_pc {} => {};", self.flag, self.target)
+    }
+}
+
 impl<T: Display> Display for FunctionStatement<T> {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         match self {
@@ -532,6 +532,15 @@ pub struct DegreeStatement {
     pub degree: BigUint,
 }
 
+#[derive(Clone, Debug)]
+pub struct PcOverrideStatement {
+    pub source: SourceRef,
+    /// The boolean column which, when set, overrides the update of the pc
+    pub flag: String,
+    /// The value the pc is set to when the flag is set
+    pub target: String,
+}
+
 #[derive(Clone, Debug)]
 pub enum FunctionStatement<T> {
     Assignment(AssignmentStatement<T>),
@@ -696,6 +705,8 @@ pub struct Machine<T> {
     pub registers: Vec<RegisterDeclarationStatement>,
     /// The index of the program counter in the registers, if any
     pub pc: Option<usize>,
+    /// The override of the program counter update, if any
+    pub pc_override: Option<PcOverrideStatement>,
     /// The set of pil statements
     pub pil: Vec<PilStatement<T>>,
     /// The set of instructions which can be invoked in functions
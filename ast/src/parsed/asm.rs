@@ -381,6 +381,7 @@ impl<T: Clone> Machine<T> {
             MachineStatement::RegisterDeclaration(_, name, _) => Box::new(once(name)),
             MachineStatement::Pil(_, statement) => statement.symbol_definition_names(),
             MachineStatement::Degree(_, _)
+            | MachineStatement::PcOverride(_, _, _)
             | MachineStatement::Submachine(_, _, _)
             | MachineStatement::InstructionDeclaration(_, _, _)
             | MachineStatement::LinkDeclaration(_, _)
@@ -448,6 +449,9 @@ pub enum MachineStatement<T> {
     Pil(SourceRef, PilStatement<T>),
     Submachine(SourceRef, SymbolPath, String),
     RegisterDeclaration(SourceRef, String, Option<RegisterFlag>),
+    /// Lets the prover override the update of the pc: in rows where the flag
+    /// (first name) is set, the pc is set to the target (second name).
+    PcOverride(SourceRef, String, String),
     InstructionDeclaration(SourceRef, String, Instruction<T>),
     LinkDeclaration(SourceRef, LinkDeclaration<T>),
     FunctionDeclaration(SourceRef, String, Params<T>, Vec<FunctionStatement<T>>),
@@ -173,6 +173,9 @@ impl<T: Display> Display for MachineStatement<T> {
                     .map(|flag| format!("[{flag}]"))
                     .unwrap_or_default()
             ),
+            MachineStatement::PcOverride(_, flag, target) => {
+                write!(f, "override_pc {flag} => {target};")
+            }
             MachineStatement::InstructionDeclaration(_, name, instruction) => {
                 write!(f, "instr {}{}", name, instruction)
             }
@@ -3,7 +3,7 @@ use std::{
     path::Path,
 };
 
-use crate::{Backend, BackendFactory, Error, Proof};
+use crate::{ensure_single_degree, Backend, BackendFactory, Error, Proof};
 use powdr_ast::analyzed::Analyzed;
 use powdr_halo2::{generate_setup, Halo2Prover, Params};
 use powdr_number::{DegreeType, FieldElement};
@@ -19,6 +19,7 @@ impl<F: FieldElement> BackendFactory<F> for Halo2ProverFactory {
         setup: Option<&mut dyn io::Read>,
         verification_key: Option<&mut dyn io::Read>,
     ) -> Result<Box<dyn crate::Backend<'a, F> + 'a>, Error> {
+        ensure_single_degree(pil)?;
         let mut halo2 = Box::new(Halo2Prover::new(pil, fixed, setup)?);
         if let Some(vk) = verification_key {
             halo2.add_verification_key(vk);
@@ -77,6 +78,7 @@ impl<F: FieldElement> BackendFactory<F> for Halo2MockFactory {
         setup: Option<&mut dyn io::Read>,
         verification_key: Option<&mut dyn io::Read>,
     ) -> Result<Box<dyn crate::Backend<'a, F> + 'a>, Error> {
+        ensure_single_degree(pil)?;
         if setup.is_some() {
             return Err(Error::NoSetupAvailable);
         }
@@ -102,7 +104,7 @@ impl<'a, T: FieldElement> Backend<'a, T> for Halo2Mock<'a, T> {
             return Err(Error::NoAggregationAvailable);
         }
 
-        powdr_halo2::mock_prove(self.pil, self.fixed, witness);
+        powdr_halo2::mock_prove(self.pil, self.fixed, witness)?;
 
         Ok(vec![])
     }
@@ -2,8 +2,12 @@
 
 #[cfg(feature = "halo2")]
 mod halo2_impl;
+mod pil_verify;
 mod pilstark;
+#[cfg(feature = "plonky3")]
+mod plonky3_impl;
 
+use itertools::Itertools;
 use powdr_ast::analyzed::Analyzed;
 use powdr_number::{DegreeType, FieldElement};
 use std::{io, path::Path};
@@ -21,6 +25,11 @@ pub enum BackendType {
     EStark,
     #[strum(serialize = "pil-stark-cli")]
     PilStarkCli,
+    #[strum(serialize = "pil-verify")]
+    PilVerify,
+    #[cfg(feature = "plonky3")]
+    #[strum(serialize = "plonky3")]
+    Plonky3,
 }
 
 impl BackendType {
@@ -31,6 +40,10 @@ impl BackendType {
         const HALO2_MOCK_FACTORY: halo2_impl::Halo2MockFactory = halo2_impl::Halo2MockFactory;
         const ESTARK_FACTORY: pilstark::estark::EStarkFactory = pilstark::estark::EStarkFactory;
         const PIL_STARK_CLI_FACTORY: pilstark::PilStarkCliFactory = pilstark::PilStarkCliFactory;
+        const PIL_VERIFY_FACTORY: pil_verify::PilVerifyFactory = pil_verify::PilVerifyFactory;
+        #[cfg(feature = "plonky3")]
+        const PLONKY3_FACTORY: plonky3_impl::Plonky3ProverFactory =
+            plonky3_impl::Plonky3ProverFactory;
 
         match self {
             #[cfg(feature = "halo2")]
@@ -39,6 +52,9 @@ impl BackendType {
             BackendType::Halo2Mock => &HALO2_MOCK_FACTORY,
             BackendType::EStark => &ESTARK_FACTORY,
             BackendType::PilStarkCli => &PIL_STARK_CLI_FACTORY,
+            BackendType::PilVerify => &PIL_VERIFY_FACTORY,
+            #[cfg(feature = "plonky3")]
+            BackendType::Plonky3 => &PLONKY3_FACTORY,
         }
     }
 }
@@ -55,7 +71,9 @@ pub enum Error {
     NoVerificationAvailable,
     #[error("the backend does not support proof aggregation")]
     NoAggregationAvailable,
-    #[error("internal backend error")]
+    #[error("the backend requires all namespaces to have the same degree, but found degrees {0}")]
+    DifferentDegrees(String),
+    #[error("internal backend error: {0}")]
     BackendError(String),
 }
 
@@ -67,6 +85,16 @@ impl From<String> for Error {
 
 pub type Proof = Vec<u8>;
 
+/// Fails if the namespaces of the PIL have different degrees, which backends
+/// that prove a single AIR do not support.
+pub(crate) fn ensure_single_degree<F>(pil: &Analyzed<F>) -> Result<(), Error> {
+    let degrees = pil.degrees();
+    if degrees.len() > 1 {
+        return Err(Error::DifferentDegrees(degrees.iter().join(", ")));
+    }
+    Ok(())
+}
+
 /*
     Bellow are the public interface traits. They are implemented in this
     module, wrapping the traits implemented by each backend.
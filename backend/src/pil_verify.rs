@@ -0,0 +1,57 @@
+use std::{io, path::Path};
+
+use crate::{Backend, BackendFactory, Error, Proof};
+use powdr_ast::analyzed::Analyzed;
+use powdr_executor::constraint_checker::check_constraints;
+use powdr_number::FieldElement;
+
+/// A backend that checks the witness against all identities in-process,
+/// without generating an actual proof.
+pub(crate) struct PilVerifyFactory;
+
+impl<F: FieldElement> BackendFactory<F> for PilVerifyFactory {
+    fn create<'a>(
+        &self,
+        pil: &'a Analyzed<F>,
+        fixed: &'a [(String, Vec<F>)],
+        _output_dir: Option<&'a Path>,
+        setup: Option<&mut dyn io::Read>,
+        verification_key: Option<&mut dyn io::Read>,
+    ) -> Result<Box<dyn crate::Backend<'a, F> + 'a>, Error> {
+        if setup.is_some() {
+            return Err(Error::NoSetupAvailable);
+        }
+        if verification_key.is_some() {
+            return Err(Error::NoVerificationAvailable);
+        }
+        Ok(Box::new(PilVerify { pil, fixed }))
+    }
+}
+
+pub struct PilVerify<'a, F: FieldElement> {
+    pil: &'a Analyzed<F>,
+    fixed: &'a [(String, Vec<F>)],
+}
+
+impl<'a, T: FieldElement> Backend<'a, T> for PilVerify<'a, T> {
+    fn prove(
+        &self,
+        witness: &[(String, Vec<T>)],
+        prev_proof: Option<Proof>,
+    ) -> Result<Proof, Error> {
+        if prev_proof.is_some() {
+            return Err(Error::NoAggregationAvailable);
+        }
+        if witness.is_empty() && self.pil.commitment_count() > 0 {
+            return Err(Error::EmptyWitness);
+        }
+
+        log::info!("Checking constraints...");
+        check_constraints(self.pil, self.fixed, witness).map_err(|e| {
+            Error::BackendError(format!("Constraints are not satisfied:\n{e}"))
+        })?;
+        log::info!("All constraints are satisfied.");
+
+        Ok(vec![])
+    }
+}
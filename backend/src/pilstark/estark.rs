@@ -1,16 +1,20 @@
 use std::iter::{once, repeat};
 use std::time::Instant;
 
-use crate::{pilstark, Backend, BackendFactory, Error};
+use crate::{ensure_single_degree, pilstark, Backend, BackendFactory, Error};
 use powdr_ast::analyzed::Analyzed;
 use powdr_number::{BigInt, DegreeType, FieldElement, GoldilocksField};
 
 use starky::{
+    digest::ElementDigest,
+    field_gl::Fr as FGL,
     merklehash::MerkleTreeGL,
     polsarray::{PolKind, PolsArray},
     stark_gen::StarkProof,
     stark_setup::StarkSetup,
     stark_verify::stark_verify,
+    starkinfo::StarkInfo,
+    starkinfo_codegen::Program,
     transcript::TranscriptGL,
     types::{StarkStruct, Step, PIL},
 };
@@ -31,12 +35,17 @@ impl<F: FieldElement> BackendFactory<F> for EStarkFactory {
             unimplemented!("eSTARK is only implemented for Goldilocks field");
         }
 
+        ensure_single_degree(pil)?;
         if setup.is_some() {
             return Err(Error::NoSetupAvailable);
         }
-        if verification_key.is_some() {
-            return Err(Error::NoVerificationAvailable);
-        }
+        let verification_key = verification_key
+            .map(|vkey| {
+                serde_json::from_reader(vkey).map_err(|e| {
+                    Error::BackendError(format!("Could not read verification key: {e}"))
+                })
+            })
+            .transpose()?;
 
         let degree = pil.degree();
         assert!(degree > 1);
@@ -59,6 +68,7 @@ impl<F: FieldElement> BackendFactory<F> for EStarkFactory {
                 verificationHashType: "GL".to_owned(),
                 steps,
             },
+            verification_key,
         }))
     }
 }
@@ -67,23 +77,15 @@ pub struct EStark<'a, F: FieldElement> {
     pil: &'a Analyzed<F>,
     fixed: &'a [(String, Vec<F>)],
     params: StarkStruct,
+    /// The root of the constant tree. If not given, it is computed from the
+    /// fixed columns.
+    verification_key: Option<ElementDigest<4>>,
 }
 
-impl<'a, F: FieldElement> Backend<'a, F> for EStark<'a, F> {
-    fn prove(
-        &self,
-        witness: &[(String, Vec<F>)],
-        prev_proof: Option<crate::Proof>,
-    ) -> Result<crate::Proof, Error> {
-        if prev_proof.is_some() {
-            return Err(Error::NoAggregationAvailable);
-        }
-        if witness.is_empty() {
-            return Err(Error::EmptyWitness);
-        }
-
-        log::info!("Creating eSTARK proof.");
-
+impl<'a, F: FieldElement> EStark<'a, F> {
+    /// Returns the starky PIL and the fixed columns, including the
+    /// `main.first_step` column starky needs.
+    fn starky_pil(&self) -> (PIL, Vec<(String, Vec<F>)>) {
         let degree = self.pil.degree();
 
         let mut pil: PIL = pilstark::json_exporter::export(self.pil);
@@ -120,10 +122,16 @@ impl<'a, F: FieldElement> Backend<'a, F> for EStark<'a, F> {
             ));
         }
 
+        (pil, fixed)
+    }
+
+    /// Returns the starky PIL, the constant polynomials and the setup, which
+    /// contains the constant tree.
+    fn setup(&self) -> (PIL, PolsArray, StarkSetup<MerkleTreeGL>) {
+        let (mut pil, fixed) = self.starky_pil();
         let const_pols = to_starky_pols_array(&fixed, &pil, PolKind::Constant);
-        let cm_pols = to_starky_pols_array(witness, &pil, PolKind::Commit);
 
-        let mut setup = StarkSetup::<MerkleTreeGL>::new(
+        let setup = StarkSetup::<MerkleTreeGL>::new(
             &const_pols,
             &mut pil,
             &self.params,
@@ -131,6 +139,50 @@ impl<'a, F: FieldElement> Backend<'a, F> for EStark<'a, F> {
         )
         .unwrap();
 
+        (pil, const_pols, setup)
+    }
+
+    fn verify_proof(
+        &self,
+        proof: &StarkProof<MerkleTreeGL>,
+        const_root: &ElementDigest<4>,
+        starkinfo: &StarkInfo,
+        program: &mut Program,
+    ) -> Result<(), Error> {
+        let valid = stark_verify::<MerkleTreeGL, TranscriptGL>(
+            proof,
+            const_root,
+            starkinfo,
+            &self.params,
+            program,
+        )
+        .map_err(|e| Error::BackendError(format!("Proof verification failed: {e}")))?;
+        if valid {
+            Ok(())
+        } else {
+            Err(Error::BackendError("Proof is invalid.".to_string()))
+        }
+    }
+}
+
+impl<'a, F: FieldElement> Backend<'a, F> for EStark<'a, F> {
+    fn prove(
+        &self,
+        witness: &[(String, Vec<F>)],
+        prev_proof: Option<crate::Proof>,
+    ) -> Result<crate::Proof, Error> {
+        if prev_proof.is_some() {
+            return Err(Error::NoAggregationAvailable);
+        }
+        if witness.is_empty() {
+            return Err(Error::EmptyWitness);
+        }
+
+        log::info!("Creating eSTARK proof.");
+
+        let (pil, const_pols, mut setup) = self.setup();
+        let cm_pols = to_starky_pols_array(witness, &pil, PolKind::Commit);
+
         let start = Instant::now();
         let starkproof = StarkProof::<MerkleTreeGL>::stark_gen::<TranscriptGL>(
             cm_pols,
@@ -147,17 +199,64 @@ impl<'a, F: FieldElement> Backend<'a, F> for EStark<'a, F> {
 
         log::info!("Proof done in: {:?}", duration);
 
-        assert!(stark_verify::<MerkleTreeGL, TranscriptGL>(
+        let const_root = self.verification_key.unwrap_or(setup.const_root);
+        self.verify_proof(
             &starkproof,
-            &setup.const_root,
+            &const_root,
             &setup.starkinfo,
-            &self.params,
             &mut setup.program,
-        )
-        .unwrap());
+        )?;
 
         Ok(serde_json::to_vec(&starkproof).unwrap())
     }
+
+    /// Verifies the proof against the constant tree root. The instances have to
+    /// match the public values of the proof, in declaration order. If a
+    /// verification key is given, the constant tree is not recomputed.
+    fn verify(&self, proof: &crate::Proof, instances: &[Vec<F>]) -> Result<(), Error> {
+        let proof: StarkProof<MerkleTreeGL> = serde_json::from_slice(proof)
+            .map_err(|e| Error::BackendError(format!("Could not read proof: {e}")))?;
+
+        let publics = instances
+            .iter()
+            .flatten()
+            .map(|v| FGL::from(v.to_degree()))
+            .collect::<Vec<_>>();
+        if publics != proof.publics {
+            return Err(Error::BackendError(format!(
+                "The {} given public values do not match the {} public values of the proof.",
+                publics.len(),
+                proof.publics.len()
+            )));
+        }
+
+        match &self.verification_key {
+            Some(const_root) => {
+                let (mut pil, _) = self.starky_pil();
+                let (starkinfo, mut program) = StarkInfo::new(&mut pil, &self.params)
+                    .map_err(|e| Error::BackendError(format!("Could not build starkinfo: {e}")))?;
+                self.verify_proof(&proof, const_root, &starkinfo, &mut program)
+            }
+            None => {
+                let (_, _, mut setup) = self.setup();
+                self.verify_proof(
+                    &proof,
+                    &setup.const_root,
+                    &setup.starkinfo,
+                    &mut setup.program,
+                )
+            }
+        }
+    }
+
+    fn export_verification_key(&self, output: &mut dyn std::io::Write) -> Result<(), Error> {
+        let const_root = match &self.verification_key {
+            Some(const_root) => *const_root,
+            None => self.setup().2.const_root,
+        };
+        serde_json::to_writer(output, &const_root)
+            .map_err(|e| Error::BackendError(format!("Could not write verification key: {e}")))
+    }
 }
 
 fn to_starky_pols_array<F: FieldElement>(
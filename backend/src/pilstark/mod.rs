@@ -7,7 +7,7 @@ use std::{
     path::Path,
 };
 
-use crate::{Backend, BackendFactory, Error, Proof};
+use crate::{ensure_single_degree, Backend, BackendFactory, Error, Proof};
 use powdr_ast::analyzed::Analyzed;
 use powdr_number::FieldElement;
 
@@ -22,6 +22,7 @@ impl<F: FieldElement> BackendFactory<F> for PilStarkCliFactory {
         setup: Option<&mut dyn std::io::Read>,
         verification_key: Option<&mut dyn std::io::Read>,
     ) -> Result<Box<dyn crate::Backend<'a, F> + 'a>, Error> {
+        ensure_single_degree(analyzed)?;
         if setup.is_some() {
             return Err(Error::NoSetupAvailable);
         }
@@ -0,0 +1,59 @@
+use std::{io, path::Path};
+
+use powdr_ast::analyzed::Analyzed;
+use powdr_number::FieldElement;
+use powdr_plonky3::Plonky3Prover;
+
+use crate::{ensure_single_degree, Backend, BackendFactory, Error, Proof};
+
+pub(crate) struct Plonky3ProverFactory;
+
+impl<F: FieldElement> BackendFactory<F> for Plonky3ProverFactory {
+    fn create<'a>(
+        &self,
+        pil: &'a Analyzed<F>,
+        fixed: &'a [(String, Vec<F>)],
+        _output_dir: Option<&'a Path>,
+        setup: Option<&mut dyn io::Read>,
+        verification_key: Option<&mut dyn io::Read>,
+    ) -> Result<Box<dyn crate::Backend<'a, F> + 'a>, Error> {
+        ensure_single_degree(pil)?;
+        if setup.is_some() {
+            return Err(Error::NoSetupAvailable);
+        }
+        Ok(Box::new(match verification_key {
+            Some(verification_key) => {
+                Plonky3Prover::new_with_verification_key(pil, fixed, verification_key)?
+            }
+            None => Plonky3Prover::new(pil, fixed)?,
+        }))
+    }
+}
+
+impl From<powdr_plonky3::Error> for Error {
+    fn from(e: powdr_plonky3::Error) -> Self {
+        Error::BackendError(e.to_string())
+    }
+}
+
+impl<'a, T: FieldElement> Backend<'a, T> for Plonky3Prover<'a, T> {
+    fn verify(&self, proof: &Proof, instances: &[Vec<T>]) -> Result<(), Error> {
+        Ok(self.verify(proof, instances)?)
+    }
+
+    fn prove(
+        &self,
+        witness: &[(String, Vec<T>)],
+        prev_proof: Option<Proof>,
+    ) -> Result<Proof, Error> {
+        if prev_proof.is_some() {
+            return Err(Error::NoAggregationAvailable);
+        }
+
+        Ok(self.prove(witness)?)
+    }
+
+    fn export_verification_key(&self, output: &mut dyn io::Write) -> Result<(), Error> {
+        Ok(self.export_verification_key(output)?)
+    }
+}
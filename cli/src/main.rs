@@ -2,18 +2,22 @@
 
 mod util;
 
-use clap::{CommandFactory, Parser, Subcommand};
+use clap::{Args, CommandFactory, Parser, Subcommand};
 use env_logger::fmt::Color;
 use env_logger::{Builder, Target};
 use log::LevelFilter;
 use powdr_backend::BackendType;
+use powdr_executor::witgen::WitgenError;
 use powdr_number::{read_polys_csv_file, CsvRenderMode};
 use powdr_number::{Bn254Field, FieldElement, GoldilocksField};
+use powdr_pilopt::unconstrained_columns::unconstrained_columns;
 use powdr_pipeline::util::write_or_panic;
-use powdr_pipeline::{Pipeline, Stage};
-use powdr_riscv::continuations::{rust_continuations, rust_continuations_dry_run};
-use powdr_riscv::{compile_riscv_asm, compile_rust};
+use powdr_pipeline::{LinkerMode, Pipeline, Stage};
+use powdr_riscv::continuations::{rust_continuations_dry_run, rust_continuations_parallel};
+use powdr_riscv::{compile_riscv_asm, compile_riscv_elf, compile_rust};
+use powdr_riscv_executor::profiler::ProfilerOptions;
 use std::io::{self, BufWriter};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
 use std::path::PathBuf;
 use std::{borrow::Cow, fs, io::Write, path::Path};
 use strum::{Display, EnumString, EnumVariantNames};
@@ -138,6 +142,35 @@ enum Commands {
         #[arg(short, long)]
         #[arg(default_value_t = false)]
         continuations: bool,
+
+        /// Number of continuation chunks to run in parallel
+        #[arg(long)]
+        #[arg(default_value_t = 1)]
+        #[arg(value_parser = parse_continuations_workers)]
+        continuations_workers: usize,
+
+        /// With --just-execute (and without continuations), write a folded-stack (flamegraph)
+        /// and a callgrind profile of the guest to the output directory
+        #[arg(long)]
+        #[arg(default_value_t = false)]
+        profile: bool,
+
+        /// With --just-execute (and without continuations), wait for GDB to connect
+        /// on this local port and let it control the execution
+        #[arg(long)]
+        gdb_port: Option<u16>,
+
+        /// How to link the machines of an .asm file: into a single AIR with a common degree,
+        /// or into one AIR per machine with its own degree
+        #[arg(long)]
+        #[arg(default_value_t = LinkerMode::Monolithic)]
+        #[arg(value_parser = clap_enum_variants!(LinkerMode))]
+        linker_mode: LinkerMode,
+
+        /// Generate the witness with this degree first and then use the smallest power of two
+        /// that fits the execution as degree, instead of the declared one
+        #[arg(long)]
+        max_degree: Option<u64>,
     },
     /// Compiles (no-std) rust code to riscv assembly, then to powdr assembly
     /// and finally to PIL and generates fixed and witness columns.
@@ -196,6 +229,23 @@ enum Commands {
         #[arg(short, long)]
         #[arg(default_value_t = false)]
         continuations: bool,
+
+        /// Number of continuation chunks to run in parallel
+        #[arg(long)]
+        #[arg(default_value_t = 1)]
+        #[arg(value_parser = parse_continuations_workers)]
+        continuations_workers: usize,
+
+        /// With --just-execute (and without continuations), write a folded-stack (flamegraph)
+        /// and a callgrind profile of the guest to the output directory
+        #[arg(long)]
+        #[arg(default_value_t = false)]
+        profile: bool,
+
+        /// With --just-execute (and without continuations), wait for GDB to connect
+        /// on this local port and let it control the execution
+        #[arg(long)]
+        gdb_port: Option<u16>,
     },
 
     /// Compiles riscv assembly to powdr assembly and then to PIL
@@ -205,56 +255,19 @@ enum Commands {
         #[arg(required = true)]
         files: Vec<String>,
 
-        /// The field to use
-        #[arg(long)]
-        #[arg(default_value_t = FieldArgument::Gl)]
-        #[arg(value_parser = clap_enum_variants!(FieldArgument))]
-        field: FieldArgument,
-
-        /// Comma-separated list of free inputs (numbers).
-        #[arg(short, long)]
-        #[arg(default_value_t = String::new())]
-        inputs: String,
-
-        /// Directory for output files.
-        #[arg(short, long)]
-        #[arg(default_value_t = String::from("."))]
-        output_directory: String,
-
-        /// Force overwriting of files in output directory.
-        #[arg(short, long)]
-        #[arg(default_value_t = false)]
-        force: bool,
-
-        /// Generate a proof with a given backend.
-        #[arg(short, long)]
-        #[arg(value_parser = clap_enum_variants!(BackendType))]
-        prove_with: Option<BackendType>,
-
-        /// Generate a CSV file containing the fixed and witness column values. Useful for debugging purposes.
-        #[arg(long)]
-        #[arg(default_value_t = false)]
-        export_csv: bool,
-
-        /// How to render field elements in the csv file
-        #[arg(long)]
-        #[arg(default_value_t = CsvRenderModeCLI::Hex)]
-        #[arg(value_parser = clap_enum_variants!(CsvRenderModeCLI))]
-        csv_mode: CsvRenderModeCLI,
-
-        /// Comma-separated list of coprocessors.
-        #[arg(long)]
-        coprocessors: Option<String>,
+        #[command(flatten)]
+        args: RiscvArgs,
+    },
 
-        /// Just execute in the RISCV/Powdr executor
-        #[arg(short, long)]
-        #[arg(default_value_t = false)]
-        just_execute: bool,
+    /// Compiles a RISC-V ELF executable to powdr assembly and then to PIL
+    /// and generates fixed and witness columns.
+    /// The executable should be linked with `--emit-relocs`.
+    RiscvElf {
+        /// Input file
+        file: String,
 
-        /// Run a long execution in chunks (Experimental and not sound!)
-        #[arg(short, long)]
-        #[arg(default_value_t = false)]
-        continuations: bool,
+        #[command(flatten)]
+        args: RiscvArgs,
     },
 
     Prove {
@@ -321,6 +334,11 @@ enum Commands {
         /// File containing the params.
         #[arg(long)]
         params: Option<String>,
+
+        /// Comma-separated list of public values (numbers) the proof has to attest to.
+        #[arg(long)]
+        #[arg(default_value_t = String::new())]
+        publics: String,
     },
 
     VerificationKey {
@@ -387,6 +405,92 @@ enum Commands {
         #[arg(value_parser = clap_enum_variants!(FieldArgument))]
         field: FieldArgument,
     },
+
+    /// Reports witness columns of the optimized PIL file that are likely under-constrained:
+    /// Columns that are not referenced by any identity and columns that only appear
+    /// linearly in a single identity with a non-constant coefficient.
+    UnconstrainedColumns {
+        /// Input file
+        file: String,
+
+        /// The field to use
+        #[arg(long)]
+        #[arg(default_value_t = FieldArgument::Gl)]
+        #[arg(value_parser = clap_enum_variants!(FieldArgument))]
+        field: FieldArgument,
+    },
+}
+
+/// The arguments shared by the commands that compile RISC-V code.
+#[derive(Args)]
+struct RiscvArgs {
+    /// The field to use
+    #[arg(long)]
+    #[arg(default_value_t = FieldArgument::Gl)]
+    #[arg(value_parser = clap_enum_variants!(FieldArgument))]
+    field: FieldArgument,
+
+    /// Comma-separated list of free inputs (numbers).
+    #[arg(short, long)]
+    #[arg(default_value_t = String::new())]
+    inputs: String,
+
+    /// Directory for output files.
+    #[arg(short, long)]
+    #[arg(default_value_t = String::from("."))]
+    output_directory: String,
+
+    /// Force overwriting of files in output directory.
+    #[arg(short, long)]
+    #[arg(default_value_t = false)]
+    force: bool,
+
+    /// Generate a proof with a given backend.
+    #[arg(short, long)]
+    #[arg(value_parser = clap_enum_variants!(BackendType))]
+    prove_with: Option<BackendType>,
+
+    /// Generate a CSV file containing the fixed and witness column values. Useful for debugging purposes.
+    #[arg(long)]
+    #[arg(default_value_t = false)]
+    export_csv: bool,
+
+    /// How to render field elements in the csv file
+    #[arg(long)]
+    #[arg(default_value_t = CsvRenderModeCLI::Hex)]
+    #[arg(value_parser = clap_enum_variants!(CsvRenderModeCLI))]
+    csv_mode: CsvRenderModeCLI,
+
+    /// Comma-separated list of coprocessors.
+    #[arg(long)]
+    coprocessors: Option<String>,
+
+    /// Just execute in the RISCV/Powdr executor
+    #[arg(short, long)]
+    #[arg(default_value_t = false)]
+    just_execute: bool,
+
+    /// Run a long execution in chunks (Experimental and not sound!)
+    #[arg(short, long)]
+    #[arg(default_value_t = false)]
+    continuations: bool,
+
+    /// Number of continuation chunks to run in parallel
+    #[arg(long)]
+    #[arg(default_value_t = 1)]
+    #[arg(value_parser = parse_continuations_workers)]
+    continuations_workers: usize,
+
+    /// With --just-execute (and without continuations), write a folded-stack (flamegraph)
+    /// and a callgrind profile of the guest to the output directory
+    #[arg(long)]
+    #[arg(default_value_t = false)]
+    profile: bool,
+
+    /// With --just-execute (and without continuations), wait for GDB to connect
+    /// on this local port and let it control the execution
+    #[arg(long)]
+    gdb_port: Option<u16>,
 }
 
 fn split_inputs<T: FieldElement>(inputs: &str) -> Vec<T> {
@@ -398,6 +502,15 @@ fn split_inputs<T: FieldElement>(inputs: &str) -> Vec<T> {
         .collect()
 }
 
+/// Parses the number of continuation workers, which has to be positive.
+fn parse_continuations_workers(workers: &str) -> Result<usize, String> {
+    match workers.parse() {
+        Ok(0) => Err("at least one worker is needed".to_string()),
+        Ok(workers) => Ok(workers),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
 fn main() -> Result<(), io::Error> {
     let mut builder = Builder::new();
     builder
@@ -453,6 +566,9 @@ fn run_command(command: Commands) {
             coprocessors,
             just_execute,
             continuations,
+            continuations_workers,
+            profile,
+            gdb_port,
         } => {
             let coprocessors = match coprocessors {
                 Some(list) => {
@@ -471,21 +587,30 @@ fn run_command(command: Commands) {
                 csv_mode,
                 coprocessors,
                 just_execute,
-                continuations
+                continuations,
+                continuations_workers,
+                profile,
+                gdb_port
             ))
         }
         Commands::RiscvAsm {
             files,
-            field,
-            inputs,
-            output_directory,
-            force,
-            prove_with,
-            export_csv,
-            csv_mode,
-            coprocessors,
-            just_execute,
-            continuations,
+            args:
+                RiscvArgs {
+                    field,
+                    inputs,
+                    output_directory,
+                    force,
+                    prove_with,
+                    export_csv,
+                    csv_mode,
+                    coprocessors,
+                    just_execute,
+                    continuations,
+                    continuations_workers,
+                    profile,
+                    gdb_port,
+                },
         } => {
             assert!(!files.is_empty());
             let name = if files.len() == 1 {
@@ -512,7 +637,52 @@ fn run_command(command: Commands) {
                 csv_mode,
                 coprocessors,
                 just_execute,
-                continuations
+                continuations,
+                continuations_workers,
+                profile,
+                gdb_port
+            ))
+        }
+        Commands::RiscvElf {
+            file,
+            args:
+                RiscvArgs {
+                    field,
+                    inputs,
+                    output_directory,
+                    force,
+                    prove_with,
+                    export_csv,
+                    csv_mode,
+                    coprocessors,
+                    just_execute,
+                    continuations,
+                    continuations_workers,
+                    profile,
+                    gdb_port,
+                },
+        } => {
+            let coprocessors = match coprocessors {
+                Some(list) => {
+                    powdr_riscv::CoProcessors::try_from(list.split(',').collect::<Vec<_>>())
+                        .unwrap()
+                }
+                None => powdr_riscv::CoProcessors::base(),
+            };
+            call_with_field!(run_riscv_elf::<field>(
+                &file,
+                split_inputs(&inputs),
+                Path::new(&output_directory),
+                force,
+                prove_with,
+                export_csv,
+                csv_mode,
+                coprocessors,
+                just_execute,
+                continuations,
+                continuations_workers,
+                profile,
+                gdb_port
             ))
         }
         Commands::Reformat { file } => {
@@ -527,6 +697,9 @@ fn run_command(command: Commands) {
             call_with_field!(optimize_and_output::<field>(&file));
             Ok(())
         }
+        Commands::UnconstrainedColumns { file, field } => {
+            call_with_field!(report_unconstrained_columns::<field>(&file))
+        }
         Commands::Pil {
             file,
             field,
@@ -539,6 +712,11 @@ fn run_command(command: Commands) {
             csv_mode,
             just_execute,
             continuations,
+            continuations_workers,
+            profile,
+            gdb_port,
+            linker_mode,
+            max_degree,
         } => {
             call_with_field!(run_pil::<field>(
                 file,
@@ -550,7 +728,12 @@ fn run_command(command: Commands) {
                 export_csv,
                 csv_mode,
                 just_execute,
-                continuations
+                continuations,
+                continuations_workers,
+                profile,
+                gdb_port,
+                linker_mode,
+                max_degree
             ))
         }
         Commands::Prove {
@@ -576,11 +759,12 @@ fn run_command(command: Commands) {
             proof,
             params,
             vkey,
+            publics,
         } => {
             let pil = Path::new(&file);
             let dir = Path::new(&dir);
             call_with_field!(read_and_verify::<field>(
-                pil, dir, &backend, proof, params, vkey
+                pil, dir, &backend, proof, params, vkey, publics
             ))
         }
         Commands::VerificationKey {
@@ -656,6 +840,9 @@ fn run_rust<F: FieldElement>(
     coprocessors: powdr_riscv::CoProcessors,
     just_execute: bool,
     continuations: bool,
+    continuations_workers: usize,
+    profile: bool,
+    gdb_port: Option<u16>,
 ) -> Result<(), Vec<String>> {
     let (asm_file_path, asm_contents) = compile_rust(
         file_name,
@@ -680,7 +867,16 @@ fn run_rust<F: FieldElement>(
         export_csv,
         csv_mode,
     );
-    run(pipeline, inputs, prove_with, just_execute, continuations)?;
+    run(
+        pipeline,
+        inputs,
+        prove_with,
+        just_execute,
+        continuations,
+        continuations_workers,
+        profile,
+        gdb_port,
+    )?;
     Ok(())
 }
 
@@ -697,6 +893,9 @@ fn run_riscv_asm<F: FieldElement>(
     coprocessors: powdr_riscv::CoProcessors,
     just_execute: bool,
     continuations: bool,
+    continuations_workers: usize,
+    profile: bool,
+    gdb_port: Option<u16>,
 ) -> Result<(), Vec<String>> {
     let (asm_file_path, asm_contents) = compile_riscv_asm(
         original_file_name,
@@ -722,7 +921,69 @@ fn run_riscv_asm<F: FieldElement>(
         export_csv,
         csv_mode,
     );
-    run(pipeline, inputs, prove_with, just_execute, continuations)?;
+    run(
+        pipeline,
+        inputs,
+        prove_with,
+        just_execute,
+        continuations,
+        continuations_workers,
+        profile,
+        gdb_port,
+    )?;
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run_riscv_elf<F: FieldElement>(
+    file_name: &str,
+    inputs: Vec<F>,
+    output_dir: &Path,
+    force_overwrite: bool,
+    prove_with: Option<BackendType>,
+    export_csv: bool,
+    csv_mode: CsvRenderModeCLI,
+    coprocessors: powdr_riscv::CoProcessors,
+    just_execute: bool,
+    continuations: bool,
+    continuations_workers: usize,
+    profile: bool,
+    gdb_port: Option<u16>,
+) -> Result<(), Vec<String>> {
+    let (asm_file_path, asm_contents) = compile_riscv_elf(
+        file_name,
+        output_dir,
+        force_overwrite,
+        &coprocessors,
+        continuations,
+    )
+    .map_err(|e| vec![e])?
+    .ok_or_else(|| vec!["could not compile RISC-V ELF file".to_string()])?;
+
+    let pipeline = Pipeline::<F>::default().from_asm_string(
+        asm_contents.clone(),
+        Some(PathBuf::from(asm_file_path.to_str().unwrap())),
+    );
+
+    let pipeline = bind_cli_args(
+        pipeline,
+        inputs.clone(),
+        output_dir.to_path_buf(),
+        force_overwrite,
+        None,
+        export_csv,
+        csv_mode,
+    );
+    run(
+        pipeline,
+        inputs,
+        prove_with,
+        just_execute,
+        continuations,
+        continuations_workers,
+        profile,
+        gdb_port,
+    )?;
     Ok(())
 }
 
@@ -738,11 +999,19 @@ fn run_pil<F: FieldElement>(
     csv_mode: CsvRenderModeCLI,
     just_execute: bool,
     continuations: bool,
+    continuations_workers: usize,
+    profile: bool,
+    gdb_port: Option<u16>,
+    linker_mode: LinkerMode,
+    max_degree: Option<u64>,
 ) -> Result<(), Vec<String>> {
     let inputs = split_inputs::<F>(&inputs);
 
     let pipeline = bind_cli_args(
-        Pipeline::<F>::default().from_file(PathBuf::from(&file)),
+        Pipeline::<F>::default()
+            .from_file(PathBuf::from(&file))
+            .with_linker_mode(linker_mode)
+            .with_max_degree(max_degree),
         inputs.clone(),
         PathBuf::from(output_directory),
         force,
@@ -750,16 +1019,29 @@ fn run_pil<F: FieldElement>(
         export_csv,
         csv_mode,
     );
-    run(pipeline, inputs, prove_with, just_execute, continuations)?;
+    run(
+        pipeline,
+        inputs,
+        prove_with,
+        just_execute,
+        continuations,
+        continuations_workers,
+        profile,
+        gdb_port,
+    )?;
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 fn run<F: FieldElement>(
     mut pipeline: Pipeline<F>,
     inputs: Vec<F>,
     prove_with: Option<BackendType>,
     just_execute: bool,
     continuations: bool,
+    continuations_workers: usize,
+    profile: bool,
+    gdb_port: Option<u16>,
 ) -> Result<(), Vec<String>> {
     let bootloader_inputs = if continuations {
         pipeline = pipeline.with_prover_inputs(inputs.clone());
@@ -769,7 +1051,12 @@ fn run<F: FieldElement>(
     };
 
     let generate_witness_and_prove_maybe = |mut pipeline: Pipeline<F>| -> Result<(), Vec<String>> {
-        pipeline.advance_to(Stage::GeneratedWitness)?;
+        pipeline
+            .advance_to(Stage::GeneratedWitness)
+            .map_err(|errors| match pipeline.witgen_error() {
+                Some(error) => witgen_error_report(error),
+                None => errors,
+            })?;
         prove_with.map(|backend| pipeline.with_backend(backend).proof().unwrap());
         Ok(())
     };
@@ -782,18 +1069,34 @@ fn run<F: FieldElement>(
             let mut pipeline = pipeline.with_prover_inputs(inputs);
             pipeline.advance_to(Stage::AsmString).unwrap();
             let program = pipeline.artifact().unwrap().to_asm_string().unwrap();
+            let mode = match (profile, gdb_port) {
+                (true, Some(_)) => {
+                    return Err(vec![
+                        "Cannot profile and debug at the same time.".to_string()
+                    ])
+                }
+                (true, None) => powdr_riscv_executor::ExecMode::Profile(ProfilerOptions {
+                    output_directory: pipeline.output_dir().unwrap().to_path_buf(),
+                    file_stem: pipeline.name().to_string(),
+                    flamegraph: true,
+                    callgrind: true,
+                }),
+                (false, Some(port)) => powdr_riscv_executor::ExecMode::Gdb(accept_gdb(port)?),
+                (false, None) => powdr_riscv_executor::ExecMode::Fast,
+            };
             powdr_riscv_executor::execute::<F>(
                 program,
                 pipeline.data_callback().unwrap(),
                 &[],
-                powdr_riscv_executor::ExecMode::Fast,
+                mode,
             );
         }
         (false, true) => {
-            rust_continuations(
+            rust_continuations_parallel(
                 pipeline,
                 generate_witness_and_prove_maybe,
                 bootloader_inputs,
+                continuations_workers,
             )?;
         }
         (false, false) => {
@@ -803,6 +1106,52 @@ fn run<F: FieldElement>(
     Ok(())
 }
 
+/// Waits for GDB to connect on the given local port.
+fn accept_gdb(port: u16) -> Result<TcpStream, Vec<String>> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))
+        .map_err(|e| vec![format!("Could not listen for GDB on port {port}: {e}")])?;
+    log::info!("Waiting for GDB to connect on port {port}...");
+    let (stream, address) = listener
+        .accept()
+        .map_err(|e| vec![format!("Could not accept the GDB connection: {e}")])?;
+    log::info!("GDB connected from {address}.");
+    Ok(stream)
+}
+
+/// Reports a witness generation error as `key: value` lines, one per field and
+/// failing identity, followed by the full description.
+fn witgen_error_report<T: FieldElement>(error: &WitgenError<T>) -> Vec<String> {
+    let mut report = error
+        .machine()
+        .map(|machine| format!("machine: {machine}"))
+        .into_iter()
+        .collect::<Vec<_>>();
+    match error {
+        WitgenError::RowFailed(failure) => {
+            report.push(format!("row: {}", failure.row));
+            report.push(format!("kind: {:?}", failure.kind));
+            report.extend(
+                failure
+                    .failed_identities
+                    .iter()
+                    .map(|failed| match &failed.identity {
+                        Some((identity, source)) => format!(
+                            "identity: {}:{}:{}: {identity}",
+                            source.file.as_deref().unwrap_or("<unknown>"),
+                            source.line,
+                            source.col
+                        ),
+                        None => format!("identity: <none>: {}", failed.error),
+                    }),
+            );
+        }
+        WitgenError::FirstRowFailed { .. } => report.push("row: first".to_string()),
+        WitgenError::Setup(_) => {}
+    }
+    report.push(error.to_string());
+    report
+}
+
 fn read_and_prove<T: FieldElement>(
     file: &Path,
     dir: &Path,
@@ -830,6 +1179,7 @@ fn read_and_verify<T: FieldElement>(
     proof: String,
     params: Option<String>,
     vkey: String,
+    publics: String,
 ) -> Result<(), Vec<String>> {
     let proof = Path::new(&proof);
     let vkey = Path::new(&vkey).to_path_buf();
@@ -843,8 +1193,7 @@ fn read_and_verify<T: FieldElement>(
         .with_vkey_file(Some(vkey))
         .with_backend(*backend_type);
 
-    // TODO add support for publics
-    pipeline.verify(proof, &[vec![]])?;
+    pipeline.verify(proof, &[split_inputs(&publics)])?;
     println!("Proof is valid!");
 
     Ok(())
@@ -861,10 +1210,27 @@ fn optimize_and_output<T: FieldElement>(file: &str) {
     );
 }
 
+fn report_unconstrained_columns<T: FieldElement>(file: &str) -> Result<(), Vec<String>> {
+    let pil = Pipeline::<T>::default()
+        .from_file(PathBuf::from(file))
+        .optimized_pil()?;
+    let columns = unconstrained_columns(&pil);
+    for column in &columns {
+        println!("{column}");
+    }
+    log::info!(
+        "Found {} likely under-constrained witness columns.",
+        columns.len()
+    );
+    Ok(())
+}
+
 #[cfg(test)]
 mod test {
-    use crate::{run_command, Commands, CsvRenderModeCLI, FieldArgument};
+    use crate::{run_command, Cli, Commands, CsvRenderModeCLI, FieldArgument};
+    use clap::Parser;
     use powdr_backend::BackendType;
+    use powdr_pipeline::LinkerMode;
 
     #[test]
     fn test_simple_sum() {
@@ -887,6 +1253,11 @@ mod test {
             csv_mode: CsvRenderModeCLI::Hex,
             just_execute: false,
             continuations: false,
+            continuations_workers: 1,
+            profile: false,
+            gdb_port: None,
+            linker_mode: LinkerMode::Monolithic,
+            max_degree: None,
         };
         run_command(pil_command);
 
@@ -909,4 +1280,11 @@ mod test {
             run_command(prove_command);
         }
     }
+
+    #[test]
+    fn continuations_workers_must_be_positive() {
+        let parse = |workers| Cli::try_parse_from(["powdr", "rust", "main.rs", workers]);
+        assert!(parse("--continuations-workers=0").is_err());
+        assert!(parse("--continuations-workers=2").is_ok());
+    }
 }
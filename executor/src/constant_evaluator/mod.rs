@@ -27,7 +27,7 @@ pub fn generate<T: FieldElement>(analyzed: &Analyzed<T>) -> Vec<(String, Vec<T>)
                 let index = poly.is_array().then_some(index as u64);
                 let values = generate_values(
                     analyzed,
-                    analyzed.degree(),
+                    poly.degree.unwrap_or_else(|| analyzed.degree()),
                     &name,
                     value,
                     index,
@@ -0,0 +1,703 @@
+//! A native constraint checker: Verifies that fixed and witness columns satisfy
+//! all identities of an analyzed PIL file, without running a prover.
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::{self, Display};
+
+use itertools::Itertools;
+use powdr_ast::analyzed::{
+    connect, AlgebraicExpression as Expression, AlgebraicReference, Analyzed, Identity,
+    IdentityKind, PolyID,
+};
+use powdr_ast::parsed::visitor::ExpressionVisitable;
+use powdr_ast::parsed::SelectedExpressions;
+use powdr_ast::SourceRef;
+use powdr_number::{DegreeType, FieldElement};
+use rayon::prelude::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
+
+use crate::witgen::affine_expression::AffineResult;
+use crate::witgen::expression_evaluator::{ExpressionEvaluator, SymbolicVariables};
+use crate::witgen::IncompleteCause;
+
+/// The maximum number of failing rows that are reported per identity.
+const MAX_FAILING_ROWS_PER_IDENTITY: usize = 10;
+
+/// A row in which an identity is not satisfied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FailingRow<T> {
+    /// The identity, as it appears in the PIL file.
+    pub identity: String,
+    /// The location of the identity in the source.
+    pub source: SourceRef,
+    /// The row in which the identity is not satisfied.
+    pub row: DegreeType,
+    /// A description of why the identity is not satisfied.
+    pub reason: String,
+    /// The values of all columns referenced by the identity in that row.
+    /// Next references are suffixed by `'`.
+    pub values: Vec<(String, T)>,
+}
+
+impl<T: Display> Display for FailingRow<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let location = match &self.source.file {
+            Some(file) => format!("{file}:{}", self.source.line),
+            None => format!("line {}", self.source.line),
+        };
+        writeln!(
+            f,
+            "Identity {} ({location}) fails in row {}: {}",
+            self.identity, self.row, self.reason
+        )?;
+        for (name, value) in &self.values {
+            writeln!(f, "    {name} = {value}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The reason why the columns do not satisfy the constraints.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConstraintCheckError<T> {
+    /// The columns do not match the PIL file, e.g. a column is missing or has
+    /// the wrong number of rows, so no identity could be checked.
+    Setup(String),
+    /// The first failing rows of every identity that is not satisfied.
+    FailingRows(Vec<FailingRow<T>>),
+}
+
+impl<T: Display> Display for ConstraintCheckError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConstraintCheckError::Setup(reason) => write!(f, "{reason}"),
+            ConstraintCheckError::FailingRows(failing_rows) => {
+                write!(f, "{}", failing_rows.iter().join("\n"))
+            }
+        }
+    }
+}
+
+/// Checks that the given fixed and witness columns satisfy all polynomial, plookup,
+/// permutation and connect identities of the PIL file.
+/// Each identity is checked on the rows of the columns it references, which all need
+/// to have the same degree. Next references wrap around, i.e. the row after the last
+/// row is the first row.
+/// @returns the first failing rows of every identity that is not satisfied.
+pub fn check_constraints<T: FieldElement>(
+    pil: &Analyzed<T>,
+    fixed: &[(String, Vec<T>)],
+    witness: &[(String, Vec<T>)],
+) -> Result<(), ConstraintCheckError<T>> {
+    let checker =
+        ConstraintChecker::new(pil, fixed, witness).map_err(ConstraintCheckError::Setup)?;
+
+    let identities = pil.identities_with_inlined_intermediate_polynomials();
+    let failing_rows = identities
+        .par_iter()
+        .zip(pil.identities.par_iter())
+        .flat_map_iter(|(identity, original)| {
+            checker
+                .failing_rows(identity)
+                .into_iter()
+                .take(MAX_FAILING_ROWS_PER_IDENTITY)
+                .map(|(row, reason)| FailingRow {
+                    identity: original.to_string(),
+                    source: original.source.clone(),
+                    row,
+                    reason,
+                    values: checker.referenced_values(identity, row),
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    if failing_rows.is_empty() {
+        Ok(())
+    } else {
+        Err(ConstraintCheckError::FailingRows(failing_rows))
+    }
+}
+
+/// A row together with the reason why an identity fails in that row.
+pub(crate) type RowFailure = (DegreeType, String);
+
+#[derive(Clone)]
+pub(crate) struct ConstraintChecker<'a, T> {
+    /// The largest degree of all columns, used for identities that do not reference any column.
+    degree: DegreeType,
+    columns: HashMap<PolyID, &'a [T]>,
+    publics: HashMap<&'a str, T>,
+    /// A cell whose value is replaced by the given value, see `with_mutated_cell`.
+    mutated_cell: Option<(PolyID, DegreeType, T)>,
+}
+
+impl<'a, T: FieldElement> ConstraintChecker<'a, T> {
+    pub(crate) fn new(
+        pil: &'a Analyzed<T>,
+        fixed: &'a [(String, Vec<T>)],
+        witness: &'a [(String, Vec<T>)],
+    ) -> Result<Self, String> {
+        let poly_ids = pil
+            .constant_polys_in_source_order()
+            .into_iter()
+            .chain(pil.committed_polys_in_source_order())
+            .flat_map(|(symbol, _)| {
+                let degree = symbol.degree.or(pil.degree);
+                symbol
+                    .array_elements()
+                    .map(move |(name, poly_id)| (name, (poly_id, degree)))
+            })
+            .collect::<HashMap<_, _>>();
+        let columns = fixed
+            .iter()
+            .chain(witness)
+            .map(|(name, values)| {
+                let (poly_id, degree) = poly_ids
+                    .get(name)
+                    .ok_or_else(|| format!("Column {name} is not declared in the PIL file."))?;
+                match degree {
+                    Some(degree) if values.len() as DegreeType != *degree => Err(format!(
+                        "Column {name} has {} rows, but the degree is {degree}.",
+                        values.len()
+                    )),
+                    _ => Ok((*poly_id, &values[..])),
+                }
+            })
+            .collect::<Result<HashMap<_, _>, _>>()?;
+        if let Some(missing) = poly_ids
+            .iter()
+            .find(|(_, (id, _))| !columns.contains_key(id))
+        {
+            return Err(format!("No values provided for column {}.", missing.0));
+        }
+        let degree = columns
+            .values()
+            .map(|values| values.len() as DegreeType)
+            .max()
+            .unwrap_or_default();
+
+        let publics = pil
+            .public_declarations
+            .iter()
+            .map(|(name, public)| {
+                let poly_id = public.polynomial.poly_id.unwrap();
+                let poly_id = PolyID {
+                    id: poly_id.id + public.array_index.unwrap_or_default() as u64,
+                    ..poly_id
+                };
+                let value = columns
+                    .get(&poly_id)
+                    .ok_or_else(|| {
+                        format!("Public {name} references a column that has no values.")
+                    })?
+                    .get(public.index as usize)
+                    .ok_or_else(|| {
+                        format!(
+                            "Public {name} references row {}, which is out of range.",
+                            public.index
+                        )
+                    })?;
+                Ok((name.as_str(), *value))
+            })
+            .collect::<Result<_, String>>()?;
+
+        Ok(ConstraintChecker {
+            degree,
+            columns,
+            publics,
+            mutated_cell: None,
+        })
+    }
+
+    /// @returns a checker that sees `value` instead of the actual value of the
+    /// column in the given row. The values of publics are not affected.
+    pub(crate) fn with_mutated_cell(&self, poly_id: PolyID, row: DegreeType, value: T) -> Self {
+        Self {
+            mutated_cell: Some((poly_id, row, value)),
+            ..self.clone()
+        }
+    }
+
+    /// @returns the number of rows of the given column.
+    pub(crate) fn column_degree(&self, poly_id: &PolyID) -> DegreeType {
+        self.columns[poly_id].len() as DegreeType
+    }
+
+    /// @returns the common degree of all columns referenced by the expressions,
+    /// or an error if they differ.
+    pub(crate) fn degree_of<'b>(
+        &self,
+        expressions: impl IntoIterator<Item = &'b Expression<T>>,
+    ) -> Result<DegreeType, String>
+    where
+        T: 'b,
+    {
+        let mut degrees = BTreeSet::new();
+        for e in expressions {
+            e.pre_visit_expressions(&mut |e| {
+                if let Expression::Reference(poly) = e {
+                    degrees.insert(self.column_degree(&poly.poly_id));
+                }
+            });
+        }
+        match degrees.len() {
+            0 => Ok(self.degree),
+            1 => Ok(*degrees.first().unwrap()),
+            _ => Err(format!(
+                "The referenced columns have different degrees: {}.",
+                degrees.iter().format(", ")
+            )),
+        }
+    }
+
+    /// @returns the failing rows of the identity, in ascending order,
+    /// together with the reason why they fail.
+    pub(crate) fn failing_rows(&self, identity: &Identity<Expression<T>>) -> Vec<RowFailure> {
+        match identity.kind {
+            IdentityKind::Polynomial => self.failing_rows_polynomial(identity),
+            IdentityKind::Plookup => self.failing_rows_plookup(identity),
+            IdentityKind::Permutation => self.failing_rows_permutation(identity),
+            IdentityKind::Connect => self.failing_rows_connect(identity),
+        }
+    }
+
+    fn failing_rows_polynomial(&self, identity: &Identity<Expression<T>>) -> Vec<RowFailure> {
+        let expression = identity.expression_for_poly_id();
+        let degree = match self.degree_of([expression]) {
+            Ok(degree) => degree,
+            Err(e) => return vec![(0, e)],
+        };
+        (0..degree)
+            .filter_map(|row| match self.evaluate(expression, row) {
+                Ok(value) if value.is_zero() => None,
+                Ok(value) => Some((row, format!("Evaluates to {value} instead of zero."))),
+                Err(e) => Some((row, e)),
+            })
+            .collect()
+    }
+
+    fn failing_rows_plookup(&self, identity: &Identity<Expression<T>>) -> Vec<RowFailure> {
+        let right = match self.selected_tuples(&identity.right) {
+            Ok(right) => right
+                .into_iter()
+                .map(|(_, tuple)| tuple)
+                .collect::<BTreeSet<_>>(),
+            Err((row, e)) => return vec![(row, e)],
+        };
+        match self.selected_tuples(&identity.left) {
+            Ok(left) => left
+                .into_iter()
+                .filter(|(_, tuple)| !right.contains(tuple))
+                .map(|(row, tuple)| {
+                    (
+                        row,
+                        format!(
+                            "Tuple ({}) is not contained in the right hand side.",
+                            tuple.iter().format(", ")
+                        ),
+                    )
+                })
+                .collect(),
+            Err(e) => vec![e],
+        }
+    }
+
+    fn failing_rows_permutation(&self, identity: &Identity<Expression<T>>) -> Vec<RowFailure> {
+        let (left, right) = match (
+            self.selected_tuples(&identity.left),
+            self.selected_tuples(&identity.right),
+        ) {
+            (Ok(left), Ok(right)) => (left, right),
+            (Err(e), _) | (_, Err(e)) => return vec![e],
+        };
+        let mut counts = HashMap::new();
+        for (_, tuple) in &right {
+            *counts.entry(tuple).or_insert(0usize) += 1;
+        }
+        let mut failing = left
+            .iter()
+            .filter_map(|(row, tuple)| match counts.get_mut(tuple) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    None
+                }
+                _ => Some((
+                    *row,
+                    format!(
+                        "Tuple ({}) on the left has no match on the right.",
+                        tuple.iter().format(", ")
+                    ),
+                )),
+            })
+            .collect::<Vec<_>>();
+        if failing.is_empty() {
+            // Every tuple on the left is matched, so if the identity fails,
+            // there are more tuples on the right.
+            let mut unmatched = counts;
+            failing.extend(right.iter().filter_map(|(row, tuple)| {
+                let count = unmatched.get_mut(tuple).unwrap();
+                (*count > 0).then(|| {
+                    *count -= 1;
+                    (
+                        *row,
+                        format!(
+                            "Tuple ({}) on the right has no match on the left.",
+                            tuple.iter().format(", ")
+                        ),
+                    )
+                })
+            }));
+        }
+        failing
+    }
+
+    fn failing_rows_connect(&self, identity: &Identity<Expression<T>>) -> Vec<RowFailure> {
+        let cell_count = identity.left.expressions.len();
+        assert_eq!(cell_count, identity.right.expressions.len());
+        assert!(identity.left.selector.is_none() && identity.right.selector.is_none());
+        let degree = match self.degree_of(
+            identity
+                .left
+                .expressions
+                .iter()
+                .chain(&identity.right.expressions),
+        ) {
+            Ok(degree) => degree,
+            Err(e) => return vec![(0, e)],
+        };
+        let positions = match connect::cell_positions::<T>(cell_count, degree) {
+            Ok(positions) => positions,
+            Err(e) => return vec![(0, e)],
+        };
+        let (values, connections) = match (
+            self.evaluate_columns(&identity.left.expressions, degree),
+            self.evaluate_columns(&identity.right.expressions, degree),
+        ) {
+            (Ok(values), Ok(connections)) => (values, connections),
+            (Err(e), _) | (_, Err(e)) => return vec![e],
+        };
+        (0..degree)
+            .flat_map(|row| (0..cell_count).map(move |index| (row, index)))
+            .filter_map(|(row, index)| {
+                let connection = connections[index][row as usize];
+                let value = values[index][row as usize];
+                match positions.get(&connection) {
+                    None => Some((
+                        row,
+                        format!("Connection {connection} of expression {index} is not a cell."),
+                    )),
+                    Some((other_index, other_row)) => {
+                        let other_value = values[*other_index][*other_row as usize];
+                        (value != other_value).then(|| {
+                            (
+                                row,
+                                format!(
+                                    "Expression {index} is {value}, but the connected \
+                                     cell ({other_index}, {other_row}) is {other_value}."
+                                ),
+                            )
+                        })
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// Evaluates the expressions in all rows in which the selector is non-zero.
+    /// @returns the rows and the evaluated tuples or the first row where the evaluation failed.
+    fn selected_tuples(
+        &self,
+        selected: &SelectedExpressions<Expression<T>>,
+    ) -> Result<Vec<(DegreeType, Vec<T>)>, RowFailure> {
+        let degree = self
+            .degree_of(selected.selector.iter().chain(&selected.expressions))
+            .map_err(|e| (0, e))?;
+        (0..degree)
+            .filter_map(|row| {
+                self.selected_tuple(selected, row)
+                    .map(|tuple| tuple.map(|tuple| (row, tuple)))
+                    .map_err(|e| (row, e))
+                    .transpose()
+            })
+            .collect()
+    }
+
+    /// Evaluates the expressions in the given row.
+    /// @returns None if the selector is zero in that row.
+    pub(crate) fn selected_tuple(
+        &self,
+        selected: &SelectedExpressions<Expression<T>>,
+        row: DegreeType,
+    ) -> Result<Option<Vec<T>>, String> {
+        let selector = match &selected.selector {
+            Some(selector) => self.evaluate(selector, row)?,
+            None => T::one(),
+        };
+        if selector.is_zero() {
+            return Ok(None);
+        }
+        selected
+            .expressions
+            .iter()
+            .map(|e| self.evaluate(e, row))
+            .collect::<Result<Vec<_>, _>>()
+            .map(Some)
+    }
+
+    /// Evaluates each expression in all rows.
+    fn evaluate_columns(
+        &self,
+        expressions: &[Expression<T>],
+        degree: DegreeType,
+    ) -> Result<Vec<Vec<T>>, RowFailure> {
+        expressions
+            .iter()
+            .map(|e| {
+                (0..degree)
+                    .map(|row| self.evaluate(e, row).map_err(|err| (row, err)))
+                    .collect()
+            })
+            .collect()
+    }
+
+    pub(crate) fn evaluate(
+        &self,
+        expression: &Expression<T>,
+        row: DegreeType,
+    ) -> Result<T, String> {
+        let evaluator = ExpressionEvaluator::new(RowValues { checker: self, row });
+        match evaluator.evaluate(expression) {
+            Ok(result) => result
+                .constant_value()
+                .ok_or_else(|| format!("Expression {expression} is not constant.")),
+            Err(cause) => Err(format!("Could not evaluate {expression}: {cause:?}")),
+        }
+    }
+
+    fn value(&self, poly: &AlgebraicReference, row: DegreeType) -> T {
+        let row = if poly.next {
+            (row + 1) % self.column_degree(&poly.poly_id)
+        } else {
+            row
+        };
+        match self.mutated_cell {
+            Some((poly_id, mutated_row, value))
+                if poly_id == poly.poly_id && mutated_row == row =>
+            {
+                value
+            }
+            _ => self.columns[&poly.poly_id][row as usize],
+        }
+    }
+
+    /// @returns the values of all columns referenced by the identity in the given row.
+    fn referenced_values(
+        &self,
+        identity: &Identity<Expression<T>>,
+        row: DegreeType,
+    ) -> Vec<(String, T)> {
+        let mut references = BTreeSet::new();
+        identity.pre_visit_expressions(&mut |e| {
+            if let Expression::Reference(poly) = e {
+                references.insert(poly.clone());
+            }
+        });
+        references
+            .into_iter()
+            .map(|poly| {
+                let name = if poly.next {
+                    format!("{}'", poly.name)
+                } else {
+                    poly.name.clone()
+                };
+                (name, self.value(&poly, row))
+            })
+            .collect()
+    }
+}
+
+/// Concrete values of all columns in a specific row, to be used with [ExpressionEvaluator].
+struct RowValues<'a, 'b, T> {
+    checker: &'b ConstraintChecker<'a, T>,
+    row: DegreeType,
+}
+
+impl<'a, 'b, T: FieldElement> SymbolicVariables<T> for RowValues<'a, 'b, T> {
+    fn value<'c>(&self, poly: &'c AlgebraicReference) -> AffineResult<&'c AlgebraicReference, T> {
+        Ok(self.checker.value(poly, self.row).into())
+    }
+
+    fn public_value<'c>(&self, name: &str) -> AffineResult<&'c AlgebraicReference, T> {
+        self.checker
+            .publics
+            .get(name)
+            .map(|value| (*value).into())
+            .ok_or_else(|| IncompleteCause::ExpressionEvaluationUnimplemented(format!(":{name}")))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use powdr_number::GoldilocksField;
+    use powdr_pil_analyzer::analyze_string;
+    use test_log::test;
+
+    use crate::constant_evaluator;
+
+    use super::*;
+
+    fn check(
+        src: &str,
+        witness: Vec<(&str, Vec<u64>)>,
+    ) -> Result<(), Vec<FailingRow<GoldilocksField>>> {
+        let pil = analyze_string::<GoldilocksField>(src);
+        let fixed = constant_evaluator::generate(&pil);
+        let witness = witness
+            .into_iter()
+            .map(|(name, values)| {
+                (
+                    name.to_string(),
+                    values.into_iter().map(GoldilocksField::from).collect(),
+                )
+            })
+            .collect::<Vec<_>>();
+        check_constraints(&pil, &fixed, &witness).map_err(|e| match e {
+            ConstraintCheckError::FailingRows(failing_rows) => failing_rows,
+            e => panic!("{e}"),
+        })
+    }
+
+    #[test]
+    fn polynomial_identity() {
+        let src = r"
+            namespace main(4);
+                col fixed FIRST = [1, 0, 0, 0];
+                col witness x;
+                FIRST * (x - 1) = 0;
+                (1 - FIRST') * (x' - 2 * x) = 0;
+        ";
+        assert!(check(src, vec![("main.x", vec![1, 2, 4, 8])]).is_ok());
+
+        let failures = check(src, vec![("main.x", vec![1, 2, 5, 10])]).unwrap_err();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].row, 1);
+        assert_eq!(
+            failures[0].values,
+            vec![
+                ("main.x".to_string(), 2.into()),
+                ("main.x'".to_string(), 5.into()),
+                ("main.FIRST'".to_string(), 0.into())
+            ]
+        );
+    }
+
+    #[test]
+    fn setup_error() {
+        let src = r"
+            namespace main(4);
+                col witness x;
+                x = 0;
+        ";
+        let pil = analyze_string::<GoldilocksField>(src);
+        let witness = vec![("main.x".to_string(), vec![GoldilocksField::from(0); 3])];
+        assert_eq!(
+            check_constraints(&pil, &[], &witness),
+            Err(ConstraintCheckError::Setup(
+                "Column main.x has 3 rows, but the degree is 4.".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn public_out_of_range() {
+        let src = r"
+            namespace main(4);
+                col witness x;
+                public out = x(7);
+                x = 0;
+        ";
+        let pil = analyze_string::<GoldilocksField>(src);
+        let witness = vec![("main.x".to_string(), vec![GoldilocksField::from(0); 4])];
+        assert_eq!(
+            check_constraints(&pil, &[], &witness),
+            Err(ConstraintCheckError::Setup(
+                "Public out references row 7, which is out of range.".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn plookup_identity() {
+        let src = r"
+            namespace main(4);
+                col fixed BYTE = [0, 1, 2, 3];
+                col fixed SEL = [1, 1, 0, 1];
+                col witness x;
+                SEL { x } in { BYTE };
+        ";
+        assert!(check(src, vec![("main.x", vec![3, 0, 7, 1])]).is_ok());
+
+        let failures = check(src, vec![("main.x", vec![3, 0, 7, 4])]).unwrap_err();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].row, 3);
+    }
+
+    #[test]
+    fn permutation_identity() {
+        let src = r"
+            namespace main(4);
+                col fixed A = [1, 2, 3, 4];
+                col witness x;
+                { x } is { A };
+        ";
+        assert!(check(src, vec![("main.x", vec![4, 3, 2, 1])]).is_ok());
+
+        let failures = check(src, vec![("main.x", vec![4, 4, 2, 1])]).unwrap_err();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].row, 1);
+    }
+
+    #[test]
+    fn connect_identity() {
+        // Connects x in row 0 with y in row 1 and leaves all other cells alone.
+        let identifiers = connect::cell_positions::<GoldilocksField>(2, 4)
+            .unwrap()
+            .into_iter()
+            .map(|(identifier, cell)| (cell, identifier))
+            .collect::<HashMap<_, _>>();
+        let cell = |expression, row| identifiers[&(expression, row)];
+        let src = format!(
+            r"
+            namespace main(4);
+                col fixed CONN_X = [{}, {}, {}, {}];
+                col fixed CONN_Y = [{}, {}, {}, {}];
+                col witness x;
+                col witness y;
+                {{ x, y }} connect {{ CONN_X, CONN_Y }};
+            ",
+            cell(1, 1),
+            cell(0, 1),
+            cell(0, 2),
+            cell(0, 3),
+            cell(1, 0),
+            cell(0, 0),
+            cell(1, 2),
+            cell(1, 3),
+        );
+        assert!(check(
+            &src,
+            vec![("main.x", vec![5, 1, 2, 3]), ("main.y", vec![0, 5, 0, 0])]
+        )
+        .is_ok());
+
+        let failures = check(
+            &src,
+            vec![("main.x", vec![5, 1, 2, 3]), ("main.y", vec![0, 6, 0, 0])],
+        )
+        .unwrap_err();
+        assert_eq!(
+            failures.iter().map(|f| f.row).collect::<Vec<_>>(),
+            vec![0, 1]
+        );
+    }
+}
@@ -3,4 +3,6 @@
 #![deny(clippy::print_stdout)]
 
 pub mod constant_evaluator;
+pub mod constraint_checker;
 pub mod witgen;
+pub mod witness_fuzzer;
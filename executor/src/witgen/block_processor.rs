@@ -3,7 +3,8 @@ use std::collections::HashSet;
 use powdr_ast::analyzed::{
     AlgebraicExpression as Expression, AlgebraicReference, Identity, PolyID,
 };
-use powdr_number::FieldElement;
+use powdr_number::{DegreeType, FieldElement};
+use powdr_parser_util::lines::indent;
 
 use super::{
     data_structures::finalizable_data::FinalizableData,
@@ -21,6 +22,8 @@ use super::{
 /// - `'c`: The duration of this Processor's lifetime (e.g. the reference to the identity processor)
 pub struct BlockProcessor<'a, 'b, 'c, T: FieldElement, Q: QueryCallback<T>> {
     processor: Processor<'a, 'b, 'c, T, Q>,
+    /// The global index of the first row of the block.
+    row_offset: DegreeType,
     /// The list of identities
     identities: &'c [&'a Identity<Expression<T>>],
 }
@@ -37,6 +40,7 @@ impl<'a, 'b, 'c, T: FieldElement, Q: QueryCallback<T>> BlockProcessor<'a, 'b, 'c
         let processor = Processor::new(row_offset, data, mutable_state, fixed_data, witness_cols);
         Self {
             processor,
+            row_offset,
             identities,
         }
     }
@@ -61,12 +65,12 @@ impl<'a, 'b, 'c, T: FieldElement, Q: QueryCallback<T>> BlockProcessor<'a, 'b, 'c
             let row_index = (1 + row_delta) as usize;
             let progress = match action {
                 Action::InternalIdentity(identity_index) => {
+                    let identity = self.identities[identity_index];
                     self.processor
-                        .process_identity(
-                            row_index,
-                            self.identities[identity_index],
-                            UnknownStrategy::Unknown,
-                        )?
+                        .process_identity(row_index, identity, UnknownStrategy::Unknown)
+                        .map_err(|e| -> EvalError<T> {
+                            format!("{identity}:\n{}", indent(&e.to_string(), "    ")).into()
+                        })?
                         .progress
                 }
                 Action::OuterQuery => {
@@ -80,6 +84,13 @@ impl<'a, 'b, 'c, T: FieldElement, Q: QueryCallback<T>> BlockProcessor<'a, 'b, 'c
             sequence_iterator.report_progress(progress);
         }
 
+        if let Some(row_delta) = sequence_iterator.non_converging_row_delta() {
+            let row_index = (1 + row_delta) as DegreeType;
+            return Err(EvalError::SolvingDidNotConverge(
+                self.row_offset + row_index,
+            ));
+        }
+
         match self.processor.finshed_outer_query() {
             true => Ok(EvalValue::complete(outer_assignments)),
             false => Ok(EvalValue::incomplete_with_constraints(
@@ -105,6 +116,7 @@ mod tests {
     use crate::{
         constant_evaluator::generate,
         witgen::{
+            copy_constraints::CopyConstraints,
             data_structures::column_map::FixedColumnMap,
             data_structures::finalizable_data::FinalizableData,
             global_constraints::GlobalConstraints,
@@ -161,11 +173,13 @@ mod tests {
             .collect();
         let data = FinalizableData::with_initial_rows_in_progress(
             &columns,
-            (0..fixed_data.degree).map(|i| row_factory.fresh_row(i)),
+            (0..analyzed.degree()).map(|i| row_factory.fresh_row(i)),
         );
 
+        let mut copy_constraints = CopyConstraints::default();
         let mut mutable_state = MutableState {
             fixed_lookup: &mut fixed_lookup,
+            copy_constraints: &mut copy_constraints,
             machines: Machines::from(machines.iter_mut()),
             query_callback: &mut query_callback,
         };
@@ -0,0 +1,143 @@
+use std::collections::HashMap;
+
+use powdr_ast::analyzed::connect::cell_positions;
+use powdr_ast::analyzed::{AlgebraicExpression as Expression, Identity, IdentityKind};
+use powdr_number::{DegreeType, FieldElement};
+
+use super::util::try_to_simple_poly;
+use super::{EvalError, FixedData};
+
+/// The cells of the left hand side of a `connect` identity, grouped into the cycles
+/// of the permutation defined by the fixed columns on the right hand side.
+struct ConnectCycles {
+    degree: DegreeType,
+    /// The cycle of each cell, indexed by `expression * degree + row`.
+    cycle_of_cell: Vec<usize>,
+}
+
+impl ConnectCycles {
+    fn new<T: FieldElement>(
+        fixed_data: &FixedData<T>,
+        identity: &Identity<Expression<T>>,
+    ) -> Result<Self, String> {
+        if identity.left.selector.is_some() || identity.right.selector.is_some() {
+            return Err(format!(
+                "Selectors are not supported for connect identities: {identity}"
+            ));
+        }
+        let columns = identity
+            .right
+            .expressions
+            .iter()
+            .map(|e| {
+                try_to_simple_poly(e)
+                    .filter(|poly| poly.is_fixed())
+                    .map(|poly| (poly.name.as_str(), fixed_data.fixed_cols[&poly.poly_id].values))
+                    .ok_or_else(|| {
+                        format!("The right hand side of a connect identity must consist of fixed columns, but found {e} in {identity}")
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        // The fixed columns on the right hand side span the rows of the machine.
+        let degree = columns
+            .iter()
+            .map(|(_, values)| values.len() as DegreeType)
+            .max()
+            .unwrap_or_default();
+        let positions = cell_positions::<T>(columns.len(), degree)?;
+
+        // Union-find over all cells, merging each cell with its successor in the permutation.
+        let mut parent = (0..columns.len() * degree as usize).collect::<Vec<_>>();
+        for (expression, (name, values)) in columns.iter().enumerate() {
+            for (row, value) in values.iter().enumerate() {
+                let (next_expression, next_row) = positions.get(value).ok_or_else(|| {
+                    format!("Value {value} of {name} in row {row} does not identify a cell of the connect identity {identity}")
+                })?;
+                let cell = find(&mut parent, expression * degree as usize + row);
+                let next_cell = find(
+                    &mut parent,
+                    next_expression * degree as usize + *next_row as usize,
+                );
+                parent[cell] = next_cell;
+            }
+        }
+        let cycle_of_cell = (0..parent.len())
+            .map(|cell| find(&mut parent, cell))
+            .collect();
+
+        Ok(Self {
+            degree,
+            cycle_of_cell,
+        })
+    }
+
+    fn cycle(&self, expression: usize, row: DegreeType) -> usize {
+        self.cycle_of_cell[expression * self.degree as usize + (row % self.degree) as usize]
+    }
+}
+
+fn find(parent: &mut [usize], mut cell: usize) -> usize {
+    while parent[cell] != cell {
+        parent[cell] = parent[parent[cell]];
+        cell = parent[cell];
+    }
+    cell
+}
+
+/// The values known so far for the cells connected by `connect` identities.
+///
+/// Witness generation proceeds row by row, so a value that becomes known in one cell
+/// is propagated to all cells of its cycle that are processed later.
+#[derive(Default)]
+pub struct CopyConstraints<T> {
+    /// The cycles of each connect identity, by identity ID.
+    cycles: HashMap<u64, ConnectCycles>,
+    /// The known values, by identity ID and cycle.
+    values: HashMap<(u64, usize), T>,
+}
+
+impl<T: FieldElement> CopyConstraints<T> {
+    /// Fails if one of the connect identities is not supported.
+    pub fn new<'a>(
+        fixed_data: &'a FixedData<'a, T>,
+        identities: impl IntoIterator<Item = &'a Identity<Expression<T>>>,
+    ) -> Result<Self, String> {
+        let cycles = identities
+            .into_iter()
+            .filter(|identity| identity.kind == IdentityKind::Connect)
+            .map(|identity| Ok((identity.id, ConnectCycles::new(fixed_data, identity)?)))
+            .collect::<Result<_, String>>()?;
+        Ok(Self {
+            cycles,
+            values: Default::default(),
+        })
+    }
+
+    /// Returns the value of the cycle that contains the cell of the `expression`th
+    /// expression of the given connect identity in the given row, if known.
+    pub fn value(&self, identity_id: u64, expression: usize, row: DegreeType) -> Option<T> {
+        let cycle = self.cycles.get(&identity_id)?.cycle(expression, row);
+        self.values.get(&(identity_id, cycle)).cloned()
+    }
+
+    /// Sets the value of the cycle that contains the given cell.
+    /// Fails if a different value is already known for the cycle.
+    pub fn set_value(
+        &mut self,
+        identity_id: u64,
+        expression: usize,
+        row: DegreeType,
+        value: T,
+    ) -> Result<(), EvalError<T>> {
+        let Some(cycles) = self.cycles.get(&identity_id) else {
+            return Ok(());
+        };
+        let cycle = cycles.cycle(expression, row);
+        match self.values.insert((identity_id, cycle), value) {
+            Some(existing) if existing != value => Err(EvalError::Generic(format!(
+                "Value {value} in row {row} is copy-constrained to a cell with value {existing}."
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
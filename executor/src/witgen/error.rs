@@ -0,0 +1,183 @@
+use std::fmt;
+
+use itertools::Itertools;
+use powdr_ast::analyzed::{AlgebraicExpression as Expression, Identity};
+use powdr_ast::SourceRef;
+use powdr_number::{DegreeType, FieldElement};
+use powdr_parser_util::lines::indent;
+
+use super::EvalError;
+
+/// An error that made witness generation fail.
+#[derive(Clone, Debug, PartialEq)]
+pub enum WitgenError<T: FieldElement> {
+    /// A row of a machine could not be completed.
+    RowFailed(Box<RowFailure<T>>),
+    /// The first row of a machine could not be computed.
+    FirstRowFailed {
+        machine: String,
+        error: EvalError<T>,
+    },
+    /// Witness generation could not be set up, e.g. because an identity is not supported.
+    Setup(String),
+}
+
+impl<T: FieldElement> WitgenError<T> {
+    /// Returns the name of the machine in which witness generation failed, if it
+    /// failed in a machine.
+    pub fn machine(&self) -> Option<&str> {
+        match self {
+            WitgenError::RowFailed(failure) => Some(&failure.machine),
+            WitgenError::FirstRowFailed { machine, .. } => Some(machine),
+            WitgenError::Setup(_) => None,
+        }
+    }
+}
+
+impl<T: FieldElement> From<RowFailure<T>> for WitgenError<T> {
+    fn from(failure: RowFailure<T>) -> Self {
+        WitgenError::RowFailed(Box::new(failure))
+    }
+}
+
+impl<T: FieldElement> fmt::Display for WitgenError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WitgenError::RowFailed(failure) => write!(f, "{failure}"),
+            WitgenError::FirstRowFailed { machine, error } => write!(
+                f,
+                "Witness generation failed in machine {machine} while computing the first row:\n{}",
+                indent(&error.to_string(), "    ")
+            ),
+            WitgenError::Setup(reason) => {
+                write!(f, "Witness generation could not be set up: {reason}")
+            }
+        }
+    }
+}
+
+/// Why a row could not be completed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RowFailureKind {
+    /// Some identities are not satisfiable given the values determined so far.
+    Unsatisfiable,
+    /// Some cells could not be determined, and setting them to zero violates
+    /// some identities. This typically means that the system is under-constrained.
+    UnderConstrained,
+}
+
+/// A row of a machine that could not be completed during witness generation.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RowFailure<T: FieldElement> {
+    /// The name of the machine the row belongs to.
+    pub machine: String,
+    /// The (global) index of the row.
+    pub row: DegreeType,
+    pub kind: RowFailureKind,
+    /// The identities that could not be solved, together with the reason.
+    pub failed_identities: Vec<FailedIdentity<T>>,
+    /// The cells of the failing row.
+    pub current_row: RowCells<T>,
+    /// The cells of the row after the failing row.
+    pub next_row: RowCells<T>,
+}
+
+impl<T: FieldElement> fmt::Display for RowFailure<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self.kind {
+            RowFailureKind::Unsatisfiable => {
+                "some identities are not satisfiable after the following values were uniquely determined"
+            }
+            RowFailureKind::UnderConstrained => {
+                "some columns could not be determined, but setting them to zero does not satisfy the constraints. \
+                 This typically means that the system is under-constrained"
+            }
+        };
+        writeln!(
+            f,
+            "Witness generation failed in machine {} at row {}: {reason}.",
+            self.machine, self.row
+        )?;
+        writeln!(f, "{}", self.current_row)?;
+        writeln!(f, "{}", self.next_row)?;
+        write!(
+            f,
+            "Failing identities:\n{}",
+            self.failed_identities
+                .iter()
+                .map(|i| indent(&i.to_string(), "    "))
+                .join("\n")
+        )
+    }
+}
+
+/// An identity that could not be solved.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FailedIdentity<T: FieldElement> {
+    /// The identity, or `None` if the error did not originate from a single
+    /// identity (e.g. from a prover query).
+    pub identity: Option<(String, SourceRef)>,
+    pub error: EvalError<T>,
+}
+
+impl<T: FieldElement> FailedIdentity<T> {
+    pub fn new(identity: Option<&Identity<Expression<T>>>, error: EvalError<T>) -> Self {
+        Self {
+            identity: identity.map(|identity| (identity.to_string(), identity.source.clone())),
+            error,
+        }
+    }
+}
+
+impl<T: FieldElement> fmt::Display for FailedIdentity<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.identity {
+            Some((identity, source)) => {
+                let file = source.file.as_deref().unwrap_or("<unknown>");
+                writeln!(f, "{file}:{}:{}: {identity}", source.line, source.col)?;
+                write!(f, "{}", indent(&self.error.to_string(), "    "))
+            }
+            None => write!(f, "{}", self.error),
+        }
+    }
+}
+
+/// The cells of a single row of a machine at the time witness generation failed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RowCells<T: FieldElement> {
+    /// The (global) index of the row.
+    pub row: DegreeType,
+    /// The cells with a known value, non-zero values first.
+    pub known: Vec<(String, T)>,
+    /// The cells without a known value.
+    pub unknown: Vec<String>,
+    /// The range constraints of cells without a known value.
+    pub range_constraints: Vec<(String, String)>,
+}
+
+impl<T: FieldElement> fmt::Display for RowCells<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Row {}:", self.row)?;
+        let sections = [
+            (
+                "Known cells",
+                self.known
+                    .iter()
+                    .map(|(name, value)| format!("{name} = {value}"))
+                    .collect_vec(),
+            ),
+            ("Unknown cells", self.unknown.clone()),
+            (
+                "Range constraints",
+                self.range_constraints
+                    .iter()
+                    .map(|(name, constraint)| format!("{name}: {constraint}"))
+                    .collect_vec(),
+            ),
+        ];
+        for (title, lines) in sections.iter().filter(|(_, lines)| !lines.is_empty()) {
+            write!(f, "\n  {title}:\n{}", indent(&lines.join("\n"), "    "))?;
+        }
+        Ok(())
+    }
+}
@@ -1,8 +1,9 @@
 use std::fmt;
 
 use powdr_ast::analyzed::AlgebraicReference;
-use powdr_number::FieldElement;
+use powdr_number::{DegreeType, FieldElement};
 
+use super::error::WitgenError;
 use super::range_constraints::RangeConstraint;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -164,6 +165,11 @@ pub enum EvalError<T: FieldElement> {
     FixedLookupFailed(Vec<(String, T)>),
     /// Error getting information from the prover.
     ProverQueryError(String),
+    /// Solving did not converge in a row: Progress was made in every round,
+    /// but the maximum number of rounds was reached. Argument: the row.
+    SolvingDidNotConverge(DegreeType),
+    /// Witness generation failed in a machine called via a lookup or permutation.
+    MachineCallFailed(Box<WitgenError<T>>),
     Generic(String),
     Multiple(Vec<EvalError<T>>),
 }
@@ -174,6 +180,12 @@ impl<T: FieldElement> From<String> for EvalError<T> {
     }
 }
 
+impl<T: FieldElement> From<WitgenError<T>> for EvalError<T> {
+    fn from(value: WitgenError<T>) -> Self {
+        Self::MachineCallFailed(Box::new(value))
+    }
+}
+
 impl<T: FieldElement> EvalError<T> {
     pub fn combine(self, other: EvalError<T>) -> EvalError<T> {
         match (self, other) {
@@ -221,6 +233,11 @@ impl<T: FieldElement> fmt::Display for EvalError<T> {
             EvalError::ProverQueryError(s) => {
                 write!(f, "Error getting external information from the prover: {s}")
             }
+            EvalError::SolvingDidNotConverge(row) => write!(
+                f,
+                "Solving did not converge in row {row}. This is a bug in the witness generation algorithm."
+            ),
+            EvalError::MachineCallFailed(e) => write!(f, "{e}"),
             EvalError::Generic(s) => write!(f, "{s}"),
         }
     }
@@ -12,6 +12,13 @@ use super::{affine_expression::AffineResult, IncompleteCause};
 pub trait SymbolicVariables<T> {
     /// Value of a polynomial (fixed or witness).
     fn value<'a>(&self, poly: &'a AlgebraicReference) -> AffineResult<&'a AlgebraicReference, T>;
+
+    /// Value of a public reference.
+    fn public_value<'a>(&self, name: &str) -> AffineResult<&'a AlgebraicReference, T> {
+        Err(IncompleteCause::ExpressionEvaluationUnimplemented(format!(
+            ":{name}"
+        )))
+    }
 }
 
 pub struct ExpressionEvaluator<T, SV> {
@@ -38,14 +45,12 @@ where
         // we could store the simplified values.
         match expr {
             Expression::Reference(poly) => self.variables.value(poly),
+            Expression::PublicReference(name) => self.variables.public_value(name),
             Expression::Number(n) => Ok((*n).into()),
             Expression::BinaryOperation(left, op, right) => {
                 self.evaluate_binary_operation(left, op, right)
             }
             Expression::UnaryOperation(op, expr) => self.evaluate_unary_operation(op, expr),
-            e => Err(IncompleteCause::ExpressionEvaluationUnimplemented(
-                e.to_string(),
-            )),
         }
     }
 
@@ -13,13 +13,14 @@ use crate::witgen::EvalValue;
 
 use super::affine_expression::AffineExpression;
 use super::block_processor::BlockProcessor;
+use super::copy_constraints::CopyConstraints;
 use super::data_structures::column_map::WitnessColumnMap;
 use super::global_constraints::GlobalConstraints;
 use super::machines::{FixedLookup, Machine};
 use super::rows::{Row, RowFactory};
 use super::sequence_iterator::{DefaultSequenceIterator, ProcessingSequenceIterator};
 use super::vm_processor::VmProcessor;
-use super::{EvalResult, FixedData, MutableState, QueryCallback};
+use super::{EvalResult, FixedData, MutableState, QueryCallback, WitgenError};
 
 struct ProcessResult<'a, T: FieldElement> {
     eval_value: EvalValue<&'a AlgebraicReference, T>,
@@ -28,6 +29,8 @@ struct ProcessResult<'a, T: FieldElement> {
 
 pub struct Generator<'a, T: FieldElement> {
     fixed_data: &'a FixedData<'a, T>,
+    /// The number of rows of the machine.
+    degree: DegreeType,
     identities: Vec<&'a Identity<Expression<T>>>,
     witnesses: HashSet<PolyID>,
     global_range_constraints: GlobalConstraints<T>,
@@ -48,62 +51,35 @@ impl<'a, T: FieldElement> Machine<'a, T> for Generator<'a, T> {
         left: &[AffineExpression<&'a AlgebraicReference, T>],
         right: &'a SelectedExpressions<Expression<T>>,
     ) -> Option<EvalResult<'a, T>> {
-        if right.selector != self.latch {
-            None
-        } else {
-            log::trace!("Start processing secondary VM '{}'", self.name());
-            log::trace!("Arguments:");
-            for (r, l) in right.expressions.iter().zip(left) {
-                log::trace!("  {r} = {l}");
-            }
-
-            let first_row = self
-                .data
-                .last()
-                .cloned()
-                .unwrap_or_else(|| self.compute_partial_first_row(mutable_state));
-
-            let outer_query = OuterQuery {
-                left: left.to_vec(),
-                right,
-            };
-            let ProcessResult { eval_value, block } =
-                self.process(first_row, 0, mutable_state, Some(outer_query), false);
-
-            if eval_value.is_complete() {
-                log::trace!("End processing VM '{}' (successfully)", self.name());
-                // Remove the last row of the previous block, as it is the first row of the current
-                // block.
-                self.data.pop();
-                self.data.extend(block);
-            } else {
-                log::trace!("End processing VM '{}' (incomplete)", self.name());
-            }
-            Some(Ok(eval_value))
-        }
+        (right.selector == self.latch)
+            .then(|| self.process_plookup_internal(mutable_state, left, right))
     }
 
     fn take_witness_col_values<'b, Q: QueryCallback<T>>(
         &mut self,
         fixed_lookup: &'b mut FixedLookup<T>,
         query_callback: &'b mut Q,
-    ) -> HashMap<String, Vec<T>> {
+    ) -> Result<HashMap<String, Vec<T>>, WitgenError<T>> {
         log::debug!("Finalizing VM: {}", self.name());
 
         // In this stage, we don't have access to other machines, as they might already be finalized.
+        // Values of copy-constrained cells are not propagated into the remaining rows either.
+        let mut copy_constraints = CopyConstraints::default();
         let mut mutable_state_no_machines = MutableState {
             fixed_lookup,
+            copy_constraints: &mut copy_constraints,
             machines: [].into_iter().into(),
             query_callback,
         };
 
-        self.fill_remaining_rows(&mut mutable_state_no_machines);
+        self.fill_remaining_rows(&mut mutable_state_no_machines)?;
         self.fix_first_row();
 
-        self.data
+        Ok(self
+            .data
             .take_transposed()
             .map(|(id, (values, _))| (self.fixed_data.column_name(&id).to_string(), values))
-            .collect()
+            .collect())
     }
 }
 
@@ -120,6 +96,7 @@ impl<'a, T: FieldElement> Generator<'a, T> {
         Self {
             name,
             fixed_data,
+            degree: fixed_data.common_degree(&witnesses),
             identities: identities.to_vec(),
             witnesses,
             global_range_constraints: global_range_constraints.clone(),
@@ -129,19 +106,61 @@ impl<'a, T: FieldElement> Generator<'a, T> {
     }
 
     /// Runs the machine without any arguments from the first row.
-    pub fn run<'b, Q: QueryCallback<T>>(&mut self, mutable_state: &mut MutableState<'a, 'b, T, Q>) {
+    pub fn run<'b, Q: QueryCallback<T>>(
+        &mut self,
+        mutable_state: &mut MutableState<'a, 'b, T, Q>,
+    ) -> Result<(), WitgenError<T>> {
         record_start(self.name());
         assert!(self.data.is_empty());
-        let first_row = self.compute_partial_first_row(mutable_state);
-        self.data = self.process(first_row, 0, mutable_state, None, true).block;
+        let result = self
+            .compute_partial_first_row(mutable_state)
+            .and_then(|first_row| self.process(first_row, 0, mutable_state, None, true));
         record_end(self.name());
+        self.data = result?.block;
+        Ok(())
+    }
+
+    fn process_plookup_internal<Q: QueryCallback<T>>(
+        &mut self,
+        mutable_state: &mut MutableState<'a, '_, T, Q>,
+        left: &[AffineExpression<&'a AlgebraicReference, T>],
+        right: &'a SelectedExpressions<Expression<T>>,
+    ) -> EvalResult<'a, T> {
+        log::trace!("Start processing secondary VM '{}'", self.name());
+        log::trace!("Arguments:");
+        for (r, l) in right.expressions.iter().zip(left) {
+            log::trace!("  {r} = {l}");
+        }
+
+        let first_row = match self.data.last() {
+            Some(row) => row.clone(),
+            None => self.compute_partial_first_row(mutable_state)?,
+        };
+
+        let outer_query = OuterQuery {
+            left: left.to_vec(),
+            right,
+        };
+        let ProcessResult { eval_value, block } =
+            self.process(first_row, 0, mutable_state, Some(outer_query), false)?;
+
+        if eval_value.is_complete() {
+            log::trace!("End processing VM '{}' (successfully)", self.name());
+            // Remove the last row of the previous block, as it is the first row of the current
+            // block.
+            self.data.pop();
+            self.data.extend(block);
+        } else {
+            log::trace!("End processing VM '{}' (incomplete)", self.name());
+        }
+        Ok(eval_value)
     }
 
     fn fill_remaining_rows<Q: QueryCallback<T>>(
         &mut self,
         mutable_state: &mut MutableState<'a, '_, T, Q>,
-    ) {
-        if self.data.len() < self.fixed_data.degree as usize + 1 {
+    ) -> Result<(), WitgenError<T>> {
+        if self.data.len() < self.degree as usize + 1 {
             assert!(self.latch.is_some());
 
             let first_row = self.data.pop().unwrap();
@@ -151,11 +170,12 @@ impl<'a, T: FieldElement> Generator<'a, T> {
                 mutable_state,
                 None,
                 false,
-            );
+            )?;
             assert!(eval_value.is_complete());
 
             self.data.extend(block);
         }
+        Ok(())
     }
 
     /// Runs the solver on the row pair (degree - 1, 0) in order to partially compute the first
@@ -163,7 +183,7 @@ impl<'a, T: FieldElement> Generator<'a, T> {
     fn compute_partial_first_row<Q: QueryCallback<T>>(
         &self,
         mutable_state: &mut MutableState<'a, '_, T, Q>,
-    ) -> Row<'a, T> {
+    ) -> Result<Row<'a, T>, WitgenError<T>> {
         // Use `BlockProcessor` + `DefaultSequenceIterator` using a "block size" of 0. Because `BlockProcessor`
         // expects `data` to include the row before and after the block, this means we'll run the
         // solver on exactly one row pair.
@@ -174,7 +194,7 @@ impl<'a, T: FieldElement> Generator<'a, T> {
         let data = FinalizableData::with_initial_rows_in_progress(
             &self.witnesses,
             [
-                row_factory.fresh_row(self.fixed_data.degree - 1),
+                row_factory.fresh_row(self.degree - 1),
                 row_factory.fresh_row(0),
             ]
             .into_iter(),
@@ -190,7 +210,7 @@ impl<'a, T: FieldElement> Generator<'a, T> {
             .filter_map(|identity| identity.contains_next_ref().then_some(*identity))
             .collect::<Vec<_>>();
         let mut processor = BlockProcessor::new(
-            self.fixed_data.degree - 1,
+            self.degree - 1,
             data,
             mutable_state,
             &identities_with_next_reference,
@@ -200,10 +220,13 @@ impl<'a, T: FieldElement> Generator<'a, T> {
         let mut sequence_iterator = ProcessingSequenceIterator::Default(
             DefaultSequenceIterator::new(0, identities_with_next_reference.len(), None),
         );
-        processor.solve(&mut sequence_iterator).unwrap();
-        let first_row = processor.finish().remove(1);
-
-        first_row
+        processor
+            .solve(&mut sequence_iterator)
+            .map_err(|error| WitgenError::FirstRowFailed {
+                machine: self.name.clone(),
+                error,
+            })?;
+        Ok(processor.finish().remove(1))
     }
 
     fn process<Q: QueryCallback<T>>(
@@ -213,7 +236,7 @@ impl<'a, T: FieldElement> Generator<'a, T> {
         mutable_state: &mut MutableState<'a, '_, T, Q>,
         outer_query: Option<OuterQuery<'a, T>>,
         is_main_run: bool,
-    ) -> ProcessResult<'a, T> {
+    ) -> Result<ProcessResult<'a, T>, WitgenError<T>> {
         log::trace!(
             "Running main machine from row {row_offset} with the following initial values in the first row:\n{}", first_row.render_values(false, None)
         );
@@ -223,7 +246,9 @@ impl<'a, T: FieldElement> Generator<'a, T> {
             [first_row].into_iter(),
         );
         let mut processor = VmProcessor::new(
+            self.name.clone(),
             row_offset,
+            self.degree,
             self.fixed_data,
             &self.identities,
             &self.witnesses,
@@ -234,15 +259,15 @@ impl<'a, T: FieldElement> Generator<'a, T> {
         if let Some(outer_query) = outer_query {
             processor = processor.with_outer_query(outer_query);
         }
-        let eval_value = processor.run(is_main_run);
+        let eval_value = processor.run(is_main_run)?;
         let block = processor.finish();
-        ProcessResult { eval_value, block }
+        Ok(ProcessResult { eval_value, block })
     }
 
     /// At the end of the solving algorithm, we'll have computed the first row twice
     /// (as row 0 and as row <degree>). This function merges the two versions.
     fn fix_first_row(&mut self) {
-        assert_eq!(self.data.len() as DegreeType, self.fixed_data.degree + 1);
+        assert_eq!(self.data.len() as DegreeType, self.degree + 1);
 
         let last_row = self.data.pop().unwrap();
         self.data[0] = WitnessColumnMap::from(self.data[0].values().zip(last_row.values()).map(
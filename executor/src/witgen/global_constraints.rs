@@ -178,7 +178,11 @@ fn propagate_constraints<T: FieldElement>(
                 }
             }
         }
-        IdentityKind::Plookup | IdentityKind::Permutation | IdentityKind::Connect => {
+        IdentityKind::Connect => {
+            // The fixed columns on the right hand side describe a permutation of the
+            // cells on the left hand side, their values do not constrain the left hand side.
+        }
+        IdentityKind::Plookup | IdentityKind::Permutation => {
             if identity.left.selector.is_some() || identity.right.selector.is_some() {
                 return (known_constraints, false);
             }
@@ -100,11 +100,7 @@ impl<'a, 'b, 'c, T: FieldElement, Q: QueryCallback<T>> IdentityProcessor<'a, 'b,
             IdentityKind::Plookup | IdentityKind::Permutation => {
                 self.process_plookup(identity, rows)
             }
-            kind => {
-                unimplemented!(
-                    "Identity of kind {kind:?} is not supported by the identity processor."
-                )
-            }
+            IdentityKind::Connect => self.process_connect(identity, rows),
         };
         report_identity_solving(identity, &result);
         result
@@ -170,6 +166,7 @@ impl<'a, 'b, 'c, T: FieldElement, Q: QueryCallback<T>> IdentityProcessor<'a, 'b,
             let (current, others) = self.mutable_state.machines.split(i);
             let mut mutable_state = MutableState {
                 fixed_lookup: self.mutable_state.fixed_lookup,
+                copy_constraints: self.mutable_state.copy_constraints,
                 machines: others,
                 query_callback: self.mutable_state.query_callback,
             };
@@ -187,6 +184,40 @@ impl<'a, 'b, 'c, T: FieldElement, Q: QueryCallback<T>> IdentityProcessor<'a, 'b,
         unimplemented!("No executor machine matched identity `{identity}`")
     }
 
+    /// Propagates values along the cycles of a connect identity: Known values on the
+    /// left hand side are recorded for their cycle, unknown ones are set to the value
+    /// recorded for their cycle, if any.
+    fn process_connect(
+        &mut self,
+        identity: &'a Identity<Expression<T>>,
+        rows: &RowPair<'_, 'a, T>,
+    ) -> EvalResult<'a, T> {
+        let row = rows.current_row_index;
+        let mut updates = EvalValue::complete(vec![]);
+        for (index, expression) in identity.left.expressions.iter().enumerate() {
+            let evaluated = match rows.evaluate(expression) {
+                Ok(evaluated) => evaluated,
+                Err(cause) => {
+                    updates.status = updates.status.combine(cause);
+                    continue;
+                }
+            };
+            let copy_constraints = &mut self.mutable_state.copy_constraints;
+            match evaluated.constant_value() {
+                Some(value) => copy_constraints.set_value(identity.id, index, row, value)?,
+                None => match copy_constraints.value(identity.id, index, row) {
+                    Some(value) => updates
+                        .combine((evaluated - value.into()).solve_with_range_constraints(rows)?),
+                    None => {
+                        updates.status =
+                            updates.status.combine(IncompleteCause::DataNotYetAvailable);
+                    }
+                },
+            }
+        }
+        Ok(updates)
+    }
+
     /// Handles the lookup that connects the current machine to the calling machine.
     /// Arguments:
     /// - `left`: The evaluation of the left side of the lookup (symbolic for unknown values).
@@ -12,7 +12,7 @@ use crate::witgen::rows::{CellValue, RowFactory, RowPair, UnknownStrategy};
 use crate::witgen::sequence_iterator::{ProcessingSequenceCache, ProcessingSequenceIterator};
 use crate::witgen::util::try_to_simple_poly;
 use crate::witgen::{machines::Machine, EvalError, EvalValue, IncompleteCause};
-use crate::witgen::{MutableState, QueryCallback};
+use crate::witgen::{MutableState, QueryCallback, WitgenError};
 use powdr_ast::analyzed::{
     AlgebraicExpression as Expression, AlgebraicReference, Identity, IdentityKind, PolyID,
     PolynomialType,
@@ -47,6 +47,8 @@ impl<'a, T: FieldElement> ProcessResult<'a, T> {
 pub struct BlockMachine<'a, T: FieldElement> {
     /// Block size, the period of the selector.
     block_size: usize,
+    /// The number of rows of the machine.
+    degree: DegreeType,
     /// The right-hand side of the connecting identity, needed to identify
     /// when this machine is responsible.
     connecting_rhs: BTreeSet<SelectedExpressions<Expression<T>>>,
@@ -112,7 +114,8 @@ impl<'a, T: FieldElement> BlockMachine<'a, T> {
                 Some((block_size, connecting_rhs))
             })
             .map(|(block_size, connecting_rhs)| {
-                assert!(block_size <= fixed_data.degree as usize);
+                let degree = fixed_data.common_degree(witness_cols);
+                assert!(block_size <= degree as usize);
                 let row_factory = RowFactory::new(fixed_data, global_range_constraints.clone());
                 // Start out with a block filled with unknown values so that we do not have to deal with wrap-around
                 // when storing machine witness data.
@@ -124,6 +127,7 @@ impl<'a, T: FieldElement> BlockMachine<'a, T> {
                 BlockMachine {
                     name,
                     block_size,
+                    degree,
                     connecting_rhs,
                     identities: identities.to_vec(),
                     data,
@@ -213,7 +217,7 @@ impl<'a, T: FieldElement> Machine<'a, T> for BlockMachine<'a, T> {
         &mut self,
         _fixed_lookup: &'b mut FixedLookup<T>,
         _query_callback: &'b mut Q,
-    ) -> HashMap<String, Vec<T>> {
+    ) -> Result<HashMap<String, Vec<T>>, WitgenError<T>> {
         if self.data.len() < 2 * self.block_size {
             log::warn!(
                 "Filling empty blocks with zeros, because the block machine is never used. \
@@ -233,7 +237,7 @@ impl<'a, T: FieldElement> Machine<'a, T> for BlockMachine<'a, T> {
 
                 // For all constraints to be satisfied, unused cells have to be filled with valid values.
                 // We do this, we construct a default block, by repeating the first input to the block machine.
-                values.resize(self.fixed_data.degree as usize, None);
+                values.resize(self.degree as usize, None);
 
                 let second_block_values = values.iter().skip(self.block_size).take(self.block_size);
 
@@ -263,9 +267,10 @@ impl<'a, T: FieldElement> Machine<'a, T> for BlockMachine<'a, T> {
             })
             .collect();
         self.handle_last_row(&mut data);
-        data.into_iter()
+        Ok(data
+            .into_iter()
             .map(|(id, values)| (self.fixed_data.column_name(&id).to_string(), values))
-            .collect()
+            .collect())
     }
 }
 
@@ -287,7 +292,7 @@ impl<'a, T: FieldElement> BlockMachine<'a, T> {
                 .ends_with("_operation_id_no_change")
             {
                 log::trace!("Setting _operation_id_no_change to 0.");
-                col[self.fixed_data.degree as usize - 1] = T::zero();
+                col[self.degree as usize - 1] = T::zero();
             }
         }
     }
@@ -430,7 +435,7 @@ impl<'a, T: FieldElement> BlockMachine<'a, T> {
     /// This is necessary to handle non-rectangular block machines, which already use
     /// unused cells in the previous block.
     fn append_block(&mut self, mut new_block: FinalizableData<'a, T>) -> Result<(), EvalError<T>> {
-        if self.rows() + self.block_size as DegreeType >= self.fixed_data.degree {
+        if self.rows() + self.block_size as DegreeType >= self.degree {
             return Err(EvalError::RowsExhausted);
         }
 
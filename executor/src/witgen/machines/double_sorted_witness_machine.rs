@@ -10,7 +10,7 @@ use crate::witgen::affine_expression::AffineExpression;
 use crate::witgen::global_constraints::GlobalConstraints;
 use crate::witgen::util::is_simple_poly_of_name;
 use crate::witgen::{EvalResult, FixedData, MutableState, QueryCallback};
-use crate::witgen::{EvalValue, IncompleteCause};
+use crate::witgen::{EvalValue, IncompleteCause, WitgenError};
 use powdr_number::{DegreeType, FieldElement};
 
 use powdr_ast::analyzed::{
@@ -126,7 +126,7 @@ impl<T: FieldElement> DoubleSortedWitnesses<T> {
                 Some(Self {
                     name,
                     namespace,
-                    degree: fixed_data.degree,
+                    degree: fixed_data.common_degree(witness_cols),
                     diff_columns_base,
                     has_bootloader_write_column,
                     trace: Default::default(),
@@ -139,7 +139,7 @@ impl<T: FieldElement> DoubleSortedWitnesses<T> {
             Some(Self {
                 name,
                 namespace,
-                degree: fixed_data.degree,
+                degree: fixed_data.common_degree(witness_cols),
                 diff_columns_base: None,
                 has_bootloader_write_column,
                 trace: Default::default(),
@@ -180,7 +180,7 @@ impl<'a, T: FieldElement> Machine<'a, T> for DoubleSortedWitnesses<T> {
         &mut self,
         _fixed_lookup: &'b mut FixedLookup<T>,
         _query_callback: &'b mut Q,
-    ) -> HashMap<String, Vec<T>> {
+    ) -> Result<HashMap<String, Vec<T>>, WitgenError<T>> {
         let mut addr = vec![];
         let mut step = vec![];
         let mut value = vec![];
@@ -279,7 +279,7 @@ impl<'a, T: FieldElement> Machine<'a, T> for DoubleSortedWitnesses<T> {
             vec![]
         };
 
-        [
+        Ok([
             (self.namespaced("m_value"), value),
             (self.namespaced("m_addr"), addr),
             (self.namespaced("m_step"), step),
@@ -290,7 +290,7 @@ impl<'a, T: FieldElement> Machine<'a, T> for DoubleSortedWitnesses<T> {
         .into_iter()
         .chain(diff_columns)
         .chain(is_bootloader_write)
-        .collect()
+        .collect())
     }
 }
 
@@ -108,7 +108,13 @@ impl<T: FieldElement> IndexedColumns<T> {
             .map(|id| fixed_data.fixed_cols[id].values)
             .collect::<Vec<_>>();
 
-        let index: BTreeMap<Vec<T>, IndexValue> = (0..fixed_data.degree as usize)
+        let degree = input_column_values
+            .iter()
+            .chain(&output_column_values)
+            .map(|column| column.len())
+            .max()
+            .unwrap_or_default();
+        let index: BTreeMap<Vec<T>, IndexValue> = (0..degree)
             .fold(
                 (
                     BTreeMap::<Vec<T>, IndexValue>::default(),
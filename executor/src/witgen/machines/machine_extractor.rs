@@ -39,6 +39,11 @@ pub fn split_out_machines<'a, T: FieldElement>(
     let mut base_identities = identities.clone();
     let mut id_counter = 0;
     for id in &identities {
+        if id.kind == IdentityKind::Connect {
+            // Connect identities do not call into other machines, their right hand
+            // side only consists of fixed columns.
+            continue;
+        }
         // Extract all witness columns in the RHS of the lookup.
         let lookup_witnesses = &refs_in_selected_expressions(&id.right) & (&remaining_witnesses);
         if lookup_witnesses.is_empty() {
@@ -205,7 +210,15 @@ fn all_row_connected_witnesses<T>(
                         witnesses.extend(in_identity);
                     }
                 }
-                IdentityKind::Plookup | IdentityKind::Permutation | IdentityKind::Connect => {
+                IdentityKind::Connect => {
+                    // All witnesses on the left hand side are in the same row,
+                    // the right hand side only consists of fixed columns.
+                    let in_lhs = &refs_in_selected_expressions(&i.left) & all_witnesses;
+                    if in_lhs.intersection(&witnesses).next().is_some() {
+                        witnesses.extend(in_lhs);
+                    }
+                }
+                IdentityKind::Plookup | IdentityKind::Permutation => {
                     // If we already have witnesses on the LHS, include the LHS,
                     // and vice-versa, but not across the "sides".
                     let in_lhs = &refs_in_selected_expressions(&i.left) & all_witnesses;
@@ -20,6 +20,7 @@ use super::EvalResult;
 use super::FixedData;
 use super::MutableState;
 use super::QueryCallback;
+use super::WitgenError;
 
 mod block_machine;
 mod double_sorted_witness_machine;
@@ -67,7 +68,7 @@ pub trait Machine<'a, T: FieldElement>: Send + Sync {
         &mut self,
         fixed_lookup: &'b mut FixedLookup<T>,
         query_callback: &'b mut Q,
-    ) -> HashMap<String, Vec<T>>;
+    ) -> Result<HashMap<String, Vec<T>>, WitgenError<T>>;
 }
 
 /// All known implementations of [Machine].
@@ -114,7 +115,7 @@ impl<'a, T: FieldElement> Machine<'a, T> for KnownMachine<'a, T> {
         &mut self,
         fixed_lookup: &'b mut FixedLookup<T>,
         query_callback: &'b mut Q,
-    ) -> HashMap<String, Vec<T>> {
+    ) -> Result<HashMap<String, Vec<T>>, WitgenError<T>> {
         match self {
             KnownMachine::SortedWitnesses(m) => {
                 m.take_witness_col_values(fixed_lookup, query_callback)
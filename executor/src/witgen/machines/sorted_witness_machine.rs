@@ -10,11 +10,11 @@ use crate::witgen::{
     expression_evaluator::ExpressionEvaluator, fixed_evaluator::FixedEvaluator,
     symbolic_evaluator::SymbolicEvaluator,
 };
-use crate::witgen::{EvalValue, IncompleteCause, MutableState, QueryCallback};
+use crate::witgen::{EvalValue, IncompleteCause, MutableState, QueryCallback, WitgenError};
 use powdr_ast::analyzed::{
     AlgebraicExpression as Expression, AlgebraicReference, Identity, IdentityKind, PolyID,
 };
-use powdr_number::FieldElement;
+use powdr_number::{DegreeType, FieldElement};
 
 /// A machine that can support a lookup in a set of columns that are sorted
 /// by one specific column and values in that column have to be unique.
@@ -30,6 +30,7 @@ pub struct SortedWitnesses<'a, T> {
     data: BTreeMap<T, Vec<Option<T>>>,
     fixed_data: &'a FixedData<'a, T>,
     name: String,
+    degree: DegreeType,
 }
 
 impl<'a, T: FieldElement> SortedWitnesses<'a, T> {
@@ -42,7 +43,8 @@ impl<'a, T: FieldElement> SortedWitnesses<'a, T> {
         if identities.len() != 1 {
             return None;
         }
-        check_identity(fixed_data, identities.first().unwrap()).map(|key_col| {
+        let degree = fixed_data.common_degree(witnesses);
+        check_identity(fixed_data, identities.first().unwrap(), degree).map(|key_col| {
             let witness_positions = witnesses
                 .iter()
                 .filter(|&w| *w != key_col)
@@ -57,6 +59,7 @@ impl<'a, T: FieldElement> SortedWitnesses<'a, T> {
                 witness_positions,
                 data: Default::default(),
                 fixed_data,
+                degree,
             }
         })
     }
@@ -65,6 +68,7 @@ impl<'a, T: FieldElement> SortedWitnesses<'a, T> {
 fn check_identity<T: FieldElement>(
     fixed_data: &FixedData<T>,
     id: &Identity<Expression<T>>,
+    degree: DegreeType,
 ) -> Option<PolyID> {
     // Looking for NOTLAST { A' - A } in { POSITIVE }
     if id.kind != IdentityKind::Plookup
@@ -82,7 +86,7 @@ fn check_identity<T: FieldElement>(
 
     // TODO this could be rather slow. We should check the code for identity instead
     // of evaluating it.
-    let degree = fixed_data.degree as usize;
+    let degree = degree as usize;
     for row in 0..(degree) {
         let ev = ExpressionEvaluator::new(FixedEvaluator::new(fixed_data, row));
         let nl = ev.evaluate(notlast).ok()?.constant_value()?;
@@ -164,14 +168,14 @@ impl<'a, T: FieldElement> Machine<'a, T> for SortedWitnesses<'a, T> {
         &mut self,
         _fixed_lookup: &'b mut FixedLookup<T>,
         _query_callback: &'b mut Q,
-    ) -> HashMap<String, Vec<T>> {
+    ) -> Result<HashMap<String, Vec<T>>, WitgenError<T>> {
         let mut result = HashMap::new();
 
         let (mut keys, mut values): (Vec<_>, Vec<_>) =
             std::mem::take(&mut self.data).into_iter().unzip();
 
         let mut last_key = keys.last().cloned().unwrap_or_default();
-        while keys.len() < self.fixed_data.degree as usize {
+        while keys.len() < self.degree as usize {
             last_key += 1u64.into();
             keys.push(last_key);
         }
@@ -182,11 +186,11 @@ impl<'a, T: FieldElement> Machine<'a, T> for SortedWitnesses<'a, T> {
                 .iter_mut()
                 .map(|row| std::mem::take(&mut row[i]).unwrap_or_default())
                 .collect::<Vec<_>>();
-            col_values.resize(self.fixed_data.degree as usize, 0.into());
+            col_values.resize(self.degree as usize, 0.into());
             result.insert(self.fixed_data.column_name(col).to_string(), col_values);
         }
 
-        result
+        Ok(result)
     }
 }
 
@@ -11,7 +11,7 @@ use powdr_number::{DegreeType, FieldElement};
 
 use crate::witgen::{
     affine_expression::AffineExpression, util::try_to_simple_poly, EvalError, EvalResult,
-    EvalValue, FixedData, IncompleteCause, MutableState, QueryCallback,
+    EvalValue, FixedData, IncompleteCause, MutableState, QueryCallback, WitgenError,
 };
 
 use super::{FixedLookup, Machine};
@@ -42,6 +42,8 @@ pub struct WriteOnceMemory<'a, T: FieldElement> {
     /// The memory content
     data: BTreeMap<DegreeType, Vec<Option<T>>>,
     name: String,
+    /// The number of rows of the machine
+    degree: DegreeType,
 }
 
 impl<'a, T: FieldElement> WriteOnceMemory<'a, T> {
@@ -92,8 +94,9 @@ impl<'a, T: FieldElement> WriteOnceMemory<'a, T> {
             })
             .collect::<Vec<_>>();
 
+        let degree = fixed_data.common_degree(&value_polys);
         let mut key_to_index = BTreeMap::new();
-        for row in 0..fixed_data.degree {
+        for row in 0..degree {
             let key = key_polys
                 .iter()
                 .map(|k| fixed_data.fixed_cols[k].values[row as usize])
@@ -111,6 +114,7 @@ impl<'a, T: FieldElement> WriteOnceMemory<'a, T> {
             value_polys,
             key_to_index,
             data: BTreeMap::new(),
+            degree,
         })
     }
 
@@ -219,8 +223,9 @@ impl<'a, T: FieldElement> Machine<'a, T> for WriteOnceMemory<'a, T> {
         &mut self,
         _fixed_lookup: &'b mut FixedLookup<T>,
         _query_callback: &'b mut Q,
-    ) -> HashMap<String, Vec<T>> {
-        self.value_polys
+    ) -> Result<HashMap<String, Vec<T>>, WitgenError<T>> {
+        Ok(self
+            .value_polys
             .iter()
             .enumerate()
             .map(|(value_index, poly)| {
@@ -229,11 +234,11 @@ impl<'a, T: FieldElement> Machine<'a, T> for WriteOnceMemory<'a, T> {
                     .clone()
                     .map(|mut external_values| {
                         // External witness values might only be provided partially.
-                        external_values.resize(self.fixed_data.degree as usize, T::zero());
+                        external_values.resize(self.degree as usize, T::zero());
                         external_values
                     })
                     .unwrap_or_else(|| {
-                        let mut column = vec![T::zero(); self.fixed_data.degree as usize];
+                        let mut column = vec![T::zero(); self.degree as usize];
                         for (row, values) in self.data.iter() {
                             column[*row as usize] = values[value_index].unwrap_or_default();
                         }
@@ -241,6 +246,6 @@ impl<'a, T: FieldElement> Machine<'a, T> for WriteOnceMemory<'a, T> {
                     });
                 (self.fixed_data.column_name(poly).to_string(), column)
             })
-            .collect()
+            .collect())
     }
 }
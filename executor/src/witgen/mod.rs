@@ -1,13 +1,17 @@
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::sync::Arc;
 
+use itertools::Itertools;
+
 use powdr_ast::analyzed::{
     AlgebraicReference, Analyzed, Expression, FunctionValueDefinition, PolyID, PolynomialType,
     SymbolKind,
 };
 use powdr_number::{DegreeType, FieldElement};
 
+use self::copy_constraints::CopyConstraints;
 use self::data_structures::column_map::{FixedColumnMap, WitnessColumnMap};
+pub use self::error::{FailedIdentity, RowCells, RowFailure, RowFailureKind, WitgenError};
 pub use self::eval_result::{
     Constraint, Constraints, EvalError, EvalResult, EvalStatus, EvalValue, IncompleteCause,
 };
@@ -18,11 +22,13 @@ use self::machines::machine_extractor::ExtractionOutput;
 use self::machines::profiling::{record_end, record_start, reset_and_print_profile_summary};
 use self::machines::{FixedLookup, Machine};
 
-mod affine_expression;
+pub(crate) mod affine_expression;
 mod block_processor;
+mod copy_constraints;
 mod data_structures;
+mod error;
 mod eval_result;
-mod expression_evaluator;
+pub(crate) mod expression_evaluator;
 pub mod fixed_evaluator;
 mod generator;
 mod global_constraints;
@@ -58,6 +64,7 @@ pub fn unused_query_callback<T>() -> impl QueryCallback<T> {
 /// Everything [Generator] needs to mutate in order to compute a new row.
 pub struct MutableState<'a, 'b, T: FieldElement, Q: QueryCallback<T>> {
     pub fixed_lookup: &'b mut FixedLookup<T>,
+    pub copy_constraints: &'b mut CopyConstraints<T>,
     pub machines: Machines<'a, 'b, T>,
     pub query_callback: &'b mut Q,
 }
@@ -94,8 +101,9 @@ impl<'a, 'b, T: FieldElement> WitnessGenerator<'a, 'b, T> {
     }
 
     /// Generates the committed polynomial values
-    /// @returns the values (in source order) and the degree of the polynomials.
-    pub fn generate(self) -> Vec<(String, Vec<T>)> {
+    /// @returns the values (in source order) and the degree of the polynomials,
+    /// or an error describing the row at which witness generation failed.
+    pub fn generate(self) -> Result<Vec<(String, Vec<T>)>, WitgenError<T>> {
         record_start(OUTER_CODE_NAME);
         let fixed = FixedData::new(
             self.analyzed,
@@ -122,9 +130,18 @@ impl<'a, 'b, T: FieldElement> WitnessGenerator<'a, 'b, T> {
             retained_identities,
             &constraints,
         );
+        let mut copy_constraints = match CopyConstraints::new(&fixed, identities.iter()) {
+            Ok(copy_constraints) => copy_constraints,
+            Err(reason) => {
+                record_end(OUTER_CODE_NAME);
+                reset_and_print_profile_summary();
+                return Err(WitgenError::Setup(reason));
+            }
+        };
         let mut query_callback = self.query_callback;
         let mut mutable_state = MutableState {
             fixed_lookup: &mut fixed_lookup,
+            copy_constraints: &mut copy_constraints,
             machines: Machines::from(machines.iter_mut()),
             query_callback: &mut query_callback,
         };
@@ -140,23 +157,27 @@ impl<'a, 'b, T: FieldElement> WitnessGenerator<'a, 'b, T> {
             None,
         );
 
-        generator.run(&mut mutable_state);
-
-        // Get columns from machines
-        let main_columns = generator
-            .take_witness_col_values(mutable_state.fixed_lookup, mutable_state.query_callback);
-        let mut columns = mutable_state
-            .machines
-            .iter_mut()
-            .flat_map(|m| {
-                m.take_witness_col_values(mutable_state.fixed_lookup, mutable_state.query_callback)
-                    .into_iter()
-            })
-            .chain(main_columns)
-            .collect::<BTreeMap<_, _>>();
+        let columns = generator.run(&mut mutable_state).and_then(|()| {
+            // Get columns from machines
+            let main_columns = generator
+                .take_witness_col_values(mutable_state.fixed_lookup, mutable_state.query_callback)?;
+            mutable_state
+                .machines
+                .iter_mut()
+                .map(|m| {
+                    m.take_witness_col_values(
+                        mutable_state.fixed_lookup,
+                        mutable_state.query_callback,
+                    )
+                })
+                .chain([Ok(main_columns)])
+                .flatten_ok()
+                .collect::<Result<BTreeMap<_, _>, _>>()
+        });
 
         record_end(OUTER_CODE_NAME);
         reset_and_print_profile_summary();
+        let mut columns = columns?;
 
         // Order columns according to the order of declaration.
         let witness_cols = self
@@ -175,7 +196,7 @@ impl<'a, 'b, T: FieldElement> WitnessGenerator<'a, 'b, T> {
         for (name, value) in extract_publics(&witness_cols, self.analyzed) {
             log::debug!("  {name:>30}: {value}");
         }
-        witness_cols
+        Ok(witness_cols)
     }
 }
 
@@ -201,7 +222,6 @@ pub fn extract_publics<T: FieldElement>(
 /// Data that is fixed for witness generation.
 pub struct FixedData<'a, T> {
     analyzed: &'a Analyzed<T>,
-    degree: DegreeType,
     fixed_cols: FixedColumnMap<FixedColumn<'a, T>>,
     witness_cols: WitnessColumnMap<WitnessColumn<'a, T>>,
     column_by_name: HashMap<String, PolyID>,
@@ -218,21 +238,23 @@ impl<'a, T: FieldElement> FixedData<'a, T> {
         let witness_cols =
             WitnessColumnMap::from(analyzed.committed_polys_in_source_order().iter().flat_map(
                 |(poly, value)| {
+                    let degree = poly.degree.unwrap_or_else(|| analyzed.degree());
                     poly.array_elements()
                         .map(|(name, poly_id)| {
                             let external_values = external_witness_values.remove(name.as_str());
                             if let Some(external_values) = &external_values {
-                                if external_values.len() != analyzed.degree() as usize {
+                                if external_values.len() != degree as usize {
                                     log::debug!(
                                         "External witness values for column {} were only partially provided \
                                          (length is {} but the degree is {})",
                                         name,
                                         external_values.len(),
-                                        analyzed.degree()
+                                        degree
                                     );
                                 }
                             }
-                            WitnessColumn::new(poly_id.id as usize, &name, value, external_values)
+                            let array_index = poly.is_array().then_some(poly_id.id - poly.id);
+                            WitnessColumn::new(poly_id.id as usize, &name, value, array_index, degree, external_values)
                         })
                         .collect::<Vec<_>>()
                 },
@@ -254,7 +276,6 @@ impl<'a, T: FieldElement> FixedData<'a, T> {
             FixedColumnMap::from(fixed_col_values.iter().map(|(n, v)| FixedColumn::new(n, v)));
         FixedData {
             analyzed,
-            degree: analyzed.degree(),
             fixed_cols,
             witness_cols,
             column_by_name: analyzed
@@ -282,8 +303,26 @@ impl<'a, T: FieldElement> FixedData<'a, T> {
         self.column_by_name.get(name).cloned()
     }
 
+    /// @returns the common degree of the given witness columns, or the largest
+    /// degree if no columns are given.
+    /// Panics if the columns have different degrees.
+    fn common_degree<'b>(&self, ids: impl IntoIterator<Item = &'b PolyID>) -> DegreeType {
+        let degrees = ids
+            .into_iter()
+            .map(|id| self.witness_cols[id].degree)
+            .collect::<BTreeSet<_>>();
+        match degrees.len() {
+            0 => *self.analyzed.degrees().last().expect("No degree declared."),
+            1 => *degrees.first().unwrap(),
+            _ => panic!(
+                "Expected all columns of a machine to have the same degree, but found degrees {}.",
+                degrees.iter().format(", ")
+            ),
+        }
+    }
+
     fn external_witness(&self, row: DegreeType, column: &PolyID) -> Option<T> {
-        let row = row % self.degree;
+        let row = row % self.witness_cols[column].degree;
         self.witness_cols[column]
             .external_values
             .as_ref()
@@ -312,6 +351,11 @@ pub struct WitnessColumn<'a, T> {
     poly: AlgebraicReference,
     /// The prover query expression, if any.
     query: Option<&'a Expression<T>>,
+    /// The index of the column in its array, if it is part of an array.
+    /// Queries of column arrays receive it as their first argument.
+    array_index: Option<u64>,
+    /// The number of rows of the column.
+    degree: DegreeType,
     /// A list of externally computed witness values, if any.
     /// The length of this list must be equal to the degree.
     external_values: Option<Vec<T>>,
@@ -322,6 +366,8 @@ impl<'a, T> WitnessColumn<'a, T> {
         id: usize,
         name: &str,
         value: &'a Option<FunctionValueDefinition<T>>,
+        array_index: Option<u64>,
+        degree: DegreeType,
         external_values: Option<Vec<T>>,
     ) -> WitnessColumn<'a, T> {
         let query = if let Some(FunctionValueDefinition::Query(query)) = value {
@@ -340,7 +386,89 @@ impl<'a, T> WitnessColumn<'a, T> {
         WitnessColumn {
             poly,
             query,
+            array_index,
+            degree,
             external_values,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use powdr_number::GoldilocksField;
+    use powdr_pil_analyzer::analyze_string;
+
+    use crate::constant_evaluator::generate;
+
+    use super::*;
+
+    #[test]
+    fn rejects_unsupported_connect_identity() {
+        let src = r#"
+            namespace main(4);
+                col witness a, b;
+                a = b;
+                { a } connect { b };
+        "#;
+        let analyzed = analyze_string::<GoldilocksField>(src);
+        let query_callback = unused_query_callback();
+        let error = WitnessGenerator::new(&analyzed, &[], &query_callback)
+            .generate()
+            .unwrap_err();
+
+        let WitgenError::Setup(reason) = error else {
+            panic!("Expected a setup error, got: {error}");
+        };
+        assert!(reason.starts_with(
+            "The right hand side of a connect identity must consist of fixed columns"
+        ));
+    }
+
+    #[test]
+    fn reports_failing_row() {
+        let src = r#"
+            constant %N = 8;
+            namespace main(%N);
+                col fixed FIRST = [1] + [0]*;
+                col witness a, b;
+                b = a + 1;
+        "#;
+        let analyzed = analyze_string::<GoldilocksField>(src);
+        let constants = generate(&analyzed)
+            .into_iter()
+            .map(|(n, c)| (n.to_string(), c))
+            .collect::<Vec<_>>();
+        let query_callback = unused_query_callback();
+        let external_witness_values = vec![
+            ("main.a".to_string(), vec![3.into(); 8]),
+            ("main.b".to_string(), vec![3.into(); 8]),
+        ];
+        let error = WitnessGenerator::new(&analyzed, &constants, &query_callback)
+            .with_external_witness_values(external_witness_values)
+            .generate()
+            .unwrap_err();
+
+        let WitgenError::RowFailed(failure) = error else {
+            panic!("Expected a row failure, got: {error}");
+        };
+        assert_eq!(failure.machine, "Main Machine");
+        assert_eq!(failure.row, 0);
+        assert_eq!(failure.kind, RowFailureKind::Unsatisfiable);
+        assert_eq!(
+            failure.current_row.known,
+            vec![
+                ("main.a".to_string(), 3.into()),
+                ("main.b".to_string(), 3.into())
+            ]
+        );
+        assert!(failure.current_row.unknown.is_empty());
+        let [FailedIdentity {
+            identity: Some((identity, _)),
+            error: EvalError::ConstraintUnsatisfiable(_),
+        }] = &failure.failed_identities[..]
+        else {
+            panic!("Unexpected failed identities: {failure}");
+        };
+        assert_eq!(identity, "main.b = (main.a + 1);");
+    }
+}
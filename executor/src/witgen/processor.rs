@@ -5,7 +5,6 @@ use powdr_ast::{
     parsed::SelectedExpressions,
 };
 use powdr_number::{DegreeType, FieldElement};
-use powdr_parser_util::lines::indent;
 
 use crate::witgen::{query_processor::QueryProcessor, util::try_to_simple_poly, Constraint};
 
@@ -183,7 +182,7 @@ impl<'a, 'b, 'c, T: FieldElement, Q: QueryCallback<T>> Processor<'a, 'b, 'c, T,
         let mut identity_processor = IdentityProcessor::new(self.fixed_data, self.mutable_state);
         let updates = identity_processor
             .process_identity(identity, &row_pair)
-            .map_err(|e| -> EvalError<T> {
+            .map_err(|e| {
                 log::warn!("Error in identity: {identity}");
                 log::warn!(
                     "Known values in current row (local: {row_index}, global {global_row_index}):\n{}",
@@ -197,7 +196,7 @@ impl<'a, 'b, 'c, T: FieldElement, Q: QueryCallback<T>> Processor<'a, 'b, 'c, T,
                         self.data[row_index + 1].render_values(false, Some(self.witness_cols)),
                     );
                 }
-                format!("{identity}:\n{}", indent(&format!("{e}"), "    ")).into()
+                e
             })?;
 
         if unknown_strategy == UnknownStrategy::Zero {
@@ -31,7 +31,7 @@ impl<'a, 'b, T: FieldElement, QueryCallback: super::QueryCallback<T>>
 
         if let Some(query) = column.query.as_ref() {
             if rows.get_value(&column.poly).is_none() {
-                return self.process_witness_query(query, &column.poly, rows);
+                return self.process_witness_query(query, &column.poly, column.array_index, rows);
             }
         }
         // Either no query or the value is already known.
@@ -42,9 +42,10 @@ impl<'a, 'b, T: FieldElement, QueryCallback: super::QueryCallback<T>>
         &mut self,
         query: &'a Expression<T>,
         poly: &'a AlgebraicReference,
+        array_index: Option<u64>,
         rows: &RowPair<T>,
     ) -> EvalResult<'a, T> {
-        let query_str = match self.interpolate_query(query, rows) {
+        let query_str = match self.interpolate_query(query, array_index, rows) {
             Ok(query) => query,
             Err(e) => {
                 return match e {
@@ -78,11 +79,14 @@ impl<'a, 'b, T: FieldElement, QueryCallback: super::QueryCallback<T>>
     fn interpolate_query(
         &self,
         query: &'a Expression<T>,
+        array_index: Option<u64>,
         rows: &RowPair<T>,
     ) -> Result<String, EvalError> {
-        let arguments = vec![Rc::new(Value::Integer(num_bigint::BigInt::from(
-            rows.current_row_index,
-        )))];
+        let arguments = array_index
+            .into_iter()
+            .chain([rows.current_row_index])
+            .map(|arg| Rc::new(Value::Integer(num_bigint::BigInt::from(arg))))
+            .collect();
         let symbols = Symbols {
             fixed_data: self.fixed_data,
             rows,
@@ -101,7 +105,25 @@ struct Symbols<'a, T: FieldElement> {
 impl<'a, T: FieldElement> SymbolLookup<'a, T, Reference<'a>> for Symbols<'a, T> {
     fn lookup(&self, name: &'a str) -> Result<Value<'a, T, Reference<'a>>, EvalError> {
         match self.fixed_data.try_column_by_name(name) {
-            Some(poly_id) => Ok(Value::Custom(Reference { name, poly_id })),
+            Some(poly_id) => {
+                let (symbol, _) = &self.fixed_data.analyzed.definitions[name];
+                Ok(if symbol.is_array() {
+                    // Column arrays evaluate to arrays of references to their elements.
+                    Value::Array(
+                        symbol
+                            .array_elements()
+                            .map(|(_, poly_id)| {
+                                Value::Custom(Reference {
+                                    name: self.fixed_data.column_name(&poly_id),
+                                    poly_id,
+                                })
+                            })
+                            .collect(),
+                    )
+                } else {
+                    Value::Custom(Reference { name, poly_id })
+                })
+            }
             None => match self.fixed_data.analyzed.definitions.get(&name.to_string()) {
                 Some((_, value)) => {
                     let value = value
@@ -9,6 +9,7 @@ use crate::witgen::Constraint;
 use super::{
     affine_expression::{AffineExpression, AffineResult},
     data_structures::column_map::WitnessColumnMap,
+    error::RowCells,
     expression_evaluator::ExpressionEvaluator,
     global_constraints::{GlobalConstraints, RangeConstraintSet},
     range_constraints::RangeConstraint,
@@ -145,6 +146,43 @@ impl<T: FieldElement> Row<'_, T> {
             .map(|(_, cell)| format!("    {:?}", cell))
             .join("\n")
     }
+
+    /// Collects the cells of the given columns, for error reporting.
+    /// `row` is the global index of this row.
+    pub fn cells(&self, row: DegreeType, cols: &HashSet<PolyID>) -> RowCells<T> {
+        let cells = self
+            .iter()
+            .filter(|(col, _)| cols.contains(col))
+            .map(|(_, cell)| cell)
+            .collect::<Vec<_>>();
+        let known = cells
+            .iter()
+            .filter_map(|cell| match cell.value {
+                CellValue::Known(v) => Some((cell.name.to_string(), v)),
+                _ => None,
+            })
+            // Nonzero first, then zero
+            .sorted_by_key(|(_, v)| v.is_zero())
+            .collect();
+        let unknown = cells
+            .iter()
+            .filter(|cell| !cell.value.is_known())
+            .map(|cell| cell.name.to_string())
+            .collect();
+        let range_constraints = cells
+            .iter()
+            .filter_map(|cell| match &cell.value {
+                CellValue::RangeConstraint(rc) => Some((cell.name.to_string(), rc.to_string())),
+                _ => None,
+            })
+            .collect();
+        RowCells {
+            row,
+            known,
+            unknown,
+            range_constraints,
+        }
+    }
 }
 
 /// A factory for rows, which knows the global range constraints and has pointers to column names.
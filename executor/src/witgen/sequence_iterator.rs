@@ -29,6 +29,9 @@ pub struct DefaultSequenceIterator {
     /// The number of rounds for the current row delta.
     /// If this number gets too large, we will assume that we're in an infinite loop and exit.
     current_round_count: usize,
+    /// Set to the current row delta if we exited because we were stuck in it
+    /// for too many rounds.
+    non_converging_row_delta: Option<i64>,
 
     /// The steps on which we made progress.
     progress_steps: Vec<SequenceStep>,
@@ -51,6 +54,7 @@ impl DefaultSequenceIterator {
             cur_row_delta_index: 0,
             cur_action_index: -1,
             current_round_count: 0,
+            non_converging_row_delta: None,
             progress_steps: vec![],
         }
     }
@@ -87,8 +91,10 @@ impl DefaultSequenceIterator {
 
     fn start_next_round(&mut self) {
         if self.current_round_count > MAX_ROUNDS_PER_ROW_DELTA {
-            panic!("In witness generation for block machine, we have been stuck in the same row for {MAX_ROUNDS_PER_ROW_DELTA} rounds. \
-                    This is a bug in the witness generation algorithm.");
+            // We have been stuck in the same row for too many rounds, give up.
+            self.non_converging_row_delta = Some(self.row_deltas[self.cur_row_delta_index]);
+            self.cur_row_delta_index = self.row_deltas.len();
+            return;
         }
 
         if !self.progress_in_current_round {
@@ -116,6 +122,12 @@ impl DefaultSequenceIterator {
         self.progress_in_current_round |= progress_in_last_step;
     }
 
+    /// Returns the row delta in which we have been stuck for too many rounds,
+    /// if the iteration was aborted because of that.
+    pub fn non_converging_row_delta(&self) -> Option<i64> {
+        self.non_converging_row_delta
+    }
+
     pub fn next(&mut self) -> Option<SequenceStep> {
         self.update_state();
 
@@ -186,6 +198,13 @@ impl ProcessingSequenceIterator {
         }
     }
 
+    pub fn non_converging_row_delta(&self) -> Option<i64> {
+        match self {
+            Self::Default(it) => it.non_converging_row_delta(),
+            Self::Cached(_) => None,
+        }
+    }
+
     pub fn is_cached(&self) -> bool {
         match self {
             Self::Default(_) => false,
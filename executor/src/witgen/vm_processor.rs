@@ -1,10 +1,8 @@
 use indicatif::{ProgressBar, ProgressStyle};
-use itertools::Itertools;
 use powdr_ast::analyzed::{
     AlgebraicExpression as Expression, AlgebraicReference, Identity, IdentityKind, PolyID,
 };
 use powdr_number::{DegreeType, FieldElement};
-use powdr_parser_util::lines::indent;
 use std::cmp::max;
 use std::collections::HashSet;
 use std::time::Instant;
@@ -16,7 +14,10 @@ use super::data_structures::finalizable_data::FinalizableData;
 use super::processor::{OuterQuery, Processor};
 
 use super::rows::{Row, RowFactory, UnknownStrategy};
-use super::{Constraints, EvalError, EvalValue, FixedData, MutableState, QueryCallback};
+use super::{
+    Constraints, EvalError, EvalValue, FailedIdentity, FixedData, MutableState, QueryCallback,
+    RowFailure, RowFailureKind, WitgenError,
+};
 
 /// Maximal period checked during loop detection.
 const MAX_PERIOD: usize = 4;
@@ -44,17 +45,23 @@ impl<'a, T: FieldElement> CompletableIdentities<'a, T> {
 }
 
 pub struct VmProcessor<'a, 'b, 'c, T: FieldElement, Q: QueryCallback<T>> {
+    /// The name of the machine being run, used for error reporting.
+    machine_name: String,
     /// The global index of the first row of [VmProcessor::data].
     row_offset: DegreeType,
+    /// The number of rows of the machine.
+    degree: DegreeType,
     /// The witness columns belonging to this machine
     witnesses: HashSet<PolyID>,
-    fixed_data: &'a FixedData<'a, T>,
     /// The subset of identities that contains a reference to the next row
     /// (precomputed once for performance reasons)
     identities_with_next_ref: Vec<&'a Identity<Expression<T>>>,
     /// The subset of identities that does not contain a reference to the next row
     /// (precomputed once for performance reasons)
     identities_without_next_ref: Vec<&'a Identity<Expression<T>>>,
+    /// Whether rows can be re-used once a loop is detected. This is not the case if
+    /// there are connect identities, because they relate cells in arbitrary rows.
+    loop_detection_enabled: bool,
     last_report: DegreeType,
     last_report_time: Instant,
     row_factory: RowFactory<'a, T>,
@@ -63,8 +70,11 @@ pub struct VmProcessor<'a, 'b, 'c, T: FieldElement, Q: QueryCallback<T>> {
 }
 
 impl<'a, 'b, 'c, T: FieldElement, Q: QueryCallback<T>> VmProcessor<'a, 'b, 'c, T, Q> {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
+        machine_name: String,
         row_offset: DegreeType,
+        degree: DegreeType,
         fixed_data: &'a FixedData<'a, T>,
         identities: &[&'a Identity<Expression<T>>],
         witnesses: &'c HashSet<PolyID>,
@@ -72,12 +82,15 @@ impl<'a, 'b, 'c, T: FieldElement, Q: QueryCallback<T>> VmProcessor<'a, 'b, 'c, T
         row_factory: RowFactory<'a, T>,
         mutable_state: &'c mut MutableState<'a, 'b, T, Q>,
     ) -> Self {
+        let loop_detection_enabled = identities
+            .iter()
+            .all(|identity| identity.kind != IdentityKind::Connect);
         let (identities_with_next, identities_without_next): (Vec<_>, Vec<_>) = identities
             .iter()
             .partition(|identity| identity.contains_next_ref());
         let processor = Processor::new(row_offset, data, mutable_state, fixed_data, witnesses);
 
-        let progress_bar = ProgressBar::new(fixed_data.degree);
+        let progress_bar = ProgressBar::new(degree);
         progress_bar.set_style(
             ProgressStyle::with_template(
                 "[{elapsed_precise} (ETA: {eta_precise})] {bar} {percent}% - {msg}",
@@ -86,12 +99,14 @@ impl<'a, 'b, 'c, T: FieldElement, Q: QueryCallback<T>> VmProcessor<'a, 'b, 'c, T
         );
 
         VmProcessor {
+            machine_name,
             row_offset,
+            degree,
             witnesses: witnesses.clone(),
-            fixed_data,
             identities_with_next_ref: identities_with_next,
             identities_without_next_ref: identities_without_next,
             row_factory,
+            loop_detection_enabled,
             last_report: 0,
             last_report_time: Instant::now(),
             processor,
@@ -110,11 +125,14 @@ impl<'a, 'b, 'c, T: FieldElement, Q: QueryCallback<T>> VmProcessor<'a, 'b, 'c, T
 
     /// Starting out with a single row (at a given offset), iteratively append rows
     /// until we have exhausted the rows or the latch expression (if available) evaluates to 1.
-    pub fn run(&mut self, is_main_run: bool) -> EvalValue<&'a AlgebraicReference, T> {
+    pub fn run(
+        &mut self,
+        is_main_run: bool,
+    ) -> Result<EvalValue<&'a AlgebraicReference, T>, WitgenError<T>> {
         assert!(self.processor.len() == 1);
 
         if is_main_run {
-            log::info!("Running main machine for {} rows", self.fixed_data.degree);
+            log::info!("Running main machine for {} rows", self.degree);
             self.progress_bar.reset();
             self.progress_bar.set_message("Starting...");
             self.progress_bar.tick();
@@ -129,7 +147,7 @@ impl<'a, 'b, 'c, T: FieldElement, Q: QueryCallback<T>> VmProcessor<'a, 'b, 'c, T
         } else {
             log::Level::Debug
         };
-        let rows_left = self.fixed_data.degree - self.row_offset + 1;
+        let rows_left = self.degree - self.row_offset + 1;
         let mut finalize_start = 1;
         for row_index in 0..rows_left {
             if is_main_run {
@@ -151,7 +169,11 @@ impl<'a, 'b, 'c, T: FieldElement, Q: QueryCallback<T>> VmProcessor<'a, 'b, 'c, T
             }
 
             // Check if we are in a loop.
-            if looping_period.is_none() && row_index % 100 == 0 && row_index > 0 {
+            if self.loop_detection_enabled
+                && looping_period.is_none()
+                && row_index % 100 == 0
+                && row_index > 0
+            {
                 looping_period = self.rows_are_repeating(row_index);
                 if let Some(p) = looping_period {
                     log::log!(
@@ -162,7 +184,7 @@ impl<'a, 'b, 'c, T: FieldElement, Q: QueryCallback<T>> VmProcessor<'a, 'b, 'c, T
             }
             if let Some(period) = looping_period {
                 let proposed_row = self.processor.row(row_index as usize - period).clone();
-                if !self.try_proposed_row(row_index, proposed_row) {
+                if !self.try_proposed_row(row_index, proposed_row)? {
                     log::log!(
                         loop_detection_log_level,
                         "Looping failed. Trying to generate regularly again. (Use RUST_LOG=debug to see whether this happens more often.) {row_index} {rows_left}"
@@ -178,40 +200,40 @@ impl<'a, 'b, 'c, T: FieldElement, Q: QueryCallback<T>> VmProcessor<'a, 'b, 'c, T
             // add and compute some values for the next row as well.
             if looping_period.is_none() && row_index != rows_left - 1 {
                 self.ensure_has_next_row(row_index);
-                outer_assignments.extend(self.compute_row(row_index).into_iter());
+                outer_assignments.extend(self.compute_row(row_index)?);
 
                 // Evaluate latch expression and return if it evaluates to 1.
                 if let Some(latch) = self.processor.latch_value(row_index as usize) {
                     if latch {
                         log::trace!("Machine returns!");
                         if self.processor.finshed_outer_query() {
-                            return EvalValue::complete(outer_assignments);
+                            return Ok(EvalValue::complete(outer_assignments));
                         } else {
-                            return EvalValue::incomplete_with_constraints(
+                            return Ok(EvalValue::incomplete_with_constraints(
                                 outer_assignments,
                                 IncompleteCause::BlockMachineLookupIncomplete,
-                            );
+                            ));
                         }
                     }
                 } else if self.processor.has_outer_query() {
                     // If we have an outer query (and therefore a latch expression),
                     // its value should be known at this point.
                     // Probably, we don't have all the necessary inputs.
-                    return EvalValue::incomplete(IncompleteCause::UnknownLatch);
+                    return Ok(EvalValue::incomplete(IncompleteCause::UnknownLatch));
                 }
             };
         }
 
         assert_eq!(
             self.processor.len() as DegreeType + self.row_offset,
-            self.fixed_data.degree + 1
+            self.degree + 1
         );
 
         if is_main_run {
             self.progress_bar.finish();
         }
 
-        EvalValue::complete(outer_assignments)
+        Ok(EvalValue::complete(outer_assignments))
     }
 
     /// Checks if the last rows are repeating and returns the period.
@@ -243,7 +265,10 @@ impl<'a, 'b, 'c, T: FieldElement, Q: QueryCallback<T>> VmProcessor<'a, 'b, 'c, T
         }
     }
 
-    fn compute_row(&mut self, row_index: DegreeType) -> Constraints<&'a AlgebraicReference, T> {
+    fn compute_row(
+        &mut self,
+        row_index: DegreeType,
+    ) -> Result<Constraints<&'a AlgebraicReference, T>, WitgenError<T>> {
         log::trace!(
             "===== Starting to process row: {}",
             row_index + self.row_offset
@@ -264,8 +289,7 @@ impl<'a, 'b, 'c, T: FieldElement, Q: QueryCallback<T>> VmProcessor<'a, 'b, 'c, T
                     .chain(self.loop_until_no_progress(row_index, &mut identities_with_next_ref)?)
                     .collect::<Vec<_>>())
             })
-            .map_err(|e| self.report_failure_and_panic_unsatisfiable(row_index, e))
-            .unwrap();
+            .map_err(|e| self.row_failure(row_index, RowFailureKind::Unsatisfiable, e))?;
 
         // Check that the computed row is "final" by asserting that all unknown values can
         // be set to 0.
@@ -287,8 +311,7 @@ impl<'a, 'b, 'c, T: FieldElement, Q: QueryCallback<T>> VmProcessor<'a, 'b, 'c, T
                     UnknownStrategy::Zero,
                 )
             })
-            .map_err(|e| self.report_failure_and_panic_underconstrained(row_index, e))
-            .unwrap();
+            .map_err(|e| self.row_failure(row_index, RowFailureKind::UnderConstrained, e))?;
         }
 
         log::trace!(
@@ -303,7 +326,7 @@ impl<'a, 'b, 'c, T: FieldElement, Q: QueryCallback<T>> VmProcessor<'a, 'b, 'c, T
             )
         );
 
-        outer_assignments
+        Ok(outer_assignments)
     }
 
     /// Loops over all identities and queries, until no further progress is made.
@@ -312,7 +335,7 @@ impl<'a, 'b, 'c, T: FieldElement, Q: QueryCallback<T>> VmProcessor<'a, 'b, 'c, T
         &mut self,
         row_index: DegreeType,
         identities: &mut CompletableIdentities<'a, T>,
-    ) -> Result<Constraints<&'a AlgebraicReference, T>, Vec<EvalError<T>>> {
+    ) -> Result<Constraints<&'a AlgebraicReference, T>, Vec<FailedIdentity<T>>> {
         let mut outer_assignments = vec![];
         loop {
             let mut progress =
@@ -322,7 +345,7 @@ impl<'a, 'b, 'c, T: FieldElement, Q: QueryCallback<T>> VmProcessor<'a, 'b, 'c, T
                 let (outer_query_progress, new_outer_assignments) = self
                     .processor
                     .process_outer_query(row_index)
-                    .map_err(|e| vec![e])?;
+                    .map_err(|error| vec![FailedIdentity::new(None, error)])?;
                 progress |= outer_query_progress;
                 outer_assignments.extend(new_outer_assignments);
             }
@@ -331,7 +354,7 @@ impl<'a, 'b, 'c, T: FieldElement, Q: QueryCallback<T>> VmProcessor<'a, 'b, 'c, T
             progress |= self
                 .processor
                 .process_queries(row_index)
-                .map_err(|e| vec![e])?;
+                .map_err(|error| vec![FailedIdentity::new(None, error)])?;
 
             if !progress {
                 break;
@@ -353,7 +376,7 @@ impl<'a, 'b, 'c, T: FieldElement, Q: QueryCallback<T>> VmProcessor<'a, 'b, 'c, T
         row_index: DegreeType,
         identities: &mut CompletableIdentities<'a, T>,
         unknown_strategy: UnknownStrategy,
-    ) -> Result<bool, Vec<EvalError<T>>> {
+    ) -> Result<bool, Vec<FailedIdentity<T>>> {
         let mut progress = false;
         let mut errors = vec![];
 
@@ -369,8 +392,10 @@ impl<'a, 'b, 'c, T: FieldElement, Q: QueryCallback<T>> VmProcessor<'a, 'b, 'c, T
             if is_machine_call && unknown_strategy == UnknownStrategy::Zero {
                 // The fact that we got to the point where we assume 0 for unknown cells, but this identity
                 // is still not complete, means that either the inputs or the machine is under-constrained.
-                errors.push(format!("{identity}:\n{}",
-                    indent("This machine call could not be completed. Either some inputs are missing or the machine is under-constrained.", "    ")).into());
+                errors.push(FailedIdentity::new(
+                    Some(identity),
+                    EvalError::Generic("This machine call could not be completed. Either some inputs are missing or the machine is under-constrained.".to_string()),
+                ));
                 continue;
             }
 
@@ -383,8 +408,8 @@ impl<'a, 'b, 'c, T: FieldElement, Q: QueryCallback<T>> VmProcessor<'a, 'b, 'c, T
                     *is_complete = res.is_complete;
                     progress |= res.progress;
                 }
-                Err(e) => {
-                    errors.push(e);
+                Err(error) => {
+                    errors.push(FailedIdentity::new(Some(identity), error));
                 }
             };
         }
@@ -396,87 +421,40 @@ impl<'a, 'b, 'c, T: FieldElement, Q: QueryCallback<T>> VmProcessor<'a, 'b, 'c, T
         }
     }
 
-    fn report_failure_and_panic_unsatisfiable(
+    /// Collects the information about a row that could not be completed.
+    fn row_failure(
         &self,
         row_index: DegreeType,
-        failures: Vec<EvalError<T>>,
-    ) -> ! {
-        log::error!(
-            "\nError: Row {} failed. Set RUST_LOG=debug for more information.\n",
-            row_index + self.row_offset
-        );
-        let row_index = row_index as usize;
-        log::debug!("Some identities where not satisfiable after the following values were uniquely determined (known nonzero first, then zero, unknown omitted):");
-        log::debug!(
-            "{}",
-            self.processor.row(row_index).render(
-                &format!("Current row ({row_index})"),
-                false,
-                &self.witnesses
-            )
-        );
+        kind: RowFailureKind,
+        failed_identities: Vec<FailedIdentity<T>>,
+    ) -> WitgenError<T> {
         log::debug!(
-            "{}",
-            self.processor.row(row_index + 1).render(
-                &format!("Next row ({})", row_index + 1),
-                false,
-                &self.witnesses
-            )
-        );
-        log::debug!("Set RUST_LOG=trace to understand why these values were chosen.");
-        log::error!(
-            "Errors:\n{}\n",
-            failures
-                .iter()
-                .map(|r| indent(&r.to_string(), "    "))
-                .join("\n")
-        );
-        panic!("Witness generation failed.");
-    }
-
-    fn report_failure_and_panic_underconstrained(
-        &self,
-        row_index: DegreeType,
-        failures: Vec<EvalError<T>>,
-    ) -> ! {
-        log::error!(
-            "\nError: Row {} failed. Set RUST_LOG=debug for more information.\n",
-            row_index + self.row_offset
+            "Set RUST_LOG=trace to understand why the values in the failing row were (not) chosen."
         );
+        let row = row_index + self.row_offset;
         let row_index = row_index as usize;
-
-        log::debug!("Some columns could not be determined, but setting them to zero does not satisfy the constraints. This typically means that the system is underconstrained!");
-        log::debug!(
-            "{}",
-            self.processor.row(row_index).render(
-                &format!("Current row ({row_index})"),
-                true,
-                &self.witnesses
-            )
-        );
-        log::debug!(
-            "{}",
-            self.processor.row(row_index + 1).render(
-                &format!("Next row ({})", row_index + 1),
-                true,
-                &self.witnesses
-            )
-        );
-        log::debug!("\nSet RUST_LOG=trace to understand why these values were (not) chosen.");
-        log::debug!(
-            "Assuming zero for unknown values, the following identities fail:\n{}\n",
-            failures
-                .iter()
-                .map(|r| indent(&r.to_string(), "    "))
-                .join("\n")
-        );
-        panic!("Witness generation failed.");
+        RowFailure {
+            machine: self.machine_name.clone(),
+            row,
+            kind,
+            failed_identities,
+            current_row: self.processor.row(row_index).cells(row, &self.witnesses),
+            next_row: self
+                .processor
+                .row(row_index + 1)
+                .cells(row + 1, &self.witnesses),
+        }
+        .into()
     }
 
     /// Verifies the proposed values for the next row.
     /// TODO this is bad for machines because we might introduce rows in the machine that are then
     /// not used.
-    fn try_proposed_row(&mut self, row_index: DegreeType, proposed_row: Row<'a, T>) -> bool {
+    fn try_proposed_row(
+        &mut self,
+        row_index: DegreeType,
+        proposed_row: Row<'a, T>,
+    ) -> Result<bool, WitgenError<T>> {
         let constraints_valid = self.identities_with_next_ref.iter().all(|i| {
             self.processor
                 .check_row_pair(row_index as usize, &proposed_row, i, true)
@@ -492,9 +470,9 @@ impl<'a, 'b, 'c, T: FieldElement, Q: QueryCallback<T>> VmProcessor<'a, 'b, 'c, T
             // If it doesn't, we re-run compute_next_row on the previous row in order to
             // correctly forward-propagate values via next references.
             self.ensure_has_next_row(row_index - 1);
-            self.compute_row(row_index - 1);
+            self.compute_row(row_index - 1)?;
         }
-        constraints_valid
+        Ok(constraints_valid)
     }
 
     fn maybe_log_performance(&mut self, row_index: DegreeType) {
@@ -0,0 +1,479 @@
+//! A witness fuzzer: Mutates single cells of a valid witness and checks whether
+//! the constraints still hold. Mutations that are not detected by any identity
+//! point to potentially under-constrained columns.
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::{self, Display};
+
+use itertools::Itertools;
+use num_bigint::BigUint;
+use num_traits::One;
+use powdr_ast::analyzed::{
+    AlgebraicExpression as Expression, Analyzed, Identity, IdentityKind, PolyID,
+};
+use powdr_ast::parsed::visitor::ExpressionVisitable;
+use powdr_number::{BigInt, DegreeType, FieldElement};
+use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
+
+use crate::constraint_checker::{check_constraints, ConstraintChecker};
+
+/// A mutation of a single witness cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Mutation {
+    /// Adds one to the value.
+    AddOne,
+    /// Subtracts one from the value.
+    SubOne,
+    /// Flips the given bit of the value. Skipped if the result is not a field element.
+    FlipBit(u32),
+}
+
+impl Mutation {
+    /// @returns the mutated value or None if the mutation does not apply to the value.
+    fn apply<T: FieldElement>(&self, value: T) -> Option<T> {
+        let mutated = match self {
+            Mutation::AddOne => value + T::one(),
+            Mutation::SubOne => value - T::one(),
+            Mutation::FlipBit(bit) => {
+                let flipped = value.to_arbitrary_integer() ^ (BigUint::one() << *bit);
+                if flipped >= T::modulus().to_arbitrary_integer() {
+                    return None;
+                }
+                T::from(flipped)
+            }
+        };
+        (mutated != value).then_some(mutated)
+    }
+}
+
+impl Display for Mutation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Mutation::AddOne => write!(f, "+1"),
+            Mutation::SubOne => write!(f, "-1"),
+            Mutation::FlipBit(bit) => write!(f, "flip bit {bit}"),
+        }
+    }
+}
+
+/// Settings of the witness fuzzer.
+#[derive(Debug, Clone)]
+pub struct FuzzConfig {
+    /// The mutations applied to every selected cell.
+    pub mutations: Vec<Mutation>,
+    /// The maximum number of rows mutated per column. The rows are spread evenly
+    /// over the whole column, always including the first and the last row.
+    pub max_rows_per_column: usize,
+    /// If set, only these witness columns are mutated.
+    pub columns: Option<Vec<String>>,
+}
+
+impl Default for FuzzConfig {
+    fn default() -> Self {
+        Self {
+            mutations: vec![
+                Mutation::AddOne,
+                Mutation::SubOne,
+                Mutation::FlipBit(0),
+                Mutation::FlipBit(7),
+                Mutation::FlipBit(15),
+                Mutation::FlipBit(31),
+            ],
+            max_rows_per_column: 16,
+            columns: None,
+        }
+    }
+}
+
+/// A mutated witness that still satisfies all constraints.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SurvivingMutation<T> {
+    pub column: String,
+    pub row: DegreeType,
+    pub mutation: Mutation,
+    pub original: T,
+    pub mutated: T,
+}
+
+impl<T: Display> Display for SurvivingMutation<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} in row {}: {} ({} -> {})",
+            self.column, self.row, self.mutation, self.original, self.mutated
+        )
+    }
+}
+
+/// The result of fuzzing a witness.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FuzzReport<T> {
+    /// The number of mutated witnesses that were checked.
+    pub mutations_checked: usize,
+    /// The mutations that were not detected by any identity, ordered by column and row.
+    pub surviving_mutations: Vec<SurvivingMutation<T>>,
+}
+
+impl<T> FuzzReport<T> {
+    /// @returns the columns with at least one surviving mutation, in the order of
+    /// `surviving_mutations`.
+    pub fn potentially_under_constrained_columns(&self) -> Vec<&str> {
+        self.surviving_mutations
+            .iter()
+            .map(|m| m.column.as_str())
+            .dedup()
+            .collect()
+    }
+}
+
+impl<T: Display> Display for FuzzReport<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let columns = self.potentially_under_constrained_columns();
+        write!(
+            f,
+            "Checked {} mutations, {} were not detected by any identity.",
+            self.mutations_checked,
+            self.surviving_mutations.len()
+        )?;
+        if columns.is_empty() {
+            return Ok(());
+        }
+        write!(f, "\nPotentially under-constrained columns:")?;
+        for column in columns {
+            let mutations = self
+                .surviving_mutations
+                .iter()
+                .filter(|m| m.column == column)
+                .collect::<Vec<_>>();
+            write!(f, "\n  {column} ({} surviving mutations)", mutations.len())?;
+            for mutation in mutations.iter().take(3) {
+                write!(f, "\n    {mutation}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Mutates single cells of the witness columns and checks which of the mutated
+/// witnesses still satisfy all identities.
+/// Fails if the unmutated witness does not satisfy the identities.
+pub fn fuzz_witness<T: FieldElement>(
+    pil: &Analyzed<T>,
+    fixed: &[(String, Vec<T>)],
+    witness: &[(String, Vec<T>)],
+    config: &FuzzConfig,
+) -> Result<FuzzReport<T>, String> {
+    check_constraints(pil, fixed, witness).map_err(|e| {
+        format!("The witness does not satisfy the constraints, so it cannot be fuzzed:\n{e}")
+    })?;
+    let checker = ConstraintChecker::new(pil, fixed, witness)?;
+    let identities = pil.identities_with_inlined_intermediate_polynomials();
+    let fuzzer = Fuzzer::new(checker, &identities);
+
+    let columns = pil
+        .committed_polys_in_source_order()
+        .into_iter()
+        .flat_map(|(symbol, _)| symbol.array_elements())
+        .filter(|(name, _)| {
+            config
+                .columns
+                .as_ref()
+                .map_or(true, |columns| columns.contains(name))
+        })
+        .collect::<Vec<_>>();
+    if let Some(selected) = &config.columns {
+        if let Some(missing) = selected
+            .iter()
+            .find(|name| !columns.iter().any(|(column, _)| column == *name))
+        {
+            return Err(format!("{missing} is not a witness column."));
+        }
+    }
+    let values = witness
+        .iter()
+        .map(|(name, values)| (name.as_str(), values))
+        .collect::<HashMap<_, _>>();
+    let candidates = columns
+        .iter()
+        .flat_map(|(name, poly_id)| {
+            let column = values[name.as_str()];
+            let rows = sample_rows(column.len() as DegreeType, config.max_rows_per_column);
+            rows.into_iter()
+                .flat_map(move |row| {
+                    let original = column[row as usize];
+                    config.mutations.iter().filter_map(move |mutation| {
+                        mutation.apply(original).map(|mutated| SurvivingMutation {
+                            column: name.clone(),
+                            row,
+                            mutation: *mutation,
+                            original,
+                            mutated,
+                        })
+                    })
+                })
+                .map(move |candidate| (*poly_id, candidate))
+        })
+        .collect::<Vec<_>>();
+
+    let surviving_mutations = candidates
+        .par_iter()
+        .filter(|(poly_id, candidate)| {
+            fuzzer.is_satisfied(*poly_id, candidate.row, candidate.mutated)
+        })
+        .map(|(_, candidate)| candidate.clone())
+        .collect();
+
+    Ok(FuzzReport {
+        mutations_checked: candidates.len(),
+        surviving_mutations,
+    })
+}
+
+/// @returns at most `max_rows` rows spread evenly between the first and the last row.
+fn sample_rows(degree: DegreeType, max_rows: usize) -> Vec<DegreeType> {
+    let max_rows = max_rows as DegreeType;
+    if degree <= max_rows {
+        return (0..degree).collect();
+    }
+    match max_rows {
+        0 => vec![],
+        1 => vec![0],
+        _ => (0..max_rows)
+            .map(|i| i * (degree - 1) / (max_rows - 1))
+            .dedup()
+            .collect(),
+    }
+}
+
+struct Fuzzer<'a, 'b, T> {
+    checker: ConstraintChecker<'a, T>,
+    identities: &'b [Identity<Expression<T>>],
+    /// The indices of the identities that reference a column.
+    identities_by_column: HashMap<PolyID, Vec<usize>>,
+    /// The tuples on the right hand side of each plookup identity, by identity index.
+    lookup_tables: HashMap<usize, BTreeSet<Vec<T>>>,
+    /// The columns referenced on the right hand side of each plookup identity.
+    lookup_table_columns: HashMap<usize, BTreeSet<PolyID>>,
+}
+
+impl<'a, 'b, T: FieldElement> Fuzzer<'a, 'b, T> {
+    fn new(checker: ConstraintChecker<'a, T>, identities: &'b [Identity<Expression<T>>]) -> Self {
+        let mut identities_by_column: HashMap<PolyID, Vec<usize>> = HashMap::new();
+        let mut lookup_tables = HashMap::new();
+        let mut lookup_table_columns = HashMap::new();
+        for (index, identity) in identities.iter().enumerate() {
+            for poly_id in referenced_columns(identity) {
+                identities_by_column.entry(poly_id).or_default().push(index);
+            }
+            if identity.kind == IdentityKind::Plookup {
+                let degree = checker
+                    .degree_of(
+                        identity
+                            .right
+                            .selector
+                            .iter()
+                            .chain(&identity.right.expressions),
+                    )
+                    .unwrap();
+                let table = (0..degree)
+                    .filter_map(|row| checker.selected_tuple(&identity.right, row).unwrap())
+                    .collect();
+                lookup_tables.insert(index, table);
+                lookup_table_columns.insert(index, referenced_columns(&identity.right));
+            }
+        }
+        Self {
+            checker,
+            identities,
+            identities_by_column,
+            lookup_tables,
+            lookup_table_columns,
+        }
+    }
+
+    /// @returns true if all identities are satisfied after setting the given cell to `value`.
+    fn is_satisfied(&self, poly_id: PolyID, row: DegreeType, value: T) -> bool {
+        let Some(identities) = self.identities_by_column.get(&poly_id) else {
+            return true;
+        };
+        let checker = self.checker.with_mutated_cell(poly_id, row, value);
+        // Only the mutated row and the row before it (through next references) can be affected
+        // for polynomial identities and the left hand side of plookups.
+        let degree = checker.column_degree(&poly_id);
+        let rows = [row, (row + degree - 1) % degree];
+        identities.iter().all(|index| {
+            let identity = &self.identities[*index];
+            match identity.kind {
+                IdentityKind::Polynomial => {
+                    let expression = identity.expression_for_poly_id();
+                    rows.iter().all(|row| {
+                        checker
+                            .evaluate(expression, *row)
+                            .is_ok_and(|value| value.is_zero())
+                    })
+                }
+                IdentityKind::Plookup if !self.lookup_table_columns[index].contains(&poly_id) => {
+                    let table = &self.lookup_tables[index];
+                    rows.iter()
+                        .all(|row| match checker.selected_tuple(&identity.left, *row) {
+                            Ok(Some(tuple)) => table.contains(&tuple),
+                            Ok(None) => true,
+                            Err(_) => false,
+                        })
+                }
+                _ => checker.failing_rows(identity).is_empty(),
+            }
+        })
+    }
+}
+
+/// @returns the IDs of all columns referenced by the given expressions.
+fn referenced_columns<E: ExpressionVisitable<Expression<T>>, T>(e: &E) -> BTreeSet<PolyID> {
+    let mut columns = BTreeSet::new();
+    e.pre_visit_expressions(&mut |e| {
+        if let Expression::Reference(poly) = e {
+            columns.insert(poly.poly_id);
+        }
+    });
+    columns
+}
+
+#[cfg(test)]
+mod test {
+    use powdr_number::GoldilocksField;
+    use powdr_pil_analyzer::analyze_string;
+    use test_log::test;
+
+    use crate::constant_evaluator;
+
+    use super::*;
+
+    fn fuzz(
+        src: &str,
+        witness: Vec<(&str, Vec<u64>)>,
+        config: &FuzzConfig,
+    ) -> Result<FuzzReport<GoldilocksField>, String> {
+        let pil = analyze_string::<GoldilocksField>(src);
+        let fixed = constant_evaluator::generate(&pil);
+        let witness = witness
+            .into_iter()
+            .map(|(name, values)| {
+                (
+                    name.to_string(),
+                    values.into_iter().map(GoldilocksField::from).collect(),
+                )
+            })
+            .collect::<Vec<_>>();
+        fuzz_witness(&pil, &fixed, &witness, config)
+    }
+
+    #[test]
+    fn reports_under_constrained_columns() {
+        let src = r"
+            namespace main(4);
+                col fixed X = [1, 2, 3, 4];
+                col witness a, b, c;
+                a = X + 1;
+                // b is boolean, but otherwise unconstrained.
+                b * (b - 1) = 0;
+                // c can be any value of X.
+                { c } in { X };
+        ";
+        let witness = vec![
+            ("main.a", vec![2, 3, 4, 5]),
+            ("main.b", vec![0, 0, 1, 1]),
+            ("main.c", vec![1, 1, 1, 4]),
+        ];
+        let config = FuzzConfig {
+            mutations: vec![Mutation::AddOne],
+            ..Default::default()
+        };
+        let report = fuzz(src, witness, &config).unwrap();
+        assert_eq!(report.mutations_checked, 12);
+        assert_eq!(
+            report.potentially_under_constrained_columns(),
+            vec!["main.b", "main.c"]
+        );
+        assert_eq!(
+            report
+                .surviving_mutations
+                .iter()
+                .map(|m| (m.column.as_str(), m.row))
+                .collect::<Vec<_>>(),
+            vec![
+                ("main.b", 0),
+                ("main.b", 1),
+                ("main.c", 0),
+                ("main.c", 1),
+                ("main.c", 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn next_references() {
+        let src = r"
+            namespace main(4);
+                col fixed FIRST = [1, 0, 0, 0];
+                col witness x;
+                FIRST * (x - 1) = 0;
+                (1 - FIRST') * (x' - 2 * x) = 0;
+        ";
+        let report = fuzz(src, vec![("main.x", vec![1, 2, 4, 8])], &Default::default()).unwrap();
+        assert!(report.surviving_mutations.is_empty());
+        assert!(report.mutations_checked > 0);
+    }
+
+    #[test]
+    fn selected_columns() {
+        let src = r"
+            namespace main(4);
+                col witness a, b;
+                a = 0;
+        ";
+        let config = FuzzConfig {
+            columns: Some(vec!["main.a".to_string()]),
+            ..Default::default()
+        };
+        let witness = vec![("main.a", vec![0; 4]), ("main.b", vec![0; 4])];
+        let report = fuzz(src, witness.clone(), &config).unwrap();
+        assert!(report.surviving_mutations.is_empty());
+
+        let config = FuzzConfig {
+            columns: Some(vec!["main.c".to_string()]),
+            ..Default::default()
+        };
+        assert_eq!(
+            fuzz(src, witness, &config).unwrap_err(),
+            "main.c is not a witness column."
+        );
+    }
+
+    #[test]
+    fn invalid_witness() {
+        let src = r"
+            namespace main(4);
+                col witness a;
+                a = 0;
+        ";
+        let err = fuzz(src, vec![("main.a", vec![0, 1, 0, 0])], &Default::default()).unwrap_err();
+        assert!(err.starts_with("The witness does not satisfy the constraints"));
+    }
+
+    #[test]
+    fn sampled_rows() {
+        assert_eq!(sample_rows(4, 16), vec![0, 1, 2, 3]);
+        assert_eq!(sample_rows(1024, 4), vec![0, 341, 682, 1023]);
+        assert_eq!(sample_rows(1024, 1), vec![0]);
+        assert!(sample_rows(1024, 0).is_empty());
+    }
+
+    #[test]
+    fn flip_bit() {
+        let value = GoldilocksField::from(5);
+        assert_eq!(Mutation::FlipBit(0).apply(value), Some(4.into()));
+        assert_eq!(Mutation::FlipBit(1).apply(value), Some(7.into()));
+        // 2**64 is not a field element.
+        assert_eq!(Mutation::FlipBit(64).apply(value), None);
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 use halo2_curves::bn256::Fr;
 use halo2_curves::ff::FromUniformBytes;
@@ -9,19 +9,107 @@ use polyexen::plaf::{
     ColumnFixed, ColumnPublic, ColumnWitness, Columns, CopyC, Info, Lookup, Plaf, Poly, Shuffle,
     Witness,
 };
+use powdr_ast::parsed::visitor::ExpressionVisitable;
 use powdr_ast::parsed::SelectedExpressions;
 
 use num_traits::{One, ToPrimitive};
+use powdr_ast::analyzed::connect::cell_positions;
 use powdr_ast::analyzed::{
-    AlgebraicBinaryOperator, AlgebraicExpression as Expression, Analyzed, IdentityKind,
+    AlgebraicBinaryOperator, AlgebraicExpression as Expression, AlgebraicUnaryOperator, Analyzed,
+    Identity, IdentityKind,
 };
-use powdr_number::{BigInt, FieldElement};
+use powdr_number::{BigInt, DegreeType, FieldElement};
 
 use super::circuit_data::CircuitData;
 
 /// Name of the __enable column
 const ENABLE_NAME: &str = "__enable";
 
+/// A witness column that is not part of the PIL, but added to the circuit by the backend.
+struct AuxiliaryColumn<T> {
+    name: String,
+    definition: AuxiliaryDefinition<T>,
+}
+
+enum AuxiliaryDefinition<T> {
+    /// An expression on the left hand side of a connect identity that is not a witness
+    /// column, e.g. an inlined intermediate column. Copy constraints can only be applied
+    /// to columns, so the column is constrained to be equal to the expression.
+    Expression(Expression<T>),
+    /// A column that is constant and equal to the value of the given public.
+    /// Expressions reference it instead of the public.
+    Public(String),
+}
+
+/// Returns the auxiliary columns needed to encode the given identities, in a deterministic order.
+fn auxiliary_columns<T: FieldElement>(
+    identities: &[Identity<Expression<T>>],
+) -> Vec<AuxiliaryColumn<T>> {
+    let connect_operands = identities
+        .iter()
+        .filter(|id| id.kind == IdentityKind::Connect)
+        .flat_map(|id| {
+            id.left
+                .expressions
+                .iter()
+                .enumerate()
+                .filter(|(_, expr)| connect_operand_column(expr).is_none())
+                .map(move |(index, expr)| AuxiliaryColumn {
+                    name: connect_column_name(id.id, index),
+                    definition: AuxiliaryDefinition::Expression(expr.clone()),
+                })
+        });
+
+    let mut public_names = vec![];
+    let mut seen = HashSet::new();
+    for id in identities {
+        id.pre_visit_expressions(&mut |e| {
+            if let Expression::PublicReference(name) = e {
+                if seen.insert(name.clone()) {
+                    public_names.push(name.clone());
+                }
+            }
+        });
+    }
+    let publics = public_names.into_iter().map(|name| AuxiliaryColumn {
+        name: public_column_name(&name),
+        definition: AuxiliaryDefinition::Public(name),
+    });
+
+    connect_operands.chain(publics).collect()
+}
+
+fn connect_column_name(identity_id: u64, index: usize) -> String {
+    format!("__connect_{identity_id}_{index}")
+}
+
+fn public_column_name(public_name: &str) -> String {
+    format!("__public_{public_name}")
+}
+
+/// Returns the name of the witness column if the expression can be copy-constrained directly.
+fn connect_operand_column<T>(expr: &Expression<T>) -> Option<&str> {
+    match expr {
+        Expression::Reference(poly) if poly.is_witness() && !poly.next => Some(&poly.name),
+        _ => None,
+    }
+}
+
+/// Returns the names of all witness columns of the circuit: The witness columns
+/// of the PIL followed by the auxiliary columns.
+fn witness_column_names<T: FieldElement>(
+    analyzed: &Analyzed<T>,
+    auxiliary_columns: &[AuxiliaryColumn<T>],
+) -> Vec<String> {
+    analyzed
+        .committed_polys_in_source_order()
+        .into_iter()
+        .flat_map(|(p, _)| p.array_elements())
+        .map(|(name, _)| name)
+        .chain(auxiliary_columns.iter().map(|column| column.name.clone()))
+        .collect()
+}
+
 /// Converts a column of size $2^k$ and type T to a column of size $2^{k+1}$ and type Option<BigUint>:
 /// - The first $2^k$ elements are Some(value.to_arbitrary_integer())
 /// - Value $2^k + 1$ is the first value again
@@ -51,7 +139,7 @@ fn convert_column<T: FieldElement>(column: &[T]) -> Vec<Option<BigUint>> {
 pub(crate) fn analyzed_to_plaf<T: FieldElement>(
     analyzed: &Analyzed<T>,
     fixed: &[(String, Vec<T>)],
-) -> Plaf {
+) -> Result<Plaf, String> {
     // The structure of the table is as following
     //
     // | constant columns | __enable     |  witness columns | \
@@ -66,6 +154,10 @@ pub(crate) fn analyzed_to_plaf<T: FieldElement>(
     // |  None            |    None      |   None           |  |
     // |  None            |    None      |   None           |  | <-- Halo2 will put blinding factors in the last few rows
     // |  None            |    None      |   None           | /      of the witness columns.
+    //
+    // The witness columns consist of the witness columns of the PIL, followed by
+    // auxiliary columns for connect operands that are not witness columns and for
+    // publics referenced in identities.
 
     // generate fixed and witness (witness).
 
@@ -79,6 +171,7 @@ pub(crate) fn analyzed_to_plaf<T: FieldElement>(
         .collect::<Vec<_>>();
 
     let original_size: usize = analyzed.degree() as usize;
+    let fixed_values = fixed;
     let fixed = fixed
         .iter()
         .map(|(_, column)| convert_column(column))
@@ -91,17 +184,23 @@ pub(crate) fn analyzed_to_plaf<T: FieldElement>(
         ))
         .collect::<Vec<_>>();
 
-    let cd = CircuitData::from(analyzed, &fixed_names);
+    let identities = analyzed.identities_with_inlined_intermediate_polynomials();
+    let auxiliary_columns = auxiliary_columns(&identities);
+    let auxiliary_names = auxiliary_columns
+        .iter()
+        .map(|column| column.name.clone())
+        .collect::<Vec<_>>();
+
+    let cd = CircuitData::from(analyzed, &fixed_names, &auxiliary_names);
 
     let mut lookups = vec![];
     let mut shuffles = vec![];
     let mut polys = vec![];
+    let mut copys = vec![];
 
-    let wit_columns: Vec<_> = analyzed
-        .committed_polys_in_source_order()
+    let wit_columns: Vec<_> = witness_column_names(analyzed, &auxiliary_columns)
         .into_iter()
-        .flat_map(|(p, _)| p.array_elements())
-        .map(|(name, _)| ColumnWitness::new(name.to_string(), 0))
+        .map(|name| ColumnWitness::new(name, 0))
         .collect();
 
     // build Plaf columns -------------------------------------------------
@@ -123,23 +222,23 @@ pub(crate) fn analyzed_to_plaf<T: FieldElement>(
     // build Plaf polys. -------------------------------------------------------------------------
 
     let q_enable = query(cd.col(ENABLE_NAME), 0);
-    let apply_selectors_to_set = |set: &SelectedExpressions<Expression<T>>| {
-        let selector = set
-            .selector
-            .clone()
-            .map_or(Expr::Const(BigUint::one()), |expr| {
-                expression_2_expr(&cd, &expr)
-            });
+    let apply_selectors_to_set =
+        |set: &SelectedExpressions<Expression<T>>| -> Result<Vec<_>, String> {
+            let selector = set
+                .selector
+                .as_ref()
+                .map_or(Ok(Expr::Const(BigUint::one())), |expr| {
+                    expression_2_expr(&cd, expr)
+                })?;
+
+            let selector = Expr::Mul(vec![selector, q_enable.clone()]);
+
+            set.expressions
+                .iter()
+                .map(|expr| Ok(selector.clone() * expression_2_expr(&cd, expr)?))
+                .collect()
+        };
 
-        let selector = Expr::Mul(vec![selector, q_enable.clone()]);
-
-        set.expressions
-            .iter()
-            .map(|expr| selector.clone() * expression_2_expr(&cd, expr))
-            .collect()
-    };
-
-    let identities = analyzed.identities_with_inlined_intermediate_polynomials();
     for id in &identities {
         match id.kind {
             IdentityKind::Polynomial => {
@@ -150,7 +249,7 @@ pub(crate) fn analyzed_to_plaf<T: FieldElement>(
                 assert_eq!(id.left.expressions.len(), 0);
 
                 let exp = id.expression_for_poly_id();
-                let exp = expression_2_expr(&cd, exp);
+                let exp = expression_2_expr(&cd, exp)?;
 
                 // depending whether this polynomial contains a rotation,
                 // enable for all rows or all except the last one.
@@ -162,8 +261,8 @@ pub(crate) fn analyzed_to_plaf<T: FieldElement>(
                 });
             }
             IdentityKind::Plookup => {
-                let left = apply_selectors_to_set(&id.left);
-                let right = apply_selectors_to_set(&id.right);
+                let left = apply_selectors_to_set(&id.left)?;
+                let right = apply_selectors_to_set(&id.right)?;
 
                 lookups.push(Lookup {
                     name: "".to_string(),
@@ -171,18 +270,45 @@ pub(crate) fn analyzed_to_plaf<T: FieldElement>(
                 });
             }
             IdentityKind::Permutation => {
-                let left = apply_selectors_to_set(&id.left);
-                let right = apply_selectors_to_set(&id.right);
+                let left = apply_selectors_to_set(&id.left)?;
+                let right = apply_selectors_to_set(&id.right)?;
 
                 shuffles.push(Shuffle {
                     name: "".to_string(),
                     exps: (left, right),
                 });
             }
-            _ => unimplemented!(),
+            IdentityKind::Connect => {
+                copys.extend(connect_copy_constraints(
+                    id,
+                    &cd,
+                    fixed_values,
+                    analyzed.degree(),
+                )?);
+            }
         }
     }
 
+    // Constrain the auxiliary columns to their definition.
+    for column in &auxiliary_columns {
+        let col = cd.col(&column.name);
+        let exp = match &column.definition {
+            AuxiliaryDefinition::Expression(expr) => Expr::Sum(vec![
+                query(col, 0),
+                Expr::Neg(Box::new(expression_2_expr(&cd, expr)?)),
+            ]),
+            // The column is constant. Together with the copy constraint from row 0 to
+            // row N, this constrains all N + 1 rows to the same value.
+            AuxiliaryDefinition::Public(_) => {
+                Expr::Sum(vec![query(col, 1), Expr::Neg(Box::new(query(col, 0)))])
+            }
+        };
+        polys.push(Poly {
+            name: column.name.clone(),
+            exp: Expr::Mul(vec![exp, q_enable.clone()]),
+        });
+    }
+
     if lookups.is_empty() {
         // TODO something inside halo2 breaks (only in debug mode) if lookups is empty,
         // so just add an empty lookup.
@@ -192,22 +318,104 @@ pub(crate) fn analyzed_to_plaf<T: FieldElement>(
         });
     }
 
+    copys.extend(copy_constraints(analyzed, &cd, &auxiliary_columns)?);
+
     // build Plaf fixed. -------------------------------------------------------------------------
 
-    Plaf {
+    Ok(Plaf {
         info,
         columns,
         polys,
         metadata: Default::default(),
         lookups,
         shuffles,
-        copys: copy_constraints(analyzed, &cd),
+        copys,
         fixed,
+    })
+}
+
+/// Lowers a connect identity to copy constraints between the cells of its left hand side.
+/// The fixed columns on the right hand side define the permutation of the cells,
+/// see `powdr_ast::analyzed::connect`.
+fn connect_copy_constraints<T: FieldElement>(
+    identity: &Identity<Expression<T>>,
+    cd: &CircuitData,
+    fixed: &[(String, Vec<T>)],
+    degree: DegreeType,
+) -> Result<Vec<CopyC>, String> {
+    if identity.left.selector.is_some() || identity.right.selector.is_some() {
+        return Err(format!(
+            "Selectors are not supported for connect identities: {identity}"
+        ));
+    }
+
+    let operand_columns = identity
+        .left
+        .expressions
+        .iter()
+        .enumerate()
+        .map(|(index, expr)| match connect_operand_column(expr) {
+            Some(name) => cd.col(name),
+            None => cd.col(&connect_column_name(identity.id, index)),
+        })
+        .collect::<Vec<_>>();
+
+    let fixed = fixed
+        .iter()
+        .map(|(name, values)| (name.as_str(), values))
+        .collect::<HashMap<_, _>>();
+    let permutation = identity
+        .right
+        .expressions
+        .iter()
+        .map(|expr| match expr {
+            Expression::Reference(poly) if poly.is_fixed() && !poly.next => {
+                Ok(fixed[poly.name.as_str()])
+            }
+            _ => Err(format!(
+                "The right hand side of a connect identity must consist of fixed columns, \
+                 but found {expr} in {identity}"
+            )),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let positions = cell_positions::<T>(operand_columns.len(), degree)?;
+
+    // Group the copied cells by pairs of columns.
+    let mut offsets: BTreeMap<(usize, usize), Vec<(usize, usize)>> = BTreeMap::new();
+    for (expression, values) in permutation.iter().enumerate() {
+        for (row, value) in values.iter().enumerate() {
+            let (next_expression, next_row) = positions.get(value).ok_or_else(|| {
+                format!(
+                    "Value {value} in row {row} does not identify a cell of the connect identity {identity}"
+                )
+            })?;
+            let next_row = *next_row as usize;
+            if (expression, row) != (*next_expression, next_row) {
+                offsets
+                    .entry((expression, *next_expression))
+                    .or_default()
+                    .push((row, next_row));
+            }
+        }
     }
+
+    Ok(offsets
+        .into_iter()
+        .map(|((from, to), offsets)| CopyC {
+            columns: (operand_columns[from], operand_columns[to]),
+            offsets,
+        })
+        .collect())
 }
 
-fn copy_constraints<T: FieldElement>(pil: &Analyzed<T>, cd: &CircuitData) -> Vec<CopyC> {
+fn copy_constraints<T: FieldElement>(
+    pil: &Analyzed<T>,
+    cd: &CircuitData,
+    auxiliary_columns: &[AuxiliaryColumn<T>],
+) -> Result<Vec<CopyC>, String> {
     let mut copies = vec![];
+    let public_col = cd.public_column;
 
     // Enforce publics by copy-constraining to cells in the instance column.
     // For example, if we have the following public declarations:
@@ -221,28 +429,45 @@ fn copy_constraints<T: FieldElement>(pil: &Analyzed<T>, cd: &CircuitData) -> Vec
     // |  1  |  1  |  6  |  *5*   |
     // |  2  | *2* |  7  |        |
     // |  3  |  4  |  8  |        |
-    for public_declaration in pil.public_declarations.values() {
+    let mut public_offsets = HashMap::new();
+    for (public_offset, (name, public_declaration)) in pil
+        .public_declarations_in_source_order()
+        .into_iter()
+        .enumerate()
+    {
         let witness_name = public_declaration.referenced_poly_name();
-        let witness_col = cd.col(&witness_name);
+        let Some(witness_col) = cd.columns.get(&witness_name) else {
+            return Err(format!(
+                "Public {name} references {witness_name}, which is not a witness column."
+            ));
+        };
         let witness_offset = public_declaration.index as usize;
-        let public_col = cd.public_column;
-        let public_offset = copies.len();
 
         // Add copy constraint
         copies.push(CopyC {
-            columns: (public_col, witness_col),
+            columns: (public_col, *witness_col),
             // We could also create one copy constraint per column pair wih several offsets.
             // I don't think there is a difference though...
             offsets: vec![(public_offset, witness_offset)],
         });
+        public_offsets.insert(name.as_str(), public_offset);
+    }
+
+    // Auxiliary columns for publics are copy-constrained to the public in their first row.
+    for column in auxiliary_columns {
+        if let AuxiliaryDefinition::Public(name) = &column.definition {
+            let public_offset = public_offsets
+                .get(name.as_str())
+                .ok_or_else(|| format!("Public {name} is not declared."))?;
+            copies.push(CopyC {
+                columns: (public_col, cd.col(&column.name)),
+                offsets: vec![(*public_offset, 0)],
+            });
+        }
     }
 
     // Also, copy row 0 to row N.
-    for (poly_name, _) in pil
-        .committed_polys_in_source_order()
-        .into_iter()
-        .flat_map(|(p, _)| p.array_elements())
-    {
+    for poly_name in witness_column_names(pil, auxiliary_columns) {
         let witness_col = cd.col(&poly_name);
         copies.push(CopyC {
             columns: (witness_col, witness_col),
@@ -250,25 +475,18 @@ fn copy_constraints<T: FieldElement>(pil: &Analyzed<T>, cd: &CircuitData) -> Vec
         });
     }
 
-    copies
+    Ok(copies)
 }
 
-/// Converts an analyzed PIL and fixed to a PlafH2Circuit.
+/// Converts a Plaf circuit to a PlafH2Circuit.
 /// A PlafH2Circuit contains the witness because Halo2 is like that.
 /// Because of that we just build a witness with the correct length
 /// but with 0s.
-pub(crate) fn analyzed_to_circuit_with_zeroed_witness<T: FieldElement>(
-    plaf: Plaf,
-    analyzed: &Analyzed<T>,
-) -> PlafH2Circuit {
+pub(crate) fn analyzed_to_circuit_with_zeroed_witness(plaf: Plaf) -> PlafH2Circuit {
     let num_rows = plaf.fixed.len();
 
-    let wit_columns: Vec<_> = analyzed
-        .committed_polys_in_source_order()
-        .into_iter()
-        .flat_map(|(p, _)| p.array_elements())
-        .map(|(name, _)| ColumnWitness::new(name.to_string(), 0))
-        .collect();
+    // The witness columns, including the auxiliary columns, are the same as in the Plaf.
+    let wit_columns = plaf.columns.witness.clone();
 
     // build zeroed witness. -------------------------------------------------------------------------
 
@@ -292,30 +510,34 @@ pub fn powdr_ff_to_fr<T: FieldElement>(x: T) -> Fr {
     Fr::from_uniform_bytes(&buffer)
 }
 
-fn public_values<T: FieldElement>(pil: &Analyzed<T>, witness: &[(String, Vec<T>)]) -> Vec<Fr> {
-    let witness_map: HashMap<String, Vec<T>> = witness.iter().cloned().collect();
-    let eval_witness = |name: &String, row: usize| -> T { witness_map.get(name).unwrap()[row] };
-
-    let mut publics = vec![];
-    for public_declaration in pil.public_declarations.values() {
-        let witness_name = public_declaration.referenced_poly_name();
-        let witness_offset = public_declaration.index as usize;
-
-        // Evaluate the given cell and add it to the publics.
-        let value = eval_witness(&witness_name, witness_offset);
-        let value = powdr_ff_to_fr(value);
-
-        // Add to publics.
-        publics.push(value);
-    }
+/// Returns the values of all publics, by name, in source order.
+fn public_values<T: FieldElement>(
+    pil: &Analyzed<T>,
+    witness: &[(String, Vec<T>)],
+) -> Vec<(String, T)> {
+    let witness_map: HashMap<&str, &Vec<T>> = witness
+        .iter()
+        .map(|(name, values)| (name.as_str(), values))
+        .collect();
+    let eval_witness = |name: &str, row: usize| -> T { witness_map.get(name).unwrap()[row] };
 
-    publics
+    pil.public_declarations_in_source_order()
+        .into_iter()
+        .map(|(name, public_declaration)| {
+            let witness_name = public_declaration.referenced_poly_name();
+            let witness_offset = public_declaration.index as usize;
+
+            // Evaluate the given cell.
+            (name.clone(), eval_witness(&witness_name, witness_offset))
+        })
+        .collect()
 }
 
 /// Converts an analyzed PIL, fixed and witness columns to a PlafH2Circuit and publics for the halo2 backend.
 pub(crate) fn analyzed_to_circuit_with_witness<T: FieldElement>(
     analyzed: &Analyzed<T>,
     plaf: Plaf,
+    fixed: &[(String, Vec<T>)],
     witness: &[(String, Vec<T>)],
 ) -> (PlafH2Circuit, Vec<Vec<Fr>>) {
     let num_rows = plaf.fixed.len();
@@ -325,17 +547,46 @@ pub(crate) fn analyzed_to_circuit_with_witness<T: FieldElement>(
         "More publics than rows!"
     );
 
+    let publics = public_values(analyzed, witness);
+
+    // compute auxiliary columns. ----------------------------------------------------------------
+
+    let identities = analyzed.identities_with_inlined_intermediate_polynomials();
+    let columns: HashMap<&str, &Vec<T>> = fixed
+        .iter()
+        .chain(witness)
+        .map(|(name, values)| (name.as_str(), values))
+        .collect();
+    let public_map: HashMap<&str, T> = publics
+        .iter()
+        .map(|(name, value)| (name.as_str(), *value))
+        .collect();
+    let degree = analyzed.degree() as usize;
+    let auxiliary_witness = auxiliary_columns(&identities)
+        .into_iter()
+        .map(|column| {
+            let values = match &column.definition {
+                AuxiliaryDefinition::Expression(expr) => (0..degree)
+                    .map(|row| evaluate(expr, row, &columns, &public_map))
+                    .collect(),
+                AuxiliaryDefinition::Public(name) => vec![public_map[name.as_str()]; degree],
+            };
+            (column.name, values)
+        })
+        .collect::<Vec<_>>();
+
     // build witness. -------------------------------------------------------------------------
 
+    let witness = witness.iter().chain(&auxiliary_witness);
+
     let converted_witness: Vec<Vec<_>> = witness
-        .iter()
+        .clone()
         .map(|(_, column)| convert_column(column))
         .collect();
 
     let wit = Witness {
-        num_rows: witness.len(),
+        num_rows: converted_witness.len(),
         columns: witness
-            .iter()
             .map(|(name, _)| ColumnWitness::new(name.clone(), 0))
             .collect(),
         witness: converted_witness,
@@ -343,14 +594,49 @@ pub(crate) fn analyzed_to_circuit_with_witness<T: FieldElement>(
 
     // return circuit description + witness. -------------
 
-    (
-        PlafH2Circuit { plaf, wit },
-        vec![public_values(analyzed, witness)],
-    )
+    let publics = publics
+        .into_iter()
+        .map(|(_, value)| powdr_ff_to_fr(value))
+        .collect();
+
+    (PlafH2Circuit { plaf, wit }, vec![publics])
 }
 
-fn expression_2_expr<T: FieldElement>(cd: &CircuitData, expr: &Expression<T>) -> Expr<PlonkVar> {
+/// Evaluates an expression in the given row. Next references wrap around.
+fn evaluate<T: FieldElement>(
+    expr: &Expression<T>,
+    row: usize,
+    columns: &HashMap<&str, &Vec<T>>,
+    publics: &HashMap<&str, T>,
+) -> T {
     match expr {
+        Expression::Number(n) => *n,
+        Expression::Reference(polyref) => {
+            let values = columns[polyref.name.as_str()];
+            values[(row + polyref.next as usize) % values.len()]
+        }
+        Expression::PublicReference(name) => publics[name.as_str()],
+        Expression::BinaryOperation(lhe, op, rhe) => {
+            let lhe = evaluate(lhe, row, columns, publics);
+            let rhe = evaluate(rhe, row, columns, publics);
+            match op {
+                AlgebraicBinaryOperator::Add => lhe + rhe,
+                AlgebraicBinaryOperator::Sub => lhe - rhe,
+                AlgebraicBinaryOperator::Mul => lhe * rhe,
+                AlgebraicBinaryOperator::Pow => lhe.pow(rhe.to_integer()),
+            }
+        }
+        Expression::UnaryOperation(AlgebraicUnaryOperator::Minus, e) => {
+            -evaluate(e, row, columns, publics)
+        }
+    }
+}
+
+fn expression_2_expr<T: FieldElement>(
+    cd: &CircuitData,
+    expr: &Expression<T>,
+) -> Result<Expr<PlonkVar>, String> {
+    Ok(match expr {
         Expression::Number(n) => Expr::Const(n.to_arbitrary_integer()),
         Expression::Reference(polyref) => {
             let plonkvar = PlonkVar::Query(ColumnQuery {
@@ -360,27 +646,38 @@ fn expression_2_expr<T: FieldElement>(cd: &CircuitData, expr: &Expression<T>) ->
 
             Expr::Var(plonkvar)
         }
+        Expression::PublicReference(name) => Expr::Var(PlonkVar::Query(ColumnQuery {
+            column: cd.col(&public_column_name(name)),
+            rotation: 0,
+        })),
         Expression::BinaryOperation(lhe, op, rhe_powdr) => {
-            let lhe = expression_2_expr(cd, lhe);
-            let rhe = expression_2_expr(cd, rhe_powdr);
+            let lhe = expression_2_expr(cd, lhe)?;
             match op {
-                AlgebraicBinaryOperator::Add => Expr::Sum(vec![lhe, rhe]),
-                AlgebraicBinaryOperator::Sub => Expr::Sum(vec![lhe, Expr::Neg(Box::new(rhe))]),
-                AlgebraicBinaryOperator::Mul => Expr::Mul(vec![lhe, rhe]),
+                AlgebraicBinaryOperator::Add => {
+                    Expr::Sum(vec![lhe, expression_2_expr(cd, rhe_powdr)?])
+                }
+                AlgebraicBinaryOperator::Sub => Expr::Sum(vec![
+                    lhe,
+                    Expr::Neg(Box::new(expression_2_expr(cd, rhe_powdr)?)),
+                ]),
+                AlgebraicBinaryOperator::Mul => {
+                    Expr::Mul(vec![lhe, expression_2_expr(cd, rhe_powdr)?])
+                }
                 AlgebraicBinaryOperator::Pow => {
                     let Expression::Number(e) = rhe_powdr.as_ref() else {
-                        panic!("Expected number in exponent.")
+                        return Err(format!(
+                            "Exponent has to be a number, but found {rhe_powdr} in {expr}."
+                        ));
                     };
-                    Expr::Pow(
-                        Box::new(lhe),
-                        e.to_arbitrary_integer()
-                            .to_u32()
-                            .unwrap_or_else(|| panic!("Exponent has to fit 32 bits.")),
-                    )
+                    let e = e.to_arbitrary_integer().to_u32().ok_or_else(|| {
+                        format!("Exponent has to fit 32 bits, but found {e} in {expr}.")
+                    })?;
+                    Expr::Pow(Box::new(lhe), e)
                 }
             }
         }
-
-        _ => unimplemented!("{:?}", expr),
-    }
+        Expression::UnaryOperation(AlgebraicUnaryOperator::Minus, e) => {
+            Expr::Neg(Box::new(expression_2_expr(cd, e)?))
+        }
+    })
 }
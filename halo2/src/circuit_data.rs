@@ -14,7 +14,13 @@ pub(crate) struct CircuitData {
 }
 
 impl CircuitData {
-    pub fn from<T: FieldElement>(pil: &Analyzed<T>, fixed_names: &[String]) -> Self {
+    /// Creates the column mapping for the given fixed columns, the witness columns of the PIL
+    /// and the auxiliary witness columns added by the backend (in that order).
+    pub fn from<T: FieldElement>(
+        pil: &Analyzed<T>,
+        fixed_names: &[String],
+        auxiliary_names: &[String],
+    ) -> Self {
         let const_cols = fixed_names.iter().enumerate().map(|(index, name)| {
             (
                 name.to_string(),
@@ -29,10 +35,12 @@ impl CircuitData {
             .committed_polys_in_source_order()
             .into_iter()
             .flat_map(|(p, _)| p.array_elements())
+            .map(|(name, _)| name)
+            .chain(auxiliary_names.iter().cloned())
             .enumerate()
-            .map(|(index, (name, _))| {
+            .map(|(index, name)| {
                 (
-                    name.to_string(),
+                    name,
                     Column {
                         kind: ColumnKind::Witness,
                         index,
@@ -10,13 +10,14 @@ pub fn mock_prove<T: FieldElement>(
     pil: &Analyzed<T>,
     constants: &[(String, Vec<T>)],
     witness: &[(String, Vec<T>)],
-) {
+) -> Result<(), String> {
     if polyexen::expr::get_field_p::<Fr>() != T::modulus().to_arbitrary_integer() {
         panic!("powdr modulus doesn't match halo2 modulus. Make sure you are using Bn254");
     }
 
-    let plaf_circuit = analyzed_to_plaf(pil, constants);
-    let (circuit, publics) = analyzed_to_circuit_with_witness(pil, plaf_circuit, witness);
+    let plaf_circuit = analyzed_to_plaf(pil, constants)?;
+    let (circuit, publics) =
+        analyzed_to_circuit_with_witness(pil, plaf_circuit, constants, witness);
 
     // double the row count in order to make space for the cells introduced by the backend
     // TODO: use a precise count of the extra rows needed to avoid using so many rows
@@ -29,6 +30,8 @@ pub fn mock_prove<T: FieldElement>(
 
     let mock_prover = MockProver::<Fr>::run(expanded_row_count_log, &circuit, publics).unwrap();
     mock_prover.assert_satisfied();
+
+    Ok(())
 }
 
 #[cfg(test)]
@@ -50,7 +53,8 @@ mod test {
             &result.pil,
             &result.fixed_cols,
             result.witness.as_ref().unwrap(),
-        );
+        )
+        .unwrap();
     }
 
     #[test]
@@ -65,7 +69,8 @@ mod test {
             &result.pil,
             &result.fixed_cols,
             result.witness.as_ref().unwrap(),
-        );
+        )
+        .unwrap();
     }
 
     #[test]
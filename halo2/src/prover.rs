@@ -48,6 +48,7 @@ pub use halo2_proofs::SerdeFormat;
 /// "unsafe" code, and unsafe code is harder to explain and maintain.
 pub struct Halo2Prover<'a, F: FieldElement> {
     pil: &'a Analyzed<F>,
+    fixed: &'a [(String, Vec<F>)],
     plaf: Plaf,
     params: ParamsKZG<Bn256>,
     vkey: Option<VerifyingKey<G1Affine>>,
@@ -64,24 +65,26 @@ pub fn generate_setup(size: DegreeType) -> ParamsKZG<Bn256> {
 impl<'a, F: FieldElement> Halo2Prover<'a, F> {
     pub fn new(
         pil: &'a Analyzed<F>,
-        fixed: &[(String, Vec<F>)],
+        fixed: &'a [(String, Vec<F>)],
         setup: Option<&mut dyn io::Read>,
-    ) -> Result<Self, io::Error> {
+    ) -> Result<Self, String> {
         Self::assert_field_is_bn254();
 
         let params = setup
             .map(|mut setup| ParamsKZG::<Bn256>::read(&mut setup))
-            .transpose()?
+            .transpose()
+            .map_err(|e| format!("Could not read setup: {e}"))?
             .map(|mut params| {
                 params.downsize(degree_bits(pil.degree()));
                 params
             })
             .unwrap_or_else(|| generate_setup(pil.degree()));
 
-        let plaf = analyzed_to_plaf(pil, fixed);
+        let plaf = analyzed_to_plaf(pil, fixed)?;
 
         Ok(Self {
             pil,
+            fixed,
             plaf,
             params,
             vkey: None,
@@ -96,7 +99,7 @@ impl<'a, F: FieldElement> Halo2Prover<'a, F> {
         log::info!("Starting proof generation...");
 
         let (circuit, publics) =
-            analyzed_to_circuit_with_witness(self.pil, self.plaf.clone(), witness);
+            analyzed_to_circuit_with_witness(self.pil, self.plaf.clone(), self.fixed, witness);
 
         log::debug!("{}", PlafDisplayBaseTOML(&circuit.plaf));
 
@@ -146,7 +149,7 @@ impl<'a, F: FieldElement> Halo2Prover<'a, F> {
 
         log::info!("Generating circuit for app snark...");
         let (circuit_app, publics) =
-            analyzed_to_circuit_with_witness(self.pil, self.plaf.clone(), witness);
+            analyzed_to_circuit_with_witness(self.pil, self.plaf.clone(), self.fixed, witness);
 
         assert_eq!(publics.len(), 1);
         if !publics[0].is_empty() {
@@ -224,7 +227,7 @@ impl<'a, F: FieldElement> Halo2Prover<'a, F> {
     }
 
     pub fn verification_key(&self) -> Result<VerifyingKey<G1Affine>, String> {
-        let circuit = analyzed_to_circuit_with_zeroed_witness(self.plaf.clone(), self.pil);
+        let circuit = analyzed_to_circuit_with_zeroed_witness(self.plaf.clone());
         keygen_vk(&self.params, &circuit).map_err(|e| e.to_string())
     }
 
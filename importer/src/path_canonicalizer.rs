@@ -11,7 +11,8 @@ use powdr_ast::{
     parsed::{
         asm::{
             ASMModule, ASMProgram, AbsoluteSymbolPath, Import, Machine, MachineStatement, Module,
-            ModuleRef, ModuleStatement, SymbolDefinition, SymbolValue, SymbolValueRef,
+            ModuleRef, ModuleStatement, RegisterFlag, SymbolDefinition, SymbolValue,
+            SymbolValueRef,
         },
         folder::Folder,
         visitor::ExpressionVisitable,
@@ -358,6 +359,19 @@ fn check_machine<T: Clone>(
             return Err(format!("Duplicate name `{name}` in machine `{location}`"));
         }
     }
+    // Machines with a program counter can also refer to some of the columns that are
+    // generated for them when compiling to PIL.
+    if let Some(pc) = m.statements.iter().find_map(|s| match s {
+        MachineStatement::RegisterDeclaration(_, name, Some(RegisterFlag::IsPC)) => Some(name),
+        _ => None,
+    }) {
+        local_variables.insert("first_step".to_string());
+        local_variables.insert(format!("{pc}_update"));
+        local_variables.extend(m.statements.iter().filter_map(|s| match s {
+            MachineStatement::InstructionDeclaration(_, name, _) => Some(format!("instr_{name}")),
+            _ => None,
+        }));
+    }
     for statement in &m.statements {
         match statement {
             MachineStatement::Submachine(_, path, _) => {
@@ -554,6 +568,11 @@ mod tests {
         )
     }
 
+    #[test]
+    fn generated_vm_columns() {
+        expect("generated_vm_columns", Ok(()))
+    }
+
     #[test]
     fn submachine_found() {
         expect("submachine_found", Ok(()))
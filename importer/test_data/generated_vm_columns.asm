@@ -0,0 +1,7 @@
+machine Main {
+    reg pc[@pc];
+    reg X[<=];
+    instr jump X { pc' = X }
+    col witness last_jump;
+    last_jump = (1 - first_step) * instr_jump * pc_update;
+}
@@ -14,13 +14,37 @@ use powdr_ast::{
 use powdr_number::FieldElement;
 
 use itertools::Itertools;
+use strum::{Display, EnumString, EnumVariantNames};
 
 const DEFAULT_DEGREE: u64 = 1024;
 const MAIN_OPERATION_NAME: &str = "main";
 
+/// How the machines of a PIL graph are turned into AIRs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, EnumString, EnumVariantNames, Display)]
+pub enum LinkerMode {
+    /// A single AIR: every submachine has the degree of the main machine.
+    #[default]
+    #[strum(serialize = "monolithic")]
+    Monolithic,
+    /// One AIR per machine instance, each with its own degree. Links between
+    /// machines become lookups between the tables of the machines.
+    #[strum(serialize = "per-machine")]
+    PerMachine,
+}
+
 /// a monolithic linker which outputs a single AIR
 /// It sets the degree of submachines to the degree of the main machine, and errors out if a submachine has an explicit degree which doesn't match the main one
 pub fn link<T: FieldElement>(graph: PILGraph<T>) -> Result<PILFile<T>, Vec<String>> {
+    link_with_mode(graph, LinkerMode::Monolithic)
+}
+
+/// Links the machines of the graph into a PIL file with one namespace per machine instance.
+/// In [LinkerMode::PerMachine], each namespace has the degree of its machine, defaulting to
+/// the degree of the main machine.
+pub fn link_with_mode<T: FieldElement>(
+    graph: PILGraph<T>,
+    mode: LinkerMode,
+) -> Result<PILFile<T>, Vec<String>> {
     let main_machine = graph.main;
     let main_degree = graph
         .objects
@@ -70,20 +94,23 @@ pub fn link<T: FieldElement>(graph: PILGraph<T>) -> Result<PILFile<T>, Vec<Strin
     pil.extend(graph.objects.into_iter().flat_map(|(location, object)| {
         let mut pil = vec![];
 
-        if let Some(degree) = object.degree {
-            if degree != main_degree {
+        let degree = match (mode, object.degree) {
+            (LinkerMode::PerMachine, Some(degree)) => degree,
+            (LinkerMode::Monolithic, Some(degree)) if degree != main_degree => {
                 errors.push(format!(
                     "Machine {location} should have degree {main_degree}, found {}",
                     degree
-                ))
+                ));
+                main_degree
             }
-        }
+            _ => main_degree,
+        };
 
         // create a namespace for this object
         pil.push(PilStatement::Namespace(
             SourceRef::unknown(),
             SymbolPath::from_identifier(location.to_string()),
-            Expression::Number(T::from(main_degree)),
+            Expression::Number(T::from(degree)),
         ));
         pil.extend(object.pil);
         for link in object.links {
@@ -190,7 +217,7 @@ mod test {
 
     use pretty_assertions::assert_eq;
 
-    use crate::{link, DEFAULT_DEGREE};
+    use crate::{link, link_with_mode, LinkerMode, DEFAULT_DEGREE};
 
     fn parse_analyse_and_compile<T: FieldElement>(input: &str) -> PILGraph<T> {
         let parsed = parse_asm(None, input).unwrap();
@@ -247,6 +274,48 @@ mod test {
         );
     }
 
+    #[test]
+    fn degree_per_machine() {
+        let graph: PILGraph<Bn254Field> = PILGraph {
+            main: powdr_ast::object::Machine {
+                location: Location::main(),
+                operation_id: Some("operation_id".into()),
+                latch: Some("latch".into()),
+            },
+            entry_points: vec![],
+            definitions: Default::default(),
+            objects: [
+                (Location::main(), Object::default().with_degree(Some(1024))),
+                (
+                    Location::main().join("foo"),
+                    Object::default().with_degree(Some(8)),
+                ),
+                (Location::main().join("bar"), Object::default()),
+            ]
+            .into_iter()
+            .collect(),
+        };
+        let degrees = link_with_mode(graph, LinkerMode::PerMachine)
+            .unwrap()
+            .0
+            .into_iter()
+            .filter_map(|s| match s {
+                powdr_ast::parsed::PilStatement::Namespace(_, name, e) => {
+                    Some((name.to_string(), e))
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            degrees,
+            vec![
+                ("main".to_string(), Expression::Number(1024.into())),
+                ("main_bar".to_string(), Expression::Number(1024.into())),
+                ("main_foo".to_string(), Expression::Number(8.into())),
+            ]
+        );
+    }
+
     fn extract_main(code: &str) -> &str {
         let start = code.find("namespace main").unwrap();
         &code[start..]
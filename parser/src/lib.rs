@@ -255,6 +255,7 @@ mod test {
                 MachineStatement::Degree(s, _)
                 | MachineStatement::Submachine(s, _, _)
                 | MachineStatement::RegisterDeclaration(s, _, _)
+                | MachineStatement::PcOverride(s, _, _)
                 | MachineStatement::OperationDeclaration(s, _, _, _)
                 | MachineStatement::LinkDeclaration(s, _) => {
                     *s = SourceRef::unknown();
@@ -1,9 +1,10 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 
 use std::fs;
 use std::iter::once;
 use std::path::{Path, PathBuf};
 
+use itertools::Itertools;
 use powdr_ast::parsed::asm::{AbsoluteSymbolPath, SymbolPath};
 use powdr_ast::parsed::{PILFile, PilStatement};
 use powdr_number::{DegreeType, FieldElement};
@@ -46,7 +47,10 @@ pub fn analyze_string<T: FieldElement>(contents: &str) -> Analyzed<T> {
 struct PILAnalyzer<T> {
     known_symbols: HashSet<String>,
     current_namespace: AbsoluteSymbolPath,
+    /// The degree of the current namespace.
     polynomial_degree: Option<DegreeType>,
+    /// The degrees of all namespaces.
+    namespace_degrees: BTreeSet<DegreeType>,
     definitions: HashMap<String, (Symbol, Option<FunctionValueDefinition<T>>)>,
     public_declarations: HashMap<String, PublicDeclaration>,
     identities: Vec<Identity<Expression<T>>>,
@@ -127,8 +131,10 @@ impl<T: FieldElement> PILAnalyzer<T> {
     }
 
     pub fn condense(self) -> Analyzed<T> {
+        // The degree is only common if all namespaces have the same degree.
+        let degree = self.namespace_degrees.iter().exactly_one().ok().copied();
         condenser::condense(
-            self.polynomial_degree,
+            degree,
             self.definitions,
             self.public_declarations,
             &self.identities,
@@ -203,14 +209,8 @@ impl<T: FieldElement> PILAnalyzer<T> {
                 .unwrap(),
         )
         .unwrap();
-        if let Some(degree) = self.polynomial_degree {
-            assert_eq!(
-                degree, namespace_degree,
-                "all namespaces must have the same degree"
-            );
-        } else {
-            self.polynomial_degree = Some(namespace_degree);
-        }
+        self.polynomial_degree = Some(namespace_degree);
+        self.namespace_degrees.insert(namespace_degree);
         self.current_namespace = AbsoluteSymbolPath::default().join(name);
     }
 
@@ -328,6 +328,29 @@ namespace T(65536);
         assert_eq!(formatted, expected);
     }
 
+    #[test]
+    fn different_degrees() {
+        let input = r#"namespace Main(1024);
+    col witness x;
+namespace Binary(8);
+    col witness y;
+    { Main.x } in { Binary.y };
+"#;
+        let analyzed = analyze_string::<GoldilocksField>(input);
+        assert_eq!(analyzed.degree, None);
+        assert_eq!(
+            analyzed.degrees().into_iter().collect::<Vec<_>>(),
+            [8, 1024]
+        );
+        let expected = r#"namespace Main(1024);
+    col witness x;
+namespace Binary(8);
+    col witness y;
+    { Main.x } in { Binary.y };
+"#;
+        assert_eq!(analyzed.to_string(), expected);
+    }
+
     #[test]
     fn intermediate_nested() {
         let input = r#"namespace N(65536);
@@ -382,6 +405,15 @@ namespace N(16);
         assert_eq!(formatted, input);
     }
 
+    #[test]
+    fn reparse_array_query() {
+        let input = r#"namespace N(16);
+    col witness y[3](j, i) query ("hint", ((j * 10) + i));
+"#;
+        let formatted = analyze_string::<GoldilocksField>(input).to_string();
+        assert_eq!(formatted, input);
+    }
+
     #[test]
     #[should_panic = "Operator - not supported on types"]
     fn no_direct_array_references() {
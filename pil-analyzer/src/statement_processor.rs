@@ -374,6 +374,9 @@ where
             absolute_name: name.clone(),
             kind: symbol_kind,
             length,
+            degree: self
+                .degree
+                .filter(|_| matches!(symbol_kind, SymbolKind::Poly(_))),
         };
 
         let value = value.map(|v| match v {
@@ -386,7 +389,14 @@ where
             }
             FunctionDefinition::Query(expr) => {
                 assert_eq!(symbol_kind, SymbolKind::Poly(PolynomialType::Committed));
-                assert!(ty.is_none() || ty == Some(Type::col()));
+                // Queries can be declared for single witness columns and for arrays of them.
+                assert!(match &ty {
+                    None => true,
+                    Some(Type::Array(ArrayType { base, length: _ })) => {
+                        base.as_ref() == &Type::col()
+                    }
+                    Some(t) => *t == Type::col(),
+                });
                 FunctionValueDefinition::Query(self.process_expression(expr))
             }
             FunctionDefinition::Array(value) => {
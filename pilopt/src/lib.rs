@@ -1,6 +1,8 @@
 //! PIL-based optimizer
 #![deny(clippy::print_stdout)]
 
+pub mod unconstrained_columns;
+
 use std::collections::{BTreeMap, HashSet};
 
 use powdr_ast::analyzed::{
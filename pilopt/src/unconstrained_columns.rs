@@ -0,0 +1,311 @@
+//! Static analysis that reports witness columns which are likely under-constrained.
+
+use std::collections::BTreeMap;
+use std::fmt::{self, Display};
+
+use powdr_ast::analyzed::{
+    AlgebraicBinaryOperator, AlgebraicExpression, AlgebraicReference, AlgebraicUnaryOperator,
+    Analyzed, Identity, IdentityKind, PolyID,
+};
+use powdr_ast::parsed::visitor::ExpressionVisitable;
+use powdr_ast::SourceRef;
+use powdr_number::FieldElement;
+
+/// A witness column that is likely under-constrained.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnconstrainedColumn {
+    pub name: String,
+    /// The location of the column declaration.
+    pub source: SourceRef,
+    pub reason: UnconstrainedReason,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UnconstrainedReason {
+    /// The column is not referenced by any identity.
+    NotReferenced,
+    /// The column is only referenced by a single polynomial identity, in which it
+    /// appears linearly with a coefficient that is not a constant. In rows where
+    /// the coefficient is zero, the column can take any value.
+    SingleLinearOccurrence {
+        identity: String,
+        identity_source: SourceRef,
+        coefficient: String,
+    },
+}
+
+impl Display for UnconstrainedColumn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(location) = location(&self.source) {
+            write!(f, "{location}: ")?;
+        }
+        write!(f, "{} ", self.name)?;
+        match &self.reason {
+            UnconstrainedReason::NotReferenced => write!(f, "is not referenced by any identity."),
+            UnconstrainedReason::SingleLinearOccurrence {
+                identity,
+                identity_source,
+                coefficient,
+            } => {
+                write!(f, "only appears linearly in {identity}")?;
+                if let Some(location) = location(identity_source) {
+                    write!(f, " ({location})")?;
+                }
+                write!(f, " and is unconstrained where {coefficient} is zero.")
+            }
+        }
+    }
+}
+
+/// Returns the location in the form `file:line:col`, or None if it is unknown.
+fn location(source: &SourceRef) -> Option<String> {
+    (*source != SourceRef::unknown()).then(|| {
+        let file = source.file.as_deref().unwrap_or("<unknown>");
+        format!("{file}:{}:{}", source.line, source.col)
+    })
+}
+
+/// Returns the witness columns that are not referenced by any identity or that
+/// only appear linearly in a single polynomial identity with a non-constant
+/// coefficient, in source order.
+/// Intermediate columns are inlined before the analysis.
+pub fn unconstrained_columns<T: FieldElement>(pil: &Analyzed<T>) -> Vec<UnconstrainedColumn> {
+    let identities = pil.identities_with_inlined_intermediate_polynomials();
+    let mut occurrences: BTreeMap<PolyID, Vec<&Identity<AlgebraicExpression<T>>>> = BTreeMap::new();
+    for identity in &identities {
+        let mut referenced = vec![];
+        identity.pre_visit_expressions(&mut |e| {
+            if let AlgebraicExpression::Reference(poly) = e {
+                if poly.is_witness() && !referenced.contains(&poly.poly_id) {
+                    referenced.push(poly.poly_id);
+                }
+            }
+        });
+        for poly_id in referenced {
+            occurrences.entry(poly_id).or_default().push(identity);
+        }
+    }
+
+    pil.committed_polys_in_source_order()
+        .into_iter()
+        .flat_map(|(symbol, _)| {
+            symbol
+                .array_elements()
+                .map(move |(name, poly_id)| (symbol, name, poly_id))
+        })
+        .filter_map(|(symbol, name, poly_id)| {
+            let reason = match occurrences.get(&poly_id).map(|ids| &ids[..]) {
+                None => UnconstrainedReason::NotReferenced,
+                Some([identity]) if identity.kind == IdentityKind::Polynomial => {
+                    let coefficient =
+                        variable_coefficient(identity.expression_for_poly_id(), poly_id)?;
+                    UnconstrainedReason::SingleLinearOccurrence {
+                        identity: identity.to_string(),
+                        identity_source: identity.source.clone(),
+                        coefficient: coefficient.to_string(),
+                    }
+                }
+                Some(_) => return None,
+            };
+            Some(UnconstrainedColumn {
+                name,
+                source: symbol.source.clone(),
+                reason,
+            })
+        })
+        .collect()
+}
+
+/// If the column appears linearly in the expression, either only in the current
+/// or only in the next row, returns its coefficient unless it is a constant.
+fn variable_coefficient<T: FieldElement>(
+    expr: &AlgebraicExpression<T>,
+    poly_id: PolyID,
+) -> Option<AlgebraicExpression<T>> {
+    let mut next_values = vec![];
+    expr.pre_visit_expressions(&mut |e| {
+        if let AlgebraicExpression::Reference(poly) = e {
+            if poly.poly_id == poly_id && !next_values.contains(&poly.next) {
+                next_values.push(poly.next);
+            }
+        }
+    });
+    let [next] = next_values[..] else {
+        return None;
+    };
+    let mut coefficient =
+        linear_coefficient(expr, &|poly| poly.poly_id == poly_id && poly.next == next).ok()??;
+    // The sign does not matter for whether the coefficient is zero.
+    while let AlgebraicExpression::UnaryOperation(AlgebraicUnaryOperator::Minus, inner) =
+        coefficient
+    {
+        coefficient = *inner;
+    }
+    match constant_value(&coefficient) {
+        Some(_) => None,
+        None => Some(coefficient),
+    }
+}
+
+/// The expression is not linear in the variable.
+struct NonLinear;
+
+/// Returns the coefficient of the variable in the expression, or None if the expression
+/// does not depend on the variable.
+fn linear_coefficient<T: FieldElement>(
+    expr: &AlgebraicExpression<T>,
+    is_variable: &impl Fn(&AlgebraicReference) -> bool,
+) -> Result<Option<AlgebraicExpression<T>>, NonLinear> {
+    Ok(match expr {
+        AlgebraicExpression::Reference(poly) if is_variable(poly) => {
+            Some(AlgebraicExpression::Number(1.into()))
+        }
+        AlgebraicExpression::Reference(_)
+        | AlgebraicExpression::PublicReference(_)
+        | AlgebraicExpression::Number(_) => None,
+        AlgebraicExpression::BinaryOperation(left, op, right) => {
+            let left_coefficient = linear_coefficient(left, is_variable)?;
+            let right_coefficient = linear_coefficient(right, is_variable)?;
+            match (op, left_coefficient, right_coefficient) {
+                (_, None, None) => None,
+                (AlgebraicBinaryOperator::Add, l, r) => match (l, r) {
+                    (Some(l), Some(r)) => Some(l + r),
+                    (c, None) | (None, c) => c,
+                },
+                (AlgebraicBinaryOperator::Sub, l, r) => match (l, r) {
+                    (Some(l), Some(r)) => Some(l - r),
+                    (Some(l), None) => Some(l),
+                    (None, Some(r)) => Some(negate(r)),
+                    (None, None) => unreachable!(),
+                },
+                (AlgebraicBinaryOperator::Mul, Some(l), None) => Some(multiply(l, right)),
+                (AlgebraicBinaryOperator::Mul, None, Some(r)) => Some(multiply(r, left)),
+                (AlgebraicBinaryOperator::Pow, Some(l), None) => {
+                    match constant_value(right).map(|e| e.to_degree()) {
+                        Some(1) => Some(l),
+                        _ => return Err(NonLinear),
+                    }
+                }
+                _ => return Err(NonLinear),
+            }
+        }
+        AlgebraicExpression::UnaryOperation(AlgebraicUnaryOperator::Minus, e) => {
+            linear_coefficient(e, is_variable)?.map(negate)
+        }
+    })
+}
+
+/// Multiplies a coefficient by a factor, omitting a coefficient of one.
+fn multiply<T: FieldElement>(
+    coefficient: AlgebraicExpression<T>,
+    factor: &AlgebraicExpression<T>,
+) -> AlgebraicExpression<T> {
+    match coefficient {
+        AlgebraicExpression::Number(n) if n == 1.into() => factor.clone(),
+        coefficient => coefficient * factor.clone(),
+    }
+}
+
+fn negate<T>(expr: AlgebraicExpression<T>) -> AlgebraicExpression<T> {
+    AlgebraicExpression::UnaryOperation(AlgebraicUnaryOperator::Minus, Box::new(expr))
+}
+
+/// Evaluates an expression that does not reference any columns.
+fn constant_value<T: FieldElement>(expr: &AlgebraicExpression<T>) -> Option<T> {
+    match expr {
+        AlgebraicExpression::Number(n) => Some(*n),
+        AlgebraicExpression::Reference(_) | AlgebraicExpression::PublicReference(_) => None,
+        AlgebraicExpression::BinaryOperation(left, op, right) => {
+            let left = constant_value(left)?;
+            let right = constant_value(right)?;
+            Some(match op {
+                AlgebraicBinaryOperator::Add => left + right,
+                AlgebraicBinaryOperator::Sub => left - right,
+                AlgebraicBinaryOperator::Mul => left * right,
+                AlgebraicBinaryOperator::Pow => left.pow(right.to_integer()),
+            })
+        }
+        AlgebraicExpression::UnaryOperation(AlgebraicUnaryOperator::Minus, e) => {
+            Some(-constant_value(e)?)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use powdr_number::GoldilocksField;
+    use powdr_pil_analyzer::analyze_string;
+
+    use super::*;
+
+    fn unconstrained(input: &str) -> Vec<(String, UnconstrainedReason)> {
+        unconstrained_columns(&analyze_string::<GoldilocksField>(input))
+            .into_iter()
+            .map(|c| (c.name, c.reason))
+            .collect()
+    }
+
+    #[test]
+    fn not_referenced() {
+        let input = r#"namespace N(4);
+    col witness x;
+    col witness y;
+    col witness z[2];
+    x = 1;
+    z[1] = 2;
+"#;
+        assert_eq!(
+            unconstrained(input),
+            vec![
+                ("N.y".to_string(), UnconstrainedReason::NotReferenced),
+                ("N.z[0]".to_string(), UnconstrainedReason::NotReferenced),
+            ]
+        );
+    }
+
+    #[test]
+    fn single_linear_occurrence() {
+        let input = r#"namespace N(4);
+    col fixed SEL = [0, 1]*;
+    col witness x;
+    col witness y;
+    col witness z;
+    SEL * (x - 1) = 0;
+    (1 - SEL) * y' = 3 * y;
+    z * (z - 1) = 0;
+"#;
+        let result = unconstrained(input);
+        assert_eq!(result.len(), 1);
+        let (name, UnconstrainedReason::SingleLinearOccurrence { coefficient, .. }) = &result[0]
+        else {
+            panic!()
+        };
+        assert_eq!(name, "N.x");
+        assert_eq!(coefficient, "N.SEL");
+    }
+
+    #[test]
+    fn constant_coefficient() {
+        let input = r#"namespace N(4);
+    col fixed A = [0, 1]*;
+    col witness x;
+    col witness y;
+    2 * x = A + 7;
+    y + y = A;
+"#;
+        assert_eq!(unconstrained(input), vec![]);
+    }
+
+    #[test]
+    fn intermediate_columns() {
+        let input = r#"namespace N(4);
+    col fixed SEL = [0, 1]*;
+    col witness x;
+    col inter = SEL * x;
+    inter = SEL;
+"#;
+        let result = unconstrained(input);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].0, "N.x");
+    }
+}
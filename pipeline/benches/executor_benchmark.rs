@@ -22,7 +22,8 @@ fn run_witgen<T: FieldElement>(
     let query_callback = inputs_to_query_callback(vec![]);
     powdr_executor::witgen::WitnessGenerator::new(analyzed, constants, &query_callback)
         .with_external_witness_values(external_witness_values)
-        .generate();
+        .generate()
+        .unwrap();
 }
 
 fn executor_benchmark(c: &mut Criterion) {
@@ -15,6 +15,7 @@ pub use pipeline::Stage;
 use itertools::Itertools;
 pub use powdr_backend::{BackendType, Proof};
 use powdr_executor::witgen::QueryCallback;
+pub use powdr_linker::LinkerMode;
 
 use powdr_number::FieldElement;
 
@@ -60,8 +61,9 @@ pub fn serde_data_to_query_callback<T: FieldElement, S: serde::Serialize + Send
                     .parse::<u32>()
                     .map_err(|e| format!("Error parsing callback data channel: {e})"))?;
 
+                // Let chained callbacks answer queries for other channels.
                 if channel != cb_channel {
-                    return Ok(None);
+                    return Err(format!("Unknown data channel: {cb_channel}"));
                 }
 
                 let index = index
@@ -5,7 +5,6 @@ use std::{
     io::{self, BufReader, BufWriter},
     marker::Send,
     path::{Path, PathBuf},
-    rc::Rc,
     sync::Arc,
     time::Instant,
 };
@@ -21,9 +20,14 @@ use powdr_ast::{
 use powdr_backend::{BackendType, Proof};
 use powdr_executor::{
     constant_evaluator,
-    witgen::{chain_callbacks, QueryCallback},
+    constraint_checker::check_constraints,
+    witgen::{chain_callbacks, QueryCallback, WitgenError},
+    witness_fuzzer::{self, FuzzConfig, FuzzReport},
+};
+use powdr_linker::LinkerMode;
+use powdr_number::{
+    write_polys_csv_file, write_polys_file, CsvRenderMode, DegreeType, FieldElement,
 };
-use powdr_number::{write_polys_csv_file, write_polys_file, CsvRenderMode, FieldElement};
 use powdr_schemas::SerializedAnalyzed;
 
 use crate::{
@@ -33,21 +37,21 @@ use crate::{
 
 #[derive(Clone)]
 pub struct GeneratedWitness<T: FieldElement> {
-    pub pil: Rc<Analyzed<T>>,
-    pub fixed_cols: Rc<Vec<(String, Vec<T>)>>,
+    pub pil: Arc<Analyzed<T>>,
+    pub fixed_cols: Arc<Vec<(String, Vec<T>)>>,
     pub witness: Option<Vec<(String, Vec<T>)>>,
 }
 
 #[derive(Clone)]
 pub struct PilWithEvaluatedFixedCols<T: FieldElement> {
-    pub pil: Rc<Analyzed<T>>,
-    pub fixed_cols: Rc<Vec<(String, Vec<T>)>>,
+    pub pil: Arc<Analyzed<T>>,
+    pub fixed_cols: Arc<Vec<(String, Vec<T>)>>,
 }
 
 #[derive(Clone)]
 pub struct ProofResult<T: FieldElement> {
     /// Fixed columns, potentially incomplete (if success is false)
-    pub fixed_cols: Rc<Vec<(String, Vec<T>)>>,
+    pub fixed_cols: Arc<Vec<(String, Vec<T>)>>,
     /// Witness columns, potentially None (if success is false)
     pub witness: Option<Vec<(String, Vec<T>)>>,
     /// Proof, potentially None (if success is false)
@@ -195,6 +199,11 @@ struct Arguments<T: FieldElement> {
     vkey_file: Option<PathBuf>,
     /// The optional existing proof file to use for aggregation.
     existing_proof_file: Option<PathBuf>,
+    /// How the machines are linked into PIL.
+    linker_mode: LinkerMode,
+    /// If set, witness generation first runs with this degree and then the
+    /// smallest power of two that fits the execution is chosen as degree.
+    max_degree: Option<DegreeType>,
 }
 
 #[derive(Clone)]
@@ -215,6 +224,8 @@ pub struct Pipeline<T: FieldElement> {
     log_level: Level,
     /// Optional arguments for various stages of the pipeline.
     arguments: Arguments<T>,
+    /// The error of the last failed witness generation, if any.
+    witgen_error: Option<WitgenError<T>>,
 }
 
 impl<T> Default for Pipeline<T>
@@ -230,6 +241,7 @@ where
             name: None,
             force_overwrite: false,
             arguments: Arguments::default(),
+            witgen_error: None,
         }
     }
 }
@@ -362,6 +374,19 @@ impl<T: FieldElement> Pipeline<T> {
         self
     }
 
+    pub fn with_linker_mode(mut self, linker_mode: LinkerMode) -> Self {
+        self.arguments.linker_mode = linker_mode;
+        self
+    }
+
+    /// Enables dynamic degree selection: the degree declared in the PIL file is
+    /// replaced by `max_degree` for a first witness generation run, and then by the
+    /// smallest power of two that fits the actual execution.
+    pub fn with_max_degree(mut self, max_degree: Option<DegreeType>) -> Self {
+        self.arguments.max_degree = max_degree;
+        self
+    }
+
     pub fn with_name(mut self, name: String) -> Self {
         self.name = Some(name);
         self
@@ -453,8 +478,8 @@ impl<T: FieldElement> Pipeline<T> {
         Pipeline {
             artifact: Some(Artifact::PilWithEvaluatedFixedCols(
                 PilWithEvaluatedFixedCols {
-                    pil: Rc::new(pil),
-                    fixed_cols: Rc::new(fixed),
+                    pil: Arc::new(pil),
+                    fixed_cols: Arc::new(fixed),
                 },
             )),
             ..self
@@ -479,8 +504,8 @@ impl<T: FieldElement> Pipeline<T> {
 
         Pipeline {
             artifact: Some(Artifact::GeneratedWitness(GeneratedWitness {
-                pil: Rc::new(pil),
-                fixed_cols: Rc::new(fixed),
+                pil: Arc::new(pil),
+                fixed_cols: Arc::new(fixed),
                 witness: Some(witness),
             })),
             ..self
@@ -574,7 +599,7 @@ impl<T: FieldElement> Pipeline<T> {
             }
             Artifact::LinkedMachineGraph(graph) => {
                 self.log("Run linker");
-                let linked = powdr_linker::link(graph)?;
+                let linked = powdr_linker::link_with_mode(graph, self.arguments.linker_mode)?;
                 self.diff_monitor.push(&linked);
                 log::trace!("{linked}");
                 self.maybe_write_pil(&linked, "")?;
@@ -601,11 +626,23 @@ impl<T: FieldElement> Pipeline<T> {
             Artifact::AnalyzedPil(analyzed_pil) => {
                 self.log("Optimizing pil...");
                 let optimized = powdr_pilopt::optimize(analyzed_pil);
-                self.maybe_write_pil(&optimized, "_opt")?;
-                self.maybe_write_pil_object(&optimized, "_opt")?;
+                // With dynamic degree selection, the PIL file is written once the degree is known.
+                if self.arguments.max_degree.is_none() {
+                    self.maybe_write_pil(&optimized, "_opt")?;
+                    self.maybe_write_pil_object(&optimized, "_opt")?;
+                }
                 Artifact::OptimzedPil(optimized)
             }
-            Artifact::OptimzedPil(pil) => {
+            Artifact::OptimzedPil(mut pil) => {
+                if let Some(max_degree) = self.arguments.max_degree {
+                    if pil.degree.is_none() {
+                        return Err(vec![
+                            "Dynamic degree selection requires all namespaces to have the same degree."
+                                .to_string(),
+                        ]);
+                    }
+                    pil.set_degree(max_degree).map_err(|e| vec![e])?;
+                }
                 self.log("Evaluating fixed columns...");
                 let start = Instant::now();
                 let fixed_cols = constant_evaluator::generate(&pil);
@@ -613,37 +650,44 @@ impl<T: FieldElement> Pipeline<T> {
                     .into_iter()
                     .map(|(k, v)| (k.to_string(), v))
                     .collect::<Vec<_>>();
-                self.maybe_write_constants(&fixed_cols)?;
+                if self.arguments.max_degree.is_none() {
+                    self.maybe_write_constants(&fixed_cols)?;
+                }
                 self.log(&format!("Took {}", start.elapsed().as_secs_f32()));
                 Artifact::PilWithEvaluatedFixedCols(PilWithEvaluatedFixedCols {
-                    pil: Rc::new(pil),
-                    fixed_cols: Rc::new(fixed_cols),
+                    pil: Arc::new(pil),
+                    fixed_cols: Arc::new(fixed_cols),
                 })
             }
-            Artifact::PilWithEvaluatedFixedCols(PilWithEvaluatedFixedCols { pil, fixed_cols }) => {
-                let witness = (pil.constant_count() == fixed_cols.len()).then(|| {
+            Artifact::PilWithEvaluatedFixedCols(PilWithEvaluatedFixedCols {
+                mut pil,
+                mut fixed_cols,
+            }) => {
+                let witness = if pil.constant_count() == fixed_cols.len() {
                     self.log("Deducing witness columns...");
                     let start = Instant::now();
-                    let external_witness_values =
-                        std::mem::take(&mut self.arguments.external_witness_values);
-                    let query_callback =
-                        self.arguments.query_callback.take().unwrap_or_else(|| {
-                            Arc::new(powdr_executor::witgen::unused_query_callback())
-                        });
-                    let witness = powdr_executor::witgen::WitnessGenerator::new(
-                        &pil,
-                        &fixed_cols,
-                        query_callback.borrow(),
-                    )
-                    .with_external_witness_values(external_witness_values)
-                    .generate();
-
+                    let mut witness = self
+                        .generate_witness(&pil, &fixed_cols)
+                        .map_err(|e| {
+                            let errors = vec![e.to_string()];
+                            self.witgen_error = Some(e);
+                            errors
+                        })?;
                     self.log(&format!("Took {}", start.elapsed().as_secs_f32()));
-                    witness
-                        .into_iter()
-                        .map(|(name, c)| (name.to_string(), c))
-                        .collect::<Vec<_>>()
-                });
+
+                    if self.arguments.max_degree.is_some() {
+                        (pil, fixed_cols, witness) =
+                            self.select_degree(pil, fixed_cols, witness)?;
+                        self.maybe_write_pil(pil.as_ref(), "_opt")?;
+                        self.maybe_write_pil_object(&pil, "_opt")?;
+                        self.maybe_write_constants(&fixed_cols)?;
+                    }
+                    self.arguments.external_witness_values.clear();
+                    self.arguments.query_callback = None;
+                    Some(witness)
+                } else {
+                    None
+                };
 
                 self.maybe_write_witness(&fixed_cols, &witness)?;
                 Artifact::GeneratedWitness(GeneratedWitness {
@@ -686,7 +730,7 @@ impl<T: FieldElement> Pipeline<T> {
                         setup.as_io_read(),
                         vkey.as_io_read(),
                     )
-                    .unwrap();
+                    .map_err(|e| vec![e.to_string()])?;
 
                 // Reads the existing proof file, if set.
                 let existing_proof = self
@@ -722,6 +766,64 @@ impl<T: FieldElement> Pipeline<T> {
         Ok(())
     }
 
+    /// Given the witness generated with the maximum degree, tries the powers of two
+    /// from the smallest one that fits all non-padding rows of the witness up to the
+    /// maximum degree, and returns the PIL, fixed columns and witness for the first
+    /// degree where witness generation succeeds and all constraints are satisfied.
+    #[allow(clippy::type_complexity)]
+    fn select_degree(
+        &self,
+        pil: Arc<Analyzed<T>>,
+        fixed_cols: Arc<Vec<(String, Vec<T>)>>,
+        witness: Vec<(String, Vec<T>)>,
+    ) -> Result<
+        (
+            Arc<Analyzed<T>>,
+            Arc<Vec<(String, Vec<T>)>>,
+            Vec<(String, Vec<T>)>,
+        ),
+        Vec<String>,
+    > {
+        let max_degree = pil.degree();
+        // Add the last row, which wraps around to the first one.
+        let mut degree = (used_rows(&witness) + 1)
+            .next_power_of_two()
+            .min(max_degree);
+        while degree < max_degree {
+            self.log(&format!("Trying degree {degree}..."));
+            let mut candidate_pil = pil.as_ref().clone();
+            if let Err(e) = candidate_pil.set_degree(degree) {
+                log::debug!("{e}");
+                degree *= 2;
+                continue;
+            }
+            let candidate_fixed_cols = constant_evaluator::generate(&candidate_pil)
+                .into_iter()
+                .map(|(name, values)| (name.to_string(), values))
+                .collect::<Vec<_>>();
+            match self.generate_witness(&candidate_pil, &candidate_fixed_cols) {
+                Ok(candidate_witness)
+                    if check_constraints(
+                        &candidate_pil,
+                        &candidate_fixed_cols,
+                        &candidate_witness,
+                    )
+                    .is_ok() =>
+                {
+                    self.log(&format!("Selected degree {degree}"));
+                    return Ok((
+                        Arc::new(candidate_pil),
+                        Arc::new(candidate_fixed_cols),
+                        candidate_witness,
+                    ));
+                }
+                _ => degree *= 2,
+            }
+        }
+        self.log(&format!("Selected maximum degree {max_degree}"));
+        Ok((pil, fixed_cols, witness))
+    }
+
     /// Returns the path to the output file if the output directory is set.
     /// Fails if the file already exists and `force_overwrite` is false.
     fn path_if_should_write<F: FnOnce(&str) -> String>(
@@ -748,6 +850,28 @@ impl<T: FieldElement> Pipeline<T> {
             .transpose()
     }
 
+    /// Generates the witness for the given PIL and fixed columns, using the query
+    /// callback and external witness values of the pipeline.
+    fn generate_witness(
+        &self,
+        pil: &Analyzed<T>,
+        fixed_cols: &[(String, Vec<T>)],
+    ) -> Result<Vec<(String, Vec<T>)>, WitgenError<T>> {
+        let query_callback = self
+            .arguments
+            .query_callback
+            .clone()
+            .unwrap_or_else(|| Arc::new(powdr_executor::witgen::unused_query_callback()));
+        let witness =
+            powdr_executor::witgen::WitnessGenerator::new(pil, fixed_cols, query_callback.borrow())
+                .with_external_witness_values(self.arguments.external_witness_values.clone())
+                .generate()?;
+        Ok(witness
+            .into_iter()
+            .map(|(name, c)| (name.to_string(), c))
+            .collect())
+    }
+
     fn maybe_write_pil<C: Display>(&self, content: &C, suffix: &str) -> Result<(), Vec<String>> {
         if let Some(path) = self.path_if_should_write(|name| format!("{name}{suffix}.pil"))? {
             fs::write(&path, format!("{content}"))
@@ -922,6 +1046,35 @@ impl<T: FieldElement> Pipeline<T> {
         Ok(generated_witness)
     }
 
+    /// Generates the witness and returns the degree of the PIL it was generated for.
+    /// With dynamic degree selection (see [Pipeline::with_max_degree]), this is the
+    /// chosen degree.
+    pub fn degree(&mut self) -> Result<DegreeType, Vec<String>> {
+        self.advance_to(Stage::GeneratedWitness)?;
+        let Some(Artifact::GeneratedWitness(GeneratedWitness { pil, .. })) = &self.artifact else {
+            panic!()
+        };
+        Ok(pil.degree())
+    }
+
+    /// Generates the witness and checks which mutations of single witness cells
+    /// are not detected by any identity. See [witness_fuzzer::fuzz_witness].
+    pub fn fuzz_witness(&mut self, config: &FuzzConfig) -> Result<FuzzReport<T>, Vec<String>> {
+        self.advance_to(Stage::GeneratedWitness)?;
+        let Some(Artifact::GeneratedWitness(GeneratedWitness {
+            pil,
+            fixed_cols,
+            witness,
+        })) = &self.artifact
+        else {
+            panic!()
+        };
+        let witness = witness
+            .as_ref()
+            .ok_or_else(|| vec!["No witness was generated.".to_string()])?;
+        witness_fuzzer::fuzz_witness(pil, fixed_cols, witness, config).map_err(|e| vec![e])
+    }
+
     pub fn proof(mut self) -> Result<ProofResult<T>, Vec<String>> {
         self.advance_to(Stage::Proof)?;
         let Artifact::Proof(proof) = self.artifact.unwrap() else {
@@ -942,6 +1095,12 @@ impl<T: FieldElement> Pipeline<T> {
         self.artifact.as_ref()
     }
 
+    /// Returns the error of the last failed witness generation, so that it can be
+    /// inspected after `advance_to` returned an error.
+    pub fn witgen_error(&self) -> Option<&WitgenError<T>> {
+        self.witgen_error.as_ref()
+    }
+
     pub fn data_callback(&self) -> Option<&dyn QueryCallback<T>> {
         self.arguments.query_callback.as_deref()
     }
@@ -975,7 +1134,7 @@ impl<T: FieldElement> Pipeline<T> {
                             .map(|file| file as &mut dyn std::io::Read),
                         None,
                     )
-                    .unwrap();
+                    .map_err(|e| vec![e.to_string()])?;
 
                 match backend.export_verification_key(&mut writer) {
                     Ok(()) => Ok(()),
@@ -997,11 +1156,11 @@ impl<T: FieldElement> Pipeline<T> {
                     .expect("backend must be set before generating verification key!");
                 let factory = backend.factory::<T>();
 
-                let mut setup_file = if let Some(path) = &self.arguments.setup_file {
-                    BufReader::new(fs::File::open(path).unwrap())
-                } else {
-                    panic!("Setup should have been provided for verification")
-                };
+                let mut setup_file = self
+                    .arguments
+                    .setup_file
+                    .as_ref()
+                    .map(|path| BufReader::new(fs::File::open(path).unwrap()));
 
                 let mut vkey_file = if let Some(ref path) = self.arguments.vkey_file {
                     BufReader::new(fs::File::open(path).unwrap())
@@ -1014,10 +1173,12 @@ impl<T: FieldElement> Pipeline<T> {
                         pil.borrow(),
                         &fixed_cols[..],
                         self.output_dir(),
-                        Some(&mut setup_file),
+                        setup_file
+                            .as_mut()
+                            .map(|file| file as &mut dyn std::io::Read),
                         Some(&mut vkey_file),
                     )
-                    .unwrap();
+                    .map_err(|e| vec![e.to_string()])?;
 
                 match backend.verify(&proof, instances) {
                     Ok(_) => Ok(()),
@@ -1029,3 +1190,23 @@ impl<T: FieldElement> Pipeline<T> {
         }
     }
 }
+
+/// @returns the number of rows of the witness that are not padding, i.e. the
+/// number of rows up to the last row in which any column differs from its value
+/// in the second to last row. The last row is not counted, because it usually
+/// differs in order to wrap around to the first row.
+fn used_rows<T: FieldElement>(witness: &[(String, Vec<T>)]) -> DegreeType {
+    witness
+        .iter()
+        .map(|(_, values)| {
+            let Some((_, rows)) = values.split_last() else {
+                return 0;
+            };
+            let padding = rows.last();
+            rows.iter()
+                .rposition(|v| Some(v) != padding)
+                .map_or(0, |row| row + 1)
+        })
+        .max()
+        .unwrap_or_default() as DegreeType
+}
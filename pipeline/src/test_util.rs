@@ -5,11 +5,11 @@ use powdr_pil_analyzer::evaluator::{self, SymbolLookup};
 use std::path::PathBuf;
 use std::rc::Rc;
 
-#[cfg(feature = "halo2")]
+use powdr_executor::witgen::extract_publics;
 use std::{fs::File, io::BufWriter};
 
 use crate::pipeline::{Pipeline, Stage};
-use crate::verify::verify;
+use crate::util::write_or_panic;
 
 pub fn resolve_test_file(file_name: &str) -> PathBuf {
     PathBuf::from(format!(
@@ -44,30 +44,45 @@ pub fn verify_asm_string<T: FieldElement>(
 }
 
 pub fn verify_pipeline<T: FieldElement>(pipeline: Pipeline<T>) {
-    let mut pipeline = pipeline.with_backend(BackendType::PilStarkCli);
-
-    let tmp_dir = mktemp::Temp::new_dir().unwrap();
-    if pipeline.output_dir().is_none() {
-        pipeline = pipeline.with_tmp_output(&tmp_dir);
-    }
-
-    // Don't get the proof, because that would destroy the pipeline
-    // which owns the temporary directory.
-    pipeline.advance_to(Stage::Proof).unwrap();
-
-    verify(pipeline.output_dir().unwrap(), pipeline.name(), None);
+    pipeline
+        .with_backend(BackendType::PilVerify)
+        .proof()
+        .unwrap();
 }
 
 pub fn gen_estark_proof(file_name: &str, inputs: Vec<GoldilocksField>) {
     let file_name = format!("{}/../test_data/{file_name}", env!("CARGO_MANIFEST_DIR"));
     let tmp_dir = mktemp::Temp::new_dir().unwrap();
-    Pipeline::default()
+    let mut pipeline = Pipeline::default()
         .with_tmp_output(&tmp_dir)
         .from_file(PathBuf::from(file_name))
         .with_prover_inputs(inputs)
-        .with_backend(powdr_backend::BackendType::EStark)
-        .proof()
-        .unwrap();
+        .with_backend(powdr_backend::BackendType::EStark);
+
+    let proof_artifact = pipeline.clone().proof().unwrap();
+
+    // Verify the proof against the exported verification key.
+    let vkey_file_path = tmp_dir.as_path().join("verification_key.json");
+    let vkey_file = BufWriter::new(File::create(&vkey_file_path).unwrap());
+    write_or_panic(vkey_file, |writer| {
+        pipeline.export_verification_key(writer).unwrap()
+    });
+
+    let publics = extract_publics(
+        proof_artifact.witness.as_ref().unwrap(),
+        pipeline.optimized_pil_ref().unwrap(),
+    )
+    .iter()
+    .map(|(_name, v)| *v)
+    .collect::<Vec<_>>();
+
+    let mut pipeline = pipeline.with_vkey_file(Some(vkey_file_path));
+    let proof = proof_artifact.proof.unwrap();
+    // Leaving out the public values must not skip their check.
+    if !publics.is_empty() {
+        assert!(pipeline.verify(proof.clone(), &[]).is_err());
+    }
+    pipeline.verify(proof, &[publics]).unwrap();
 }
 
 #[cfg(feature = "halo2")]
@@ -96,12 +111,33 @@ pub fn test_halo2(file_name: &str, inputs: Vec<Bn254Field>) {
 #[cfg(not(feature = "halo2"))]
 pub fn test_halo2(_file_name: &str, _inputs: Vec<Bn254Field>) {}
 
-#[cfg(feature = "halo2")]
-pub fn gen_halo2_proof(file_name: &str, inputs: Vec<Bn254Field>) {
-    use powdr_executor::witgen::extract_publics;
+#[cfg(feature = "plonky3")]
+pub fn test_plonky3(file_name: &str, inputs: Vec<GoldilocksField>) {
+    let pipeline = Pipeline::default()
+        .from_file(resolve_test_file(file_name))
+        .with_prover_inputs(inputs)
+        .with_backend(BackendType::Plonky3);
 
-    use crate::util::write_or_panic;
+    let result = pipeline.clone().generated_witness().unwrap();
+    let proof = pipeline.proof().unwrap().proof.unwrap();
 
+    // Verify the proof again, this time with the publics extracted from the witness.
+    let publics = extract_publics(result.witness.as_ref().unwrap(), &result.pil)
+        .into_iter()
+        .map(|(_, value)| value)
+        .collect();
+    let backend = BackendType::Plonky3
+        .factory::<GoldilocksField>()
+        .create(&result.pil, &result.fixed_cols, None, None, None)
+        .unwrap();
+    backend.verify(&proof, &[publics]).unwrap();
+}
+
+#[cfg(not(feature = "plonky3"))]
+pub fn test_plonky3(_file_name: &str, _inputs: Vec<GoldilocksField>) {}
+
+#[cfg(feature = "halo2")]
+pub fn gen_halo2_proof(file_name: &str, inputs: Vec<Bn254Field>) {
     let file_name = format!("{}/../test_data/{file_name}", env!("CARGO_MANIFEST_DIR"));
     let tmp_dir = mktemp::Temp::new_dir().unwrap();
     let mut pipeline = Pipeline::default()
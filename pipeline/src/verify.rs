@@ -1,42 +1,28 @@
-use std::{path::Path, process::Command};
+use std::path::Path;
 
-pub fn verify(temp_dir: &Path, name: &str, constants_name: Option<&str>) {
-    let pilcom = std::env::var("PILCOM")
-        .expect("Please set the PILCOM environment variable to the path to the pilcom repository.");
+use powdr_ast::analyzed::Analyzed;
+use powdr_executor::constraint_checker::check_constraints;
+use powdr_number::FieldElement;
+use powdr_schemas::SerializedAnalyzed;
 
+use crate::util::{read_poly_set, FixedPolySet, WitnessPolySet};
+
+/// Checks that the witness stored in `temp_dir` satisfies all constraints.
+/// The optimized PIL file and the fixed columns are read from the files named
+/// after `constants_name` (defaults to `name`), the witness is read from the
+/// file named after `name`.
+pub fn verify<T: FieldElement>(temp_dir: &Path, name: &str, constants_name: Option<&str>) {
     let constants_name = constants_name.unwrap_or(name);
 
-    let constants_file = format!(
-        "{}/{constants_name}_constants.bin",
-        temp_dir.to_str().unwrap()
-    );
-    let commits_file = format!("{}/{name}_commits.bin", temp_dir.to_str().unwrap());
-    let constraints_file = format!("{}/constraints.json", temp_dir.to_str().unwrap());
+    let pil: Analyzed<T> =
+        SerializedAnalyzed::deserialize_from(temp_dir.join(format!("{constants_name}_opt.pilo")))
+            .and_then(|serialized| serialized.try_into())
+            .unwrap_or_else(|e| panic!("Could not read the optimized PIL file: {e}"));
+    let (fixed, _) = read_poly_set::<FixedPolySet, T>(&pil, temp_dir, constants_name);
+    let (witness, _) = read_poly_set::<WitnessPolySet, T>(&pil, temp_dir, name);
 
-    let verifier_output = Command::new("node")
-        .args([
-            "--max-old-space-size=8000".to_string(), // 8GB of memory
-            format!("{pilcom}/src/main_pilverifier.js"),
-            commits_file,
-            "-j".to_string(),
-            constraints_file,
-            "-c".to_string(),
-            constants_file,
-        ])
-        .output()
-        .expect("failed to run pil verifier");
-    if !verifier_output.status.success() {
-        log::error!(
-            "Pil verifier run was unsuccessful.\nStdout: {}\nStderr: {}\n",
-            String::from_utf8_lossy(&verifier_output.stdout),
-            String::from_utf8_lossy(&verifier_output.stderr)
-        );
-        panic!("Pil verifier run was unsuccessful.");
-    } else {
-        let output = String::from_utf8(verifier_output.stdout).unwrap();
-        log::error!("PIL verifier output: {}", output);
-        if !output.trim().ends_with("PIL OK!!") {
-            panic!("Verified did not say 'PIL OK' for {name}.");
-        }
+    if let Err(e) = check_constraints(&pil, &fixed, &witness) {
+        panic!("Constraints are not satisfied for {name}:\n{e}");
     }
+    log::info!("All constraints are satisfied for {name}.");
 }
@@ -1,5 +1,8 @@
 use powdr_number::{FieldElement, GoldilocksField};
-use powdr_pipeline::test_util::{gen_estark_proof, test_halo2, verify_test_file};
+use powdr_pipeline::test_util::{
+    gen_estark_proof, resolve_test_file, test_halo2, verify_pipeline, verify_test_file,
+};
+use powdr_pipeline::{BackendType, LinkerMode, Pipeline};
 use test_log::test;
 
 fn verify_asm<T: FieldElement>(file_name: &str, inputs: Vec<T>) {
@@ -45,6 +48,19 @@ fn mem_write_once_external_write() {
     verify_test_file::<GoldilocksField>(f, Default::default(), vec![("main.v".to_string(), mem)]);
 }
 
+#[test]
+fn pc_jump() {
+    let f = "asm/pc_jump.asm";
+    // Jump out of the infinite loop in row 4.
+    let mut pc_jump_flag = vec![GoldilocksField::from(0); 16];
+    pc_jump_flag[4] = GoldilocksField::from(1);
+    verify_test_file::<GoldilocksField>(
+        f,
+        Default::default(),
+        vec![("main.pc_jump_flag".to_string(), pc_jump_flag)],
+    );
+}
+
 #[test]
 fn block_machine_cache_miss() {
     let f = "asm/block_machine_cache_miss.asm";
@@ -113,6 +129,29 @@ fn vm_to_block_unique_interface() {
     //gen_estark_proof(f, slice_to_vec(&i));
 }
 
+#[test]
+fn vm_to_block_different_degrees() {
+    let f = "asm/vm_to_block_different_degrees.asm";
+    let pipeline = Pipeline::<GoldilocksField>::default()
+        .from_file(resolve_test_file(f))
+        .with_prover_inputs(vec![])
+        .with_linker_mode(LinkerMode::PerMachine);
+    verify_pipeline(pipeline.clone());
+
+    // Backends that prove a single AIR reject the different degrees.
+    let Err(errors) = pipeline.with_backend(BackendType::EStark).proof() else {
+        panic!("Expected eStark to reject the different degrees");
+    };
+    assert!(errors[0].contains("same degree"), "{errors:?}");
+}
+
+#[test]
+#[should_panic = "Machine main_arith should have degree 32, found 16"]
+fn vm_to_block_different_degrees_monolithic() {
+    let f = "asm/vm_to_block_different_degrees.asm";
+    verify_asm::<GoldilocksField>(f, vec![]);
+}
+
 #[test]
 fn vm_to_block_to_block() {
     let f = "asm/vm_to_block_to_block.asm";
@@ -166,6 +205,17 @@ fn vm_to_vm_dynamic_trace_length() {
     gen_estark_proof(f, slice_to_vec(&i));
 }
 
+#[test]
+fn vm_to_vm_dynamic_trace_length_max_degree() {
+    let f = "asm/vm_to_vm_dynamic_trace_length.asm";
+    let mut pipeline = Pipeline::<GoldilocksField>::default()
+        .from_file(resolve_test_file(f))
+        .with_prover_inputs(vec![])
+        .with_max_degree(Some(1 << 12));
+    assert_eq!(pipeline.degree().unwrap(), 128);
+    verify_pipeline(pipeline);
+}
+
 #[test]
 fn vm_to_vm_to_block() {
     let f = "asm/vm_to_vm_to_block.asm";
@@ -341,7 +391,7 @@ mod book {
 }
 
 #[test]
-#[should_panic = "Witness generation failed."]
+#[should_panic = "Witness generation failed in machine Main Machine at row"]
 fn hello_world_asm_fail() {
     let f = "asm/book/hello_world.asm";
     let i = [1];
@@ -1,11 +1,11 @@
-#[cfg(feature = "halo2")]
-use powdr_number::Bn254Field;
-use powdr_number::GoldilocksField;
+use powdr_executor::witgen::WitgenError;
+use powdr_number::{Bn254Field, GoldilocksField};
 use powdr_pipeline::{
     test_util::{
-        gen_estark_proof, resolve_test_file, test_halo2, verify_pipeline, verify_test_file,
+        gen_estark_proof, resolve_test_file, test_halo2, test_plonky3, verify_pipeline,
+        verify_test_file,
     },
-    Pipeline,
+    Pipeline, Stage,
 };
 use test_log::test;
 
@@ -14,8 +14,8 @@ pub fn verify_pil(file_name: &str, inputs: Vec<GoldilocksField>) {
 }
 
 #[test]
-#[should_panic = "Pil verifier run was unsuccessful."]
-fn test_invalid_witness_pilcom() {
+#[should_panic = "Constraints are not satisfied"]
+fn test_invalid_witness_pil_verify() {
     let f = "pil/trivial.pil";
     let pipeline = Pipeline::default()
         .from_file(resolve_test_file(f))
@@ -27,7 +27,7 @@ fn test_invalid_witness_pilcom() {
 }
 
 #[test]
-#[should_panic = "assertion failed: stark_verify::<MerkleTreeGL,\\n            TranscriptGL>(&starkproof, &setup.const_root, &setup.starkinfo,\\n        &self.params, &mut setup.program).unwrap()"]
+#[should_panic = "Proof is invalid."]
 fn test_invalid_witness_estark() {
     let f = "pil/trivial.pil";
     Pipeline::default()
@@ -73,6 +73,17 @@ fn test_fibonacci() {
     verify_pil(f, Default::default());
     test_halo2(f, Default::default());
     gen_estark_proof(f, Default::default());
+    test_plonky3(f, Default::default());
+}
+
+#[test]
+fn fuzz_fibonacci() {
+    let report = Pipeline::<GoldilocksField>::default()
+        .from_file(resolve_test_file("pil/fibonacci.pil"))
+        .fuzz_witness(&Default::default())
+        .unwrap();
+    assert!(report.mutations_checked > 0);
+    assert!(report.surviving_mutations.is_empty(), "{report}");
 }
 
 #[test]
@@ -92,7 +103,7 @@ fn fib_arrays() {
 }
 
 #[test]
-#[should_panic = "Witness generation failed."]
+#[should_panic = "Witness generation failed in machine Main Machine at row"]
 fn test_external_witgen_fails_if_none_provided() {
     let f = "pil/external_witgen.pil";
     verify_pil(f, Default::default());
@@ -123,7 +134,7 @@ fn test_external_witgen_both_provided() {
 }
 
 #[test]
-#[should_panic = "Witness generation failed."]
+#[should_panic = "Witness generation failed in machine Main Machine at row"]
 fn test_external_witgen_fails_on_conflicting_external_witness() {
     let f = "pil/external_witgen.pil";
     let external_witness = vec![
@@ -165,7 +176,7 @@ fn test_witness_lookup() {
 }
 
 #[test]
-#[should_panic(expected = "Witness generation failed.")]
+#[should_panic(expected = "Witness generation failed in machine Main Machine at row")]
 fn test_underdetermined_zero_no_solution() {
     verify_pil(
         "pil/underdetermined_zero_no_solution.pil",
@@ -173,6 +184,19 @@ fn test_underdetermined_zero_no_solution() {
     );
 }
 
+#[test]
+fn test_witgen_error_is_kept() {
+    let mut pipeline = Pipeline::<GoldilocksField>::default().from_file(resolve_test_file(
+        "pil/underdetermined_zero_no_solution.pil",
+    ));
+    assert!(pipeline.advance_to(Stage::GeneratedWitness).is_err());
+    let WitgenError::RowFailed(failure) = pipeline.witgen_error().unwrap() else {
+        panic!("Expected a row failure");
+    };
+    assert_eq!(failure.machine, "Main Machine");
+    assert!(!failure.failed_identities.is_empty());
+}
+
 #[test]
 fn test_pair_lookup() {
     let f = "pil/pair_lookup.pil";
@@ -230,6 +254,30 @@ fn test_witness_via_let() {
     verify_pil("pil/witness_via_let.pil", Default::default());
 }
 
+#[test]
+fn test_witness_only() {
+    let f = "pil/witness_only.pil";
+    verify_pil(f, Default::default());
+    test_plonky3(f, Default::default());
+}
+
+#[test]
+fn test_array_query() {
+    verify_pil("pil/array_query.pil", Default::default());
+}
+
+#[test]
+fn test_copy_constraints() {
+    verify_pil("pil/copy_constraints.pil", Default::default());
+}
+
+#[test]
+fn test_copy_constraints_bn254() {
+    let f = "pil/copy_constraints_bn254.pil";
+    verify_test_file::<Bn254Field>(f, Default::default(), vec![]);
+    test_halo2(f, Default::default());
+}
+
 #[test]
 fn conditional_fixed_constraints() {
     verify_pil("pil/conditional_fixed_constraints.pil", Default::default());
@@ -1,8 +1,12 @@
+use powdr_executor::witness_fuzzer::FuzzConfig;
 use powdr_number::GoldilocksField;
 
-use powdr_pipeline::test_util::{
-    evaluate_integer_function, gen_estark_proof, gen_halo2_proof, std_analyzed, test_halo2,
-    verify_test_file,
+use powdr_pipeline::{
+    test_util::{
+        evaluate_integer_function, gen_estark_proof, gen_halo2_proof, resolve_test_file,
+        std_analyzed, test_halo2, verify_test_file,
+    },
+    Pipeline,
 };
 use test_log::test;
 
@@ -26,6 +30,21 @@ fn poseidon_gl_test() {
     gen_estark_proof(f, Default::default());
 }
 
+#[test]
+fn keccakf_test() {
+    let f = "std/keccakf_test.asm";
+    verify_test_file::<GoldilocksField>(f, Default::default(), vec![]);
+    test_halo2(f, Default::default());
+    gen_estark_proof(f, Default::default());
+}
+
+#[test]
+fn sha256_test() {
+    let f = "std/sha256_test.asm";
+    verify_test_file::<GoldilocksField>(f, Default::default(), vec![]);
+    gen_estark_proof(f, Default::default());
+}
+
 #[test]
 fn split_bn254_test() {
     let f = "std/split_bn254_test.asm";
@@ -39,6 +58,25 @@ fn split_gl_test() {
     gen_estark_proof(f, Default::default());
 }
 
+#[test]
+fn split_gl_fuzz() {
+    let config = FuzzConfig {
+        max_rows_per_column: 4,
+        ..Default::default()
+    };
+    let report = Pipeline::<GoldilocksField>::default()
+        .from_file(resolve_test_file("std/split_gl_test.asm"))
+        .with_prover_inputs(vec![])
+        .fuzz_witness(&config)
+        .unwrap();
+    // Free value columns are only constrained in the rows in which they are read.
+    let columns = report.potentially_under_constrained_columns();
+    assert!(
+        columns.iter().all(|column| column.ends_with("_free_value")),
+        "{report}"
+    );
+}
+
 #[test]
 #[ignore = "Too slow"]
 fn arith_test() {
@@ -0,0 +1,363 @@
+//! A plonky3 adapter for powdr
+//!
+//! Supports witness and fixed columns, polynomial identities up to degree
+//! `MAX_CONSTRAINT_DEGREE` and public values that refer to the first or the
+//! last row. The fixed columns are committed to as the preprocessed trace.
+//! Lookups, permutations and connect identities are rejected when the circuit
+//! is created.
+
+use std::collections::HashMap;
+
+use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues, BaseAir, PairBuilder};
+use p3_field::AbstractField;
+use p3_goldilocks::Goldilocks;
+use p3_matrix::{dense::RowMajorMatrix, Matrix};
+use powdr_ast::analyzed::{
+    AlgebraicBinaryOperator, AlgebraicExpression, AlgebraicUnaryOperator, Analyzed, Identity,
+    IdentityKind, PolynomialType,
+};
+use powdr_ast::parsed::utils::expr_any;
+use powdr_number::{DegreeType, FieldElement, KnownField};
+
+use crate::{params::MAX_CONSTRAINT_DEGREE, Error};
+
+/// A public value: the cell of a witness column at the first or the last row.
+struct Public {
+    column: usize,
+    row: PublicRow,
+}
+
+enum PublicRow {
+    First,
+    Last,
+}
+
+/// The cells of the current and the next row of the witness and fixed columns.
+struct Rows<'r, V> {
+    local: &'r [V],
+    next: &'r [V],
+    fixed_local: &'r [V],
+    fixed_next: &'r [V],
+}
+
+pub(crate) struct PowdrCircuit<'a, T> {
+    analyzed: &'a Analyzed<T>,
+    /// The values of the fixed columns, ordered by their ID, or `None` if there are none.
+    preprocessed: Option<RowMajorMatrix<Goldilocks>>,
+    /// The polynomial identities, with intermediate polynomials inlined.
+    identities: Vec<Identity<AlgebraicExpression<T>>>,
+    /// The public declarations, in source order.
+    publics: Vec<Public>,
+}
+
+impl<'a, T: FieldElement> PowdrCircuit<'a, T> {
+    /// Creates the circuit, or returns an error if the PIL uses a feature
+    /// that is not supported by the plonky3 backend.
+    pub(crate) fn new(
+        analyzed: &'a Analyzed<T>,
+        fixed: &'a [(String, Vec<T>)],
+    ) -> Result<Self, Error> {
+        if T::known_field() != Some(KnownField::GoldilocksField) {
+            return Err(Error::Unsupported(
+                "only the Goldilocks field is supported".to_string(),
+            ));
+        }
+        if !analyzed.degree().is_power_of_two() {
+            return Err(Error::Unsupported(format!(
+                "the degree has to be a power of two, but it is {}",
+                analyzed.degree()
+            )));
+        }
+        let identities = analyzed.identities_with_inlined_intermediate_polynomials();
+        if let Some(identity) = identities
+            .iter()
+            .find(|identity| identity.kind != IdentityKind::Polynomial)
+        {
+            return Err(Error::Unsupported(format!(
+                "only polynomial identities are supported, found: {identity}"
+            )));
+        }
+        if let Some(identity) = identities.iter().find(|identity| {
+            expr_any(identity.expression_for_poly_id(), |e| {
+                matches!(
+                    e,
+                    AlgebraicExpression::BinaryOperation(_, AlgebraicBinaryOperator::Pow, right)
+                        if !matches!(right.as_ref(), AlgebraicExpression::Number(_))
+                )
+            })
+        }) {
+            return Err(Error::Unsupported(format!(
+                "exponents have to be numbers, found: {identity}"
+            )));
+        }
+        if let Some(identity) = identities.iter().find(|identity| {
+            expression_degree(identity.expression_for_poly_id()) > MAX_CONSTRAINT_DEGREE
+        }) {
+            return Err(Error::Unsupported(format!(
+                "the degree of identities is limited to {MAX_CONSTRAINT_DEGREE} by the FRI \
+                 blowup factor, found: {identity}"
+            )));
+        }
+
+        let witness_columns: HashMap<String, usize> = analyzed
+            .committed_polys_in_source_order()
+            .into_iter()
+            .flat_map(|(symbol, _)| symbol.array_elements())
+            .map(|(name, poly_id)| (name, poly_id.id as usize))
+            .collect();
+        let publics = analyzed
+            .public_declarations_in_source_order()
+            .into_iter()
+            .map(|(name, public_declaration)| {
+                let column_name = public_declaration.referenced_poly_name();
+                let column = *witness_columns.get(&column_name).ok_or_else(|| {
+                    Error::Unsupported(format!(
+                        "public {name} refers to {column_name}, which is not a witness column"
+                    ))
+                })?;
+                let row = match public_declaration.index {
+                    0 => PublicRow::First,
+                    index if index == analyzed.degree() - 1 => PublicRow::Last,
+                    index => {
+                        return Err(Error::Unsupported(format!(
+                            "public values have to refer to the first or last row, \
+                             but {name} refers to row {index}"
+                        )))
+                    }
+                };
+                Ok(Public { column, row })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            analyzed,
+            preprocessed: preprocessed_trace(analyzed, fixed)?,
+            identities,
+            publics,
+        })
+    }
+
+    pub(crate) fn degree(&self) -> DegreeType {
+        self.analyzed.degree()
+    }
+
+    /// Returns the trace matrix, with the witness columns ordered by their ID.
+    pub(crate) fn generate_trace_rows(
+        &self,
+        witness: &[(String, Vec<T>)],
+    ) -> Result<RowMajorMatrix<Goldilocks>, Error> {
+        let witness: HashMap<&str, &Vec<T>> = witness
+            .iter()
+            .map(|(name, values)| (name.as_str(), values))
+            .collect();
+        let degree = self.degree() as usize;
+        let columns = self
+            .analyzed
+            .committed_polys_in_source_order()
+            .into_iter()
+            .flat_map(|(symbol, _)| symbol.array_elements())
+            .map(|(name, poly_id)| {
+                let values = witness
+                    .get(name.as_str())
+                    .ok_or_else(|| Error::InvalidWitness(format!("column {name} is missing")))?;
+                if values.len() != degree {
+                    return Err(Error::InvalidWitness(format!(
+                        "column {name} has {} rows, but the degree is {degree}",
+                        values.len()
+                    )));
+                }
+                Ok((poly_id.id as usize, *values))
+            })
+            .collect::<Result<HashMap<_, _>, _>>()?;
+
+        let width = self.analyzed.commitment_count();
+        let values = (0..degree)
+            .flat_map(|row| (0..width).map(move |column| (row, column)))
+            .map(|(row, column)| to_goldilocks(columns[&column][row]))
+            .collect();
+        Ok(RowMajorMatrix::new(values, width))
+    }
+
+    /// Returns the values of the public declarations, in source order.
+    pub(crate) fn public_values(&self, trace: &RowMajorMatrix<Goldilocks>) -> Vec<Goldilocks> {
+        self.publics
+            .iter()
+            .map(|public| {
+                let row = match public.row {
+                    PublicRow::First => 0,
+                    PublicRow::Last => trace.height() - 1,
+                };
+                trace.values[row * trace.width() + public.column]
+            })
+            .collect()
+    }
+
+    fn to_plonky3_expr<AB: AirBuilderWithPublicValues<F = Goldilocks>>(
+        &self,
+        e: &AlgebraicExpression<T>,
+        rows: &Rows<AB::Var>,
+        publics: &[AB::PublicVar],
+    ) -> AB::Expr {
+        match e {
+            AlgebraicExpression::Reference(r) => {
+                let (local, next) = match r.poly_id.ptype {
+                    PolynomialType::Committed => (rows.local, rows.next),
+                    PolynomialType::Constant => (rows.fixed_local, rows.fixed_next),
+                    PolynomialType::Intermediate => {
+                        unreachable!("Intermediate polynomials are inlined in new: {r}")
+                    }
+                };
+                let row = if r.next { next } else { local };
+                row[r.poly_id.id as usize].into()
+            }
+            AlgebraicExpression::PublicReference(name) => {
+                let index = self
+                    .analyzed
+                    .public_declarations_in_source_order()
+                    .iter()
+                    .position(|(public_name, _)| *public_name == name)
+                    .unwrap();
+                publics[index].into()
+            }
+            AlgebraicExpression::Number(n) => AB::Expr::from_canonical_u64(n.to_degree()),
+            AlgebraicExpression::BinaryOperation(left, op, right) => {
+                let left = self.to_plonky3_expr::<AB>(left, rows, publics);
+                match op {
+                    AlgebraicBinaryOperator::Pow => {
+                        let AlgebraicExpression::Number(exponent) = right.as_ref() else {
+                            unreachable!("Non-constant exponents are rejected in new: {right}")
+                        };
+                        left.exp_u64(exponent.to_degree())
+                    }
+                    op => {
+                        let right = self.to_plonky3_expr::<AB>(right, rows, publics);
+                        match op {
+                            AlgebraicBinaryOperator::Add => left + right,
+                            AlgebraicBinaryOperator::Sub => left - right,
+                            AlgebraicBinaryOperator::Mul => left * right,
+                            AlgebraicBinaryOperator::Pow => unreachable!(),
+                        }
+                    }
+                }
+            }
+            AlgebraicExpression::UnaryOperation(AlgebraicUnaryOperator::Minus, e) => {
+                -self.to_plonky3_expr::<AB>(e, rows, publics)
+            }
+        }
+    }
+}
+
+/// Returns the matrix of the fixed columns, ordered by their ID, or `None` if
+/// there are no fixed columns.
+fn preprocessed_trace<T: FieldElement>(
+    analyzed: &Analyzed<T>,
+    fixed: &[(String, Vec<T>)],
+) -> Result<Option<RowMajorMatrix<Goldilocks>>, Error> {
+    let width = analyzed.constant_count();
+    if width == 0 {
+        return Ok(None);
+    }
+    let fixed: HashMap<&str, &Vec<T>> = fixed
+        .iter()
+        .map(|(name, values)| (name.as_str(), values))
+        .collect();
+    let degree = analyzed.degree() as usize;
+    let columns = analyzed
+        .constant_polys_in_source_order()
+        .into_iter()
+        .flat_map(|(symbol, _)| symbol.array_elements())
+        .map(|(name, poly_id)| {
+            let values = fixed
+                .get(name.as_str())
+                .ok_or_else(|| Error::Unsupported(format!("fixed column {name} is missing")))?;
+            if values.len() != degree {
+                return Err(Error::Unsupported(format!(
+                    "fixed column {name} has {} rows, but the degree is {degree}",
+                    values.len()
+                )));
+            }
+            Ok((poly_id.id as usize, *values))
+        })
+        .collect::<Result<HashMap<_, _>, _>>()?;
+
+    let values = (0..degree)
+        .flat_map(|row| (0..width).map(move |column| (row, column)))
+        .map(|(row, column)| to_goldilocks(columns[&column][row]))
+        .collect();
+    Ok(Some(RowMajorMatrix::new(values, width)))
+}
+
+/// Returns the degree of the expression in the columns it references.
+fn expression_degree<T: FieldElement>(e: &AlgebraicExpression<T>) -> usize {
+    match e {
+        AlgebraicExpression::Reference(_) => 1,
+        AlgebraicExpression::PublicReference(_) | AlgebraicExpression::Number(_) => 0,
+        AlgebraicExpression::BinaryOperation(left, op, right) => match op {
+            AlgebraicBinaryOperator::Add | AlgebraicBinaryOperator::Sub => {
+                expression_degree(left).max(expression_degree(right))
+            }
+            AlgebraicBinaryOperator::Mul => expression_degree(left) + expression_degree(right),
+            AlgebraicBinaryOperator::Pow => match right.as_ref() {
+                AlgebraicExpression::Number(exponent) => {
+                    expression_degree(left) * exponent.to_degree() as usize
+                }
+                _ => unreachable!("Non-constant exponents are rejected before: {right}"),
+            },
+        },
+        AlgebraicExpression::UnaryOperation(_, e) => expression_degree(e),
+    }
+}
+
+/// Converts a field element into a Goldilocks element. The field has been
+/// checked to be Goldilocks when the circuit was created.
+fn to_goldilocks<T: FieldElement>(v: T) -> Goldilocks {
+    Goldilocks::from_canonical_u64(v.to_degree())
+}
+
+impl<'a, T: FieldElement> BaseAir<Goldilocks> for PowdrCircuit<'a, T> {
+    fn width(&self) -> usize {
+        self.analyzed.commitment_count()
+    }
+
+    fn preprocessed_trace(&self) -> Option<RowMajorMatrix<Goldilocks>> {
+        self.preprocessed.clone()
+    }
+}
+
+impl<'a, T: FieldElement, AB: AirBuilderWithPublicValues<F = Goldilocks> + PairBuilder> Air<AB>
+    for PowdrCircuit<'a, T>
+{
+    fn eval(&self, builder: &mut AB) {
+        let matrix = builder.main();
+        let local = matrix.row_slice(0);
+        let next = matrix.row_slice(1);
+        // Without fixed columns, there is no preprocessed trace.
+        let (fixed_local, fixed_next) = match self.preprocessed {
+            Some(_) => {
+                let fixed = builder.preprocessed();
+                (fixed.row_slice(0).to_vec(), fixed.row_slice(1).to_vec())
+            }
+            None => (vec![], vec![]),
+        };
+        let publics = builder.public_values().to_vec();
+        let rows = Rows {
+            local: &local,
+            next: &next,
+            fixed_local: &fixed_local,
+            fixed_next: &fixed_next,
+        };
+
+        for identity in &self.identities {
+            let e = self.to_plonky3_expr::<AB>(identity.expression_for_poly_id(), &rows, &publics);
+            builder.assert_zero(e);
+        }
+
+        for (public, value) in self.publics.iter().zip(&publics) {
+            let cell = local[public.column];
+            match public.row {
+                PublicRow::First => builder.when_first_row().assert_eq(cell, *value),
+                PublicRow::Last => builder.when_last_row().assert_eq(cell, *value),
+            }
+        }
+    }
+}
@@ -0,0 +1,14 @@
+/// An error of the plonky3 backend.
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("the PIL is not supported by the plonky3 backend: {0}")]
+    Unsupported(String),
+    #[error("invalid witness: {0}")]
+    InvalidWitness(String),
+    #[error("invalid verification key: {0}")]
+    InvalidVerificationKey(String),
+    #[error("proof verification failed: {0}")]
+    VerificationFailed(String),
+    #[error("serialization failed: {0}")]
+    Serialization(String),
+}
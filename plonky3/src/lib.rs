@@ -0,0 +1,9 @@
+#![deny(clippy::print_stdout)]
+
+mod circuit_builder;
+mod error;
+mod params;
+mod prover;
+
+pub use error::Error;
+pub use prover::*;
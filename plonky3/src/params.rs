@@ -0,0 +1,99 @@
+//! The FRI and hash parameters used by the Plonky3 backend over Goldilocks.
+
+use lazy_static::lazy_static;
+
+use p3_challenger::DuplexChallenger;
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::{extension::BinomialExtensionField, Field};
+use p3_fri::{FriConfig, TwoAdicFriPcs};
+use p3_goldilocks::{DiffusionMatrixGoldilocks, Goldilocks};
+use p3_merkle_tree::FieldMerkleTreeMmcs;
+use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixGeneral};
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use p3_uni_stark::StarkConfig;
+use p3_util::log2_strict_usize;
+use rand::{distributions::Standard, Rng, SeedableRng};
+
+const D: u64 = 7;
+const WIDTH: usize = 8;
+// Number of full rounds and partial rounds of the Poseidon2 permutation.
+const ROUNDS_F: usize = 8;
+const ROUNDS_P: usize = 22;
+const RATE: usize = 4;
+const OUT: usize = 4;
+const N: usize = 2;
+const CHUNK: usize = 4;
+const DIGEST_ELEMS: usize = 4;
+
+pub(crate) const FRI_LOG_BLOWUP: usize = 1;
+pub(crate) const FRI_NUM_QUERIES: usize = 100;
+pub(crate) const FRI_PROOF_OF_WORK_BITS: usize = 16;
+
+/// The quotient of a constraint of degree `d` has degree `d - 1` times the trace
+/// length, which has to fit into the blowup of the FRI domain.
+pub(crate) const MAX_CONSTRAINT_DEGREE: usize = (1 << FRI_LOG_BLOWUP) + 1;
+
+/// The round constants are derived from a fixed seed, so that the prover and
+/// the verifier agree on the permutation without any setup.
+const RNG_SEED: u64 = 42;
+
+type Perm =
+    Poseidon2<Goldilocks, Poseidon2ExternalMatrixGeneral, DiffusionMatrixGoldilocks, WIDTH, D>;
+type Hash = PaddingFreeSponge<Perm, WIDTH, RATE, OUT>;
+type Compress = TruncatedPermutation<Perm, N, CHUNK, WIDTH>;
+type ValMmcs = FieldMerkleTreeMmcs<
+    <Goldilocks as Field>::Packing,
+    <Goldilocks as Field>::Packing,
+    Hash,
+    Compress,
+    DIGEST_ELEMS,
+>;
+type Challenge = BinomialExtensionField<Goldilocks, 2>;
+type ChallengeMmcs = ExtensionMmcs<Goldilocks, Challenge, ValMmcs>;
+type Dft = Radix2DitParallel;
+type Pcs = TwoAdicFriPcs<Goldilocks, Dft, ValMmcs, ChallengeMmcs>;
+
+pub(crate) type Challenger = DuplexChallenger<Goldilocks, Perm, WIDTH>;
+pub(crate) type Config = StarkConfig<Pcs, Challenge, Challenger>;
+
+lazy_static! {
+    static ref PERM: Perm = {
+        let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(RNG_SEED);
+        Perm::new(
+            ROUNDS_F,
+            (&mut rng)
+                .sample_iter(Standard)
+                .take(ROUNDS_F)
+                .collect::<Vec<[Goldilocks; WIDTH]>>(),
+            Poseidon2ExternalMatrixGeneral,
+            ROUNDS_P,
+            (&mut rng).sample_iter(Standard).take(ROUNDS_P).collect(),
+            DiffusionMatrixGoldilocks,
+        )
+    };
+}
+
+pub(crate) fn get_challenger() -> Challenger {
+    Challenger::new(PERM.clone())
+}
+
+pub(crate) fn get_config(degree: u64) -> Config {
+    let hash = Hash::new(PERM.clone());
+    let compress = Compress::new(PERM.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_config = FriConfig {
+        log_blowup: FRI_LOG_BLOWUP,
+        num_queries: FRI_NUM_QUERIES,
+        proof_of_work_bits: FRI_PROOF_OF_WORK_BITS,
+        mmcs: challenge_mmcs,
+    };
+    let pcs = Pcs::new(
+        log2_strict_usize(degree as usize),
+        Dft {},
+        val_mmcs,
+        fri_config,
+    );
+    Config::new(pcs)
+}
@@ -0,0 +1,354 @@
+//! A plonky3 prover using FRI and Poseidon2 over Goldilocks
+
+use std::{io, time::Instant};
+
+use p3_air::BaseAir;
+use p3_commit::Pcs as _;
+use p3_field::AbstractField;
+use p3_goldilocks::Goldilocks;
+use p3_uni_stark::{
+    prove_with_key, verify_with_key, Proof, StarkGenericConfig, StarkProvingKey, StarkVerifyingKey,
+};
+use powdr_ast::analyzed::Analyzed;
+use powdr_number::{DegreeType, FieldElement};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    circuit_builder::PowdrCircuit,
+    params::{
+        get_challenger, get_config, Config, FRI_LOG_BLOWUP, FRI_NUM_QUERIES,
+        FRI_PROOF_OF_WORK_BITS,
+    },
+    Error,
+};
+
+/// Identifies the circuit and the parameters a proof was generated with.
+/// Since the backend has no setup, the key is derived from the PIL alone,
+/// and a verifier only accepts a key that matches the PIL it was created with.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct VerificationKey {
+    degree: DegreeType,
+    fri_log_blowup: usize,
+    fri_num_queries: usize,
+    fri_proof_of_work_bits: usize,
+    /// The polynomial identities, with intermediate polynomials inlined.
+    identities: Vec<String>,
+    /// The public declarations, in source order.
+    publics: Vec<String>,
+    /// The fixed columns, in source order. Their values are committed to by the
+    /// prover and the verifier alike, so they are not part of the key.
+    fixed_columns: Vec<String>,
+}
+
+impl VerificationKey {
+    fn new<T: FieldElement>(analyzed: &Analyzed<T>) -> Self {
+        Self {
+            degree: analyzed.degree(),
+            fri_log_blowup: FRI_LOG_BLOWUP,
+            fri_num_queries: FRI_NUM_QUERIES,
+            fri_proof_of_work_bits: FRI_PROOF_OF_WORK_BITS,
+            identities: analyzed
+                .identities_with_inlined_intermediate_polynomials()
+                .iter()
+                .map(|identity| identity.to_string())
+                .collect(),
+            publics: analyzed
+                .public_declarations_in_source_order()
+                .into_iter()
+                .map(|(name, public_declaration)| {
+                    format!(
+                        "{name} = {}({})",
+                        public_declaration.referenced_poly_name(),
+                        public_declaration.index
+                    )
+                })
+                .collect(),
+            fixed_columns: analyzed
+                .constant_polys_in_source_order()
+                .into_iter()
+                .flat_map(|(symbol, _)| symbol.array_elements())
+                .map(|(name, _)| name)
+                .collect(),
+        }
+    }
+}
+
+/// A prover that does not need a setup: all parameters are derived from the PIL
+/// and the fixed columns.
+pub struct Plonky3Prover<'a, T> {
+    circuit: PowdrCircuit<'a, T>,
+    verification_key: VerificationKey,
+    /// The commitment to the fixed columns, if there are any.
+    proving_key: Option<StarkProvingKey<Config>>,
+    verifying_key: Option<StarkVerifyingKey<Config>>,
+}
+
+impl<'a, T: FieldElement> Plonky3Prover<'a, T> {
+    pub fn new(analyzed: &'a Analyzed<T>, fixed: &'a [(String, Vec<T>)]) -> Result<Self, Error> {
+        let circuit = PowdrCircuit::new(analyzed, fixed)?;
+        let (proving_key, verifying_key) = match circuit.preprocessed_trace() {
+            Some(preprocessed) => {
+                let config = get_config(circuit.degree());
+                let pcs = config.pcs();
+                let domain = pcs.natural_domain_for_degree(circuit.degree() as usize);
+                let (commit, data) = pcs.commit(vec![(domain, preprocessed)]);
+                (
+                    Some(StarkProvingKey {
+                        preprocessed_commit: commit.clone(),
+                        preprocessed_data: data,
+                    }),
+                    Some(StarkVerifyingKey {
+                        preprocessed_commit: commit,
+                    }),
+                )
+            }
+            None => (None, None),
+        };
+        Ok(Self {
+            circuit,
+            verification_key: VerificationKey::new(analyzed),
+            proving_key,
+            verifying_key,
+        })
+    }
+
+    /// Creates a prover from a verification key exported by
+    /// [Plonky3Prover::export_verification_key], and fails if the key was not
+    /// exported for the same PIL and parameters.
+    pub fn new_with_verification_key(
+        analyzed: &'a Analyzed<T>,
+        fixed: &'a [(String, Vec<T>)],
+        verification_key: &mut dyn io::Read,
+    ) -> Result<Self, Error> {
+        let prover = Self::new(analyzed, fixed)?;
+        let verification_key: VerificationKey = serde_json::from_reader(verification_key)
+            .map_err(|e| Error::InvalidVerificationKey(e.to_string()))?;
+        if verification_key != prover.verification_key {
+            return Err(Error::InvalidVerificationKey(
+                "the key does not match the PIL or the FRI parameters".to_string(),
+            ));
+        }
+        Ok(prover)
+    }
+
+    pub fn export_verification_key(&self, output: &mut dyn io::Write) -> Result<(), Error> {
+        serde_json::to_writer(output, &self.verification_key)
+            .map_err(|e| Error::Serialization(e.to_string()))
+    }
+
+    /// Generates a proof for the given witness and verifies it.
+    pub fn prove(&self, witness: &[(String, Vec<T>)]) -> Result<Vec<u8>, Error> {
+        log::info!("Starting proof generation...");
+        let start = Instant::now();
+
+        let trace = self.circuit.generate_trace_rows(witness)?;
+        let publics = self.circuit.public_values(&trace);
+
+        let config = get_config(self.circuit.degree());
+        let proof = prove_with_key(
+            &config,
+            self.proving_key.as_ref(),
+            &self.circuit,
+            &mut get_challenger(),
+            trace,
+            &publics,
+        );
+
+        log::info!("Time taken: {:?}", start.elapsed());
+
+        self.verify_inner(&config, &proof, &publics)?;
+
+        log::info!("Proof generation done.");
+
+        serde_json::to_vec(&proof).map_err(|e| Error::Serialization(e.to_string()))
+    }
+
+    /// Verifies a proof against the public values, which have to be given in
+    /// the order of their declaration.
+    pub fn verify(&self, proof: &[u8], instances: &[Vec<T>]) -> Result<(), Error> {
+        let proof: Proof<Config> = serde_json::from_slice(proof)
+            .map_err(|e| Error::VerificationFailed(format!("invalid proof: {e}")))?;
+        let publics = instances
+            .iter()
+            .flatten()
+            .map(|v| Goldilocks::from_canonical_u64(v.to_degree()))
+            .collect::<Vec<_>>();
+
+        self.verify_inner(&get_config(self.circuit.degree()), &proof, &publics)
+    }
+
+    fn verify_inner(
+        &self,
+        config: &Config,
+        proof: &Proof<Config>,
+        publics: &[Goldilocks],
+    ) -> Result<(), Error> {
+        verify_with_key(
+            config,
+            self.verifying_key.as_ref(),
+            &self.circuit,
+            &mut get_challenger(),
+            proof,
+            &publics.to_vec(),
+        )
+        .map_err(|e| Error::VerificationFailed(format!("{e:?}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use powdr_number::{Bn254Field, GoldilocksField};
+    use powdr_pipeline::{pipeline::PilWithEvaluatedFixedCols, Pipeline};
+    use test_log::test;
+
+    use super::*;
+
+    fn compile<T: FieldElement>(pil: &str) -> PilWithEvaluatedFixedCols<T> {
+        Pipeline::<T>::default()
+            .from_pil_string(pil.to_string())
+            .pil_with_evaluated_fixed_cols()
+            .unwrap()
+    }
+
+    /// Proves the PIL with the given witness and verifies the proof against
+    /// the given public values.
+    fn run_test_goldilocks(
+        pil: &str,
+        witness: &[(&str, Vec<u64>)],
+        publics: Vec<GoldilocksField>,
+    ) -> Result<(), Error> {
+        let PilWithEvaluatedFixedCols { pil, fixed_cols } = compile(pil);
+        let witness = witness
+            .iter()
+            .map(|(name, values)| {
+                let values = values.iter().map(|v| (*v).into()).collect();
+                (name.to_string(), values)
+            })
+            .collect::<Vec<_>>();
+
+        let prover = Plonky3Prover::new(&pil, &fixed_cols).unwrap();
+        let proof = prover.prove(&witness).unwrap();
+        prover.verify(&proof, &[publics])
+    }
+
+    #[test]
+    fn alternating() {
+        let content = r#"
+        namespace Alternating(8);
+            let x;
+            let y;
+            x * (1 - x) = 0;
+            x' = 1 - x;
+            y = 3 * x + 1;
+        "#;
+        let witness = [
+            ("Alternating.x", vec![0, 1, 0, 1, 0, 1, 0, 1]),
+            ("Alternating.y", vec![1, 4, 1, 4, 1, 4, 1, 4]),
+        ];
+        run_test_goldilocks(content, &witness, vec![]).unwrap();
+    }
+
+    #[test]
+    fn publics() {
+        let content = r#"
+        namespace Alternating(8);
+            let x;
+            x * (1 - x) = 0;
+            x' = 1 - x;
+            public first = x(0);
+            public last = x(7);
+        "#;
+        let witness = [("Alternating.x", vec![0, 1, 0, 1, 0, 1, 0, 1])];
+        run_test_goldilocks(content, &witness, vec![0.into(), 1.into()]).unwrap();
+        run_test_goldilocks(content, &witness, vec![1.into(), 0.into()]).unwrap_err();
+    }
+
+    #[test]
+    fn fixed() {
+        let content = r#"
+        namespace Fixed(8);
+            col fixed FIRST = [1] + [0]*;
+            col fixed EVEN(i) { (i + 1) % 2 };
+            let x;
+            FIRST * x = 0;
+            x' = EVEN;
+        "#;
+        let witness = [("Fixed.x", vec![0, 1, 0, 1, 0, 1, 0, 1])];
+        run_test_goldilocks(content, &witness, vec![]).unwrap();
+    }
+
+    #[test]
+    fn degree_too_high() {
+        let content = r#"
+        namespace Degree(8);
+            let x;
+            x * x * x * (1 - x) = 0;
+        "#;
+        let PilWithEvaluatedFixedCols { pil, fixed_cols } = compile::<GoldilocksField>(content);
+        assert!(matches!(
+            Plonky3Prover::new(&pil, &fixed_cols),
+            Err(Error::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn unsupported_features() {
+        let content = r#"
+        namespace Pow(8);
+            let x;
+            let y;
+            x * (1 - x) = 0;
+            y = x ** x;
+        "#;
+        let PilWithEvaluatedFixedCols { pil, fixed_cols } = compile::<GoldilocksField>(content);
+        assert!(matches!(
+            Plonky3Prover::new(&pil, &fixed_cols),
+            Err(Error::Unsupported(_))
+        ));
+
+        let content = r#"
+        namespace Bn254(8);
+            let x;
+            x * (1 - x) = 0;
+        "#;
+        let PilWithEvaluatedFixedCols { pil, fixed_cols } = compile::<Bn254Field>(content);
+        assert!(Plonky3Prover::new(&pil, &fixed_cols).is_err());
+    }
+
+    #[test]
+    fn verification_key() {
+        let pil = |degree| {
+            let content = format!(
+                r#"
+                namespace Alternating({degree});
+                    let x;
+                    x * (1 - x) = 0;
+                    x' = 1 - x;
+                    public first = x(0);
+                "#
+            );
+            compile::<GoldilocksField>(&content)
+        };
+        let pil_8 = pil(8);
+        let prover = Plonky3Prover::new(&pil_8.pil, &pil_8.fixed_cols).unwrap();
+        let witness = vec![(
+            "Alternating.x".to_string(),
+            [0u64, 1, 0, 1, 0, 1, 0, 1].map(GoldilocksField::from).to_vec(),
+        )];
+        let proof = prover.prove(&witness).unwrap();
+
+        let mut vkey = vec![];
+        prover.export_verification_key(&mut vkey).unwrap();
+
+        let verifier =
+            Plonky3Prover::new_with_verification_key(&pil_8.pil, &pil_8.fixed_cols, &mut &vkey[..])
+                .unwrap();
+        verifier.verify(&proof, &[vec![0.into()]]).unwrap();
+
+        let pil_16 = pil(16);
+        assert!(matches!(
+            Plonky3Prover::new_with_verification_key(&pil_16.pil, &pil_16.fixed_cols, &mut &vkey[..]),
+            Err(Error::InvalidVerificationKey(_))
+        ));
+    }
+}
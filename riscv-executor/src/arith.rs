@@ -0,0 +1,65 @@
+use powdr_number::AbstractNumberType;
+
+/// Converts a 256-bit number, given as 32-bit limbs in little-endian order.
+fn from_limbs(limbs: &[u32; 8]) -> AbstractNumberType {
+    AbstractNumberType::from_slice(limbs)
+}
+
+/// Returns the lowest 256 bits of `value` as 32-bit limbs in little-endian order.
+fn to_limbs(value: &AbstractNumberType) -> [u32; 8] {
+    let digits = value.to_u32_digits();
+    std::array::from_fn(|i| digits.get(i).copied().unwrap_or_default())
+}
+
+/// Computes a * b + c, returning the high and the low 256 bits of the result.
+pub fn affine_256(a: &[u32; 8], b: &[u32; 8], c: &[u32; 8]) -> ([u32; 8], [u32; 8]) {
+    let result = from_limbs(a) * from_limbs(b) + from_limbs(c);
+    (to_limbs(&(&result >> 256)), to_limbs(&result))
+}
+
+/// Computes (hi * 2**256 + lo) % m.
+/// Like the arith machine, this returns the low 256 bits of the dividend if m is zero.
+pub fn mod_256(hi: &[u32; 8], lo: &[u32; 8], m: &[u32; 8]) -> [u32; 8] {
+    let dividend = (from_limbs(hi) << 256) + from_limbs(lo);
+    let m = from_limbs(m);
+    if m == AbstractNumberType::default() {
+        to_limbs(&dividend)
+    } else {
+        to_limbs(&(dividend % m))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_affine_256() {
+        let max = [0xffffffff; 8];
+        let one = [1, 0, 0, 0, 0, 0, 0, 0];
+        // (2**256 - 1) * 1 + (2**256 - 1) = 2**256 + (2**256 - 2)
+        let (hi, lo) = affine_256(&max, &one, &max);
+        assert_eq!(hi, one);
+        assert_eq!(
+            lo,
+            [
+                0xfffffffe, 0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff,
+                0xffffffff
+            ]
+        );
+    }
+
+    #[test]
+    fn test_mod_256() {
+        let eleven = [11, 0, 0, 0, 0, 0, 0, 0];
+        let three = [3, 0, 0, 0, 0, 0, 0, 0];
+        assert_eq!(mod_256(&[0; 8], &eleven, &three), [2, 0, 0, 0, 0, 0, 0, 0]);
+
+        // (2**256 + 1) % (2**256 - 1) = 2
+        let max = [0xffffffff; 8];
+        let one = [1, 0, 0, 0, 0, 0, 0, 0];
+        assert_eq!(mod_256(&one, &one, &max), [2, 0, 0, 0, 0, 0, 0, 0]);
+
+        assert_eq!(mod_256(&[0; 8], &eleven, &[0; 8]), eleven);
+    }
+}
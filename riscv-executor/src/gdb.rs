@@ -0,0 +1,307 @@
+//! A minimal stub for the GDB remote serial protocol, to debug guests while
+//! they are being executed.
+//!
+//! Note that the original RISC-V addresses do not exist in powdr: the PC seen
+//! by GDB is the PC of the powdr program, and a step executes one row, i.e. one
+//! powdr instruction. Breakpoints can be set at PCs of the powdr program or,
+//! through `monitor break <label>`, at labels. Breakpoints at addresses that
+//! are not PCs of the powdr program (like RISC-V addresses GDB took from an ELF
+//! file) are rejected.
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    io::{self, BufReader, Read, Write},
+    net::TcpStream,
+};
+
+use itertools::Itertools;
+
+use crate::{builder::TraceBuilder, Elem};
+
+/// The signals reported to GDB when the execution stops.
+pub const SIGILL: u8 = 4;
+pub const SIGTRAP: u8 = 5;
+
+/// Number of general purpose registers, followed by the PC in GDB's register numbering.
+const NUM_REGISTERS: usize = 32;
+
+/// The maximum size of a packet we accept or send, as announced in the `qSupported` reply.
+const PACKET_SIZE: u32 = 0x1000;
+
+pub struct GdbStub<'a> {
+    stream: BufReader<TcpStream>,
+    label_map: HashMap<&'a str, Elem>,
+    /// The number of PCs of the powdr program, i.e. the PCs are `0..num_pcs`.
+    num_pcs: u32,
+    breakpoints: BTreeSet<u32>,
+    /// Whether to stop at the next row.
+    stepping: bool,
+    /// Whether GDB is waiting for a stop reply.
+    resumed: bool,
+    attached: bool,
+    killed: bool,
+    /// The PC of the row of the last executed statement.
+    last_pc: Option<u32>,
+}
+
+impl<'a> GdbStub<'a> {
+    /// Creates a stub that talks to GDB through an established connection.
+    /// The execution is stopped at the first row, so that breakpoints can be set.
+    pub fn new(stream: TcpStream, label_map: HashMap<&'a str, Elem>, num_pcs: u32) -> Self {
+        Self {
+            stream: BufReader::new(stream),
+            label_map,
+            num_pcs,
+            breakpoints: BTreeSet::new(),
+            stepping: true,
+            resumed: false,
+            attached: true,
+            killed: false,
+            last_pc: None,
+        }
+    }
+
+    /// Returns true if GDB requested to kill the execution.
+    pub fn is_killed(&self) -> bool {
+        self.killed
+    }
+
+    /// Called for each executed statement, before it is executed.
+    /// Stops the execution at the start of a row if requested.
+    pub fn add_statement(&mut self, proc: &TraceBuilder) {
+        let pc = proc.get_pc().u();
+        if self.last_pc == Some(pc) {
+            return;
+        }
+        self.last_pc = Some(pc);
+        if self.stepping || self.breakpoints.contains(&pc) {
+            self.stop(SIGTRAP, proc);
+        }
+    }
+
+    /// Stops the execution and lets GDB inspect the state, until it resumes the execution.
+    pub fn stop(&mut self, signal: u8, proc: &TraceBuilder) {
+        if !self.attached {
+            return;
+        }
+        if self.resumed {
+            self.resumed = false;
+            self.send_packet(&format!("S{signal:02x}"));
+        }
+        while !self.resumed && self.attached {
+            let Some(packet) = self.read_packet() else {
+                log::info!("GDB disconnected.");
+                self.attached = false;
+                break;
+            };
+            if let Some(reply) = self.handle_packet(&packet, signal, proc) {
+                self.send_packet(&reply);
+            }
+        }
+    }
+
+    /// Reports the end of the execution to GDB.
+    pub fn finish(mut self) {
+        if self.attached && self.resumed {
+            self.send_packet("W00");
+        }
+    }
+
+    /// Handles a packet and returns the reply, if any.
+    /// Commands that resume the execution are answered when it stops again.
+    fn handle_packet(&mut self, packet: &str, signal: u8, proc: &TraceBuilder) -> Option<String> {
+        log::debug!("GDB packet: {packet}");
+        let (command, args) = packet.split_at(packet.chars().next().map_or(0, char::len_utf8));
+        let reply = match command {
+            "?" => format!("S{signal:02x}"),
+            "g" => (0..=NUM_REGISTERS)
+                .map(|i| encode_word(register(proc, i)))
+                .collect(),
+            "p" => match usize::from_str_radix(args, 16) {
+                Ok(i) if i <= NUM_REGISTERS => encode_word(register(proc, i)),
+                _ => "E01".to_string(),
+            },
+            "m" => match parse_address_and_length(args) {
+                // Every byte takes two hex digits in the reply.
+                Some((address, length)) if length <= PACKET_SIZE / 2 => {
+                    let end = address.wrapping_add(length);
+                    (address..end)
+                        .map(|address| {
+                            let word = proc.peek_mem(address & !3);
+                            format!("{:02x}", (word >> (8 * (address & 3))) & 0xff)
+                        })
+                        .collect()
+                }
+                _ => "E01".to_string(),
+            },
+            "Z" | "z" => match args.split(',').collect::<Vec<_>>()[..] {
+                // Software and hardware breakpoints are the same for us.
+                ["0" | "1", address, _] => match u32::from_str_radix(address, 16) {
+                    Ok(address) if command == "Z" && address >= self.num_pcs => {
+                        log::info!("Rejected breakpoint at unknown PC 0x{address:x}.");
+                        "E01".to_string()
+                    }
+                    Ok(address) => {
+                        if command == "Z" {
+                            self.breakpoints.insert(address);
+                        } else {
+                            self.breakpoints.remove(&address);
+                        }
+                        "OK".to_string()
+                    }
+                    Err(_) => "E01".to_string(),
+                },
+                _ => String::new(),
+            },
+            "c" | "s" => {
+                self.stepping = command == "s";
+                self.resumed = true;
+                return None;
+            }
+            "D" => {
+                log::info!("GDB detached.");
+                self.attached = false;
+                "OK".to_string()
+            }
+            "k" => {
+                log::info!("Execution killed by GDB.");
+                self.attached = false;
+                self.killed = true;
+                return None;
+            }
+            "H" | "T" => "OK".to_string(),
+            "q" => self.handle_query(args),
+            _ => String::new(),
+        };
+        Some(reply)
+    }
+
+    fn handle_query(&mut self, query: &str) -> String {
+        let (name, args) = query.split_once([':', ',']).unwrap_or((query, ""));
+        match name {
+            "Supported" => format!("PacketSize={PACKET_SIZE:x}"),
+            "Attached" => "1".to_string(),
+            "C" => "QC1".to_string(),
+            "fThreadInfo" => "m1".to_string(),
+            "sThreadInfo" => "l".to_string(),
+            "Rcmd" => {
+                let command = decode_hex(args).unwrap_or_default();
+                match self.monitor_command(&command) {
+                    Ok(output) => {
+                        self.send_packet(&format!("O{}", encode_hex(output.as_bytes())));
+                        "OK".to_string()
+                    }
+                    Err(message) => {
+                        self.send_packet(&format!("O{}", encode_hex(message.as_bytes())));
+                        "E01".to_string()
+                    }
+                }
+            }
+            _ => String::new(),
+        }
+    }
+
+    /// Handles the `monitor` commands, which are not part of the protocol.
+    fn monitor_command(&mut self, command: &str) -> Result<String, String> {
+        match command.split_whitespace().collect::<Vec<_>>()[..] {
+            ["break", label] => {
+                let pc = self
+                    .label_map
+                    .get(label)
+                    .ok_or_else(|| format!("Unknown label: {label}\n"))?
+                    .u();
+                self.breakpoints.insert(pc);
+                Ok(format!("Breakpoint at {label} (pc = 0x{pc:x})\n"))
+            }
+            _ => Err(format!(
+                "Unknown command: {command}\nSupported commands: break <label>\n"
+            )),
+        }
+    }
+
+    /// Reads the next packet, skipping acknowledgements and interrupts.
+    /// Returns None if the connection was closed.
+    fn read_packet(&mut self) -> Option<String> {
+        let mut byte = [0u8];
+        loop {
+            self.stream.read_exact(&mut byte).ok()?;
+            if byte[0] == b'$' {
+                break;
+            }
+        }
+        let mut data = vec![];
+        loop {
+            self.stream.read_exact(&mut byte).ok()?;
+            if byte[0] == b'#' {
+                break;
+            }
+            data.push(byte[0]);
+        }
+        let mut checksum = [0u8; 2];
+        self.stream.read_exact(&mut checksum).ok()?;
+        let valid = std::str::from_utf8(&checksum)
+            .ok()
+            .and_then(|checksum| u8::from_str_radix(checksum, 16).ok())
+            == Some(compute_checksum(&data));
+        self.write_flushed(if valid { b"+" } else { b"-" }).ok()?;
+        if valid {
+            Some(String::from_utf8_lossy(&data).into_owned())
+        } else {
+            self.read_packet()
+        }
+    }
+
+    fn send_packet(&mut self, data: &str) {
+        let packet = format!("${data}#{:02x}", compute_checksum(data.as_bytes()));
+        if self.write_flushed(packet.as_bytes()).is_err() {
+            log::info!("GDB disconnected.");
+            self.attached = false;
+        }
+    }
+
+    fn write_flushed(&mut self, data: &[u8]) -> io::Result<()> {
+        // Writes bypass the read buffer, packets are written in one go anyway.
+        let mut stream = self.stream.get_ref();
+        stream.write_all(data)?;
+        stream.flush()
+    }
+}
+
+/// Returns the value of the register with the given GDB number: x0 to x31, followed by the PC.
+fn register(proc: &TraceBuilder, i: usize) -> u32 {
+    if i == NUM_REGISTERS {
+        proc.get_pc().u()
+    } else {
+        proc.get_reg(&format!("x{i}")).0 as u32
+    }
+}
+
+/// Encodes a word in target (little-endian) byte order.
+fn encode_word(value: u32) -> String {
+    encode_hex(&value.to_le_bytes())
+}
+
+fn encode_hex(data: &[u8]) -> String {
+    data.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn decode_hex(data: &str) -> Option<String> {
+    let bytes = data
+        .as_bytes()
+        .chunks(2)
+        .map(|c| u8::from_str_radix(std::str::from_utf8(c).ok()?, 16).ok())
+        .collect::<Option<Vec<_>>>()?;
+    String::from_utf8(bytes).ok()
+}
+
+fn parse_address_and_length(args: &str) -> Option<(u32, u32)> {
+    let (address, length) = args.split(',').collect_tuple()?;
+    Some((
+        u32::from_str_radix(address, 16).ok()?,
+        u32::from_str_radix(length, 16).ok()?,
+    ))
+}
+
+fn compute_checksum(data: &[u8]) -> u8 {
+    data.iter().fold(0, |sum, b| sum.wrapping_add(*b))
+}
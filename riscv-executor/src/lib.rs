@@ -6,12 +6,13 @@
 //! much of witness generation using the present module, we lose the
 //! non-determinism verification.
 //!
-//! TODO: perform determinism verification for each instruction independently
-//! from execution.
+//! To catch differences between the two, `powdr_riscv::differential` runs a
+//! program through both and compares the register and memory state row by row.
 
-use std::{collections::HashMap, io};
+use std::{collections::HashMap, io, net::TcpStream};
 
 use builder::TraceBuilder;
+use gdb::GdbStub;
 use powdr_ast::{
     asm_analysis::{
         AnalysisASMFile, CallableSymbol, FunctionStatement, Item, LabelStatement, Machine,
@@ -19,8 +20,14 @@ use powdr_ast::{
     parsed::{asm::DebugDirective, Expression, FunctionCall},
 };
 use powdr_number::{BigInt, FieldElement, GoldilocksField};
+use profiler::{Profiler, ProfilerOptions};
 
+pub mod arith;
+mod gdb;
+pub mod keccakf;
 pub mod poseidon_gl;
+pub mod profiler;
+pub mod sha256;
 
 /// Initial value of the PC.
 ///
@@ -103,6 +110,8 @@ pub struct MemOperation {
     pub row: usize,
     pub kind: MemOperationKind,
     pub address: u32,
+    /// The value read or written.
+    pub value: u32,
 }
 
 pub struct RegWrite {
@@ -129,6 +138,9 @@ pub struct ExecutionTrace {
 
     /// The length of the trace, after applying the reg_writes.
     pub len: usize,
+
+    /// The words committed as public outputs, by index. Recorded in all modes.
+    pub public_outputs: HashMap<u32, u32>,
 }
 
 impl ExecutionTrace {
@@ -281,6 +293,7 @@ mod builder {
                     reg_writes,
                     mem_ops: Vec::new(),
                     len: PC_INITIAL_VAL + 1,
+                    public_outputs: HashMap::new(),
                 },
                 next_statement_line: 1,
                 batch_to_line_map,
@@ -377,22 +390,34 @@ mod builder {
                     row: self.trace.len,
                     kind: MemOperationKind::Write,
                     address: addr,
+                    value: val,
                 });
             }
 
             self.mem.insert(addr, val);
         }
 
+        /// get the memory word at `addr` without recording the access
+        pub(crate) fn peek_mem(&self, addr: u32) -> u32 {
+            *self.mem.get(&addr).unwrap_or(&0)
+        }
+
         pub(crate) fn get_mem(&mut self, addr: u32) -> u32 {
+            let value = self.peek_mem(addr);
             if let ExecMode::Trace = self.mode {
                 self.trace.mem_ops.push(MemOperation {
                     row: self.trace.len,
                     kind: MemOperationKind::Read,
                     address: addr,
+                    value,
                 });
             }
 
-            *self.mem.get(&addr).unwrap_or(&0)
+            value
+        }
+
+        pub(crate) fn set_public_output(&mut self, idx: u32, val: u32) {
+            self.trace.public_outputs.insert(idx, val);
         }
 
         pub fn finish(self) -> (ExecutionTrace, MemoryState) {
@@ -449,6 +474,8 @@ struct PreprocessedMain<'a, T: FieldElement> {
     label_map: HashMap<&'a str, Elem>,
     batch_to_line_map: Vec<u32>,
     debug_files: Vec<(&'a str, &'a str)>,
+    /// The labels in order, with the index of the statement they point to.
+    label_statements: Vec<(u32, &'a str)>,
 }
 
 /// Returns the list of instructions, directly indexable by PC, the map from
@@ -464,6 +491,7 @@ fn preprocess_main_function<T: FieldElement>(machine: &Machine<T>) -> Preprocess
     let mut label_map = HashMap::new();
     let mut batch_to_line_map = vec![0; PC_INITIAL_VAL];
     let mut debug_files = Vec::new();
+    let mut label_statements = Vec::new();
 
     for (batch_idx, batch) in orig_statements.iter_batches().enumerate() {
         batch_to_line_map.push(statements.len() as u32);
@@ -494,6 +522,7 @@ fn preprocess_main_function<T: FieldElement>(machine: &Machine<T>) -> Preprocess
                     // assert there are no statements in the middle of a block
                     assert!(!statement_seen);
                     label_map.insert(name.as_str(), ((batch_idx + PC_INITIAL_VAL) as i64).into());
+                    label_statements.push((statements.len() as u32, name.as_str()));
                 }
             }
         }
@@ -508,6 +537,7 @@ fn preprocess_main_function<T: FieldElement>(machine: &Machine<T>) -> Preprocess
         label_map,
         batch_to_line_map,
         debug_files,
+        label_statements,
     }
 }
 
@@ -558,6 +588,10 @@ impl<'a, 'b, F: FieldElement> Executor<'a, 'b, F> {
 
                 vec![]
             }
+            "commit_public" => {
+                self.proc.set_public_output(args[0].u(), args[1].u());
+                Vec::new()
+            }
             "load_label" => args,
             "jump" | "jump_dyn" => {
                 let next_pc = self.proc.get_pc().u() + 1;
@@ -688,6 +722,64 @@ impl<'a, 'b, F: FieldElement> Executor<'a, 'b, F> {
                 let result = poseidon_gl::poseidon_gl(&inputs);
                 result.into_iter().map(Elem::from_fe).collect()
             }
+            "keccakf" => {
+                // The state is passed in the registers K0..K49, with lane i
+                // split into the words K(2i) (low) and K(2i+1) (high).
+                let mut state: [u64; 25] = std::array::from_fn(|i| {
+                    let lo = self.proc.get_reg(&format!("K{}", 2 * i)).u() as u64;
+                    let hi = self.proc.get_reg(&format!("K{}", 2 * i + 1)).u() as u64;
+                    lo | (hi << 32)
+                });
+                keccakf::keccakf(&mut state);
+                for (i, lane) in state.into_iter().enumerate() {
+                    self.proc
+                        .set_reg(&format!("K{}", 2 * i), (lane & 0xffffffff) as u32);
+                    self.proc
+                        .set_reg(&format!("K{}", 2 * i + 1), (lane >> 32) as u32);
+                }
+
+                Vec::new()
+            }
+            "sha256_compress" => {
+                // The state is passed in the registers H0..H7
+                // and the block in the registers M0..M15.
+                let mut state: [u32; 8] =
+                    std::array::from_fn(|i| self.proc.get_reg(&format!("H{i}")).u());
+                let block: [u32; 16] =
+                    std::array::from_fn(|i| self.proc.get_reg(&format!("M{i}")).u());
+                sha256::sha256_compress(&mut state, &block);
+                for (i, word) in state.into_iter().enumerate() {
+                    self.proc.set_reg(&format!("H{i}"), word);
+                }
+
+                Vec::new()
+            }
+            "affine_256" => {
+                // The operands are passed in the registers L0..L23, 8 words each.
+                // The high and the low half of the result are returned in L0..L15.
+                let operand = |offset: usize| -> [u32; 8] {
+                    std::array::from_fn(|i| self.proc.get_reg(&format!("L{}", offset + i)).u())
+                };
+                let (hi, lo) = arith::affine_256(&operand(0), &operand(8), &operand(16));
+                for (i, word) in hi.into_iter().chain(lo).enumerate() {
+                    self.proc.set_reg(&format!("L{i}"), word);
+                }
+
+                Vec::new()
+            }
+            "mod_256" => {
+                // The dividend is passed in the registers L0..L15 (high half first),
+                // the modulus in L16..L23. The remainder is returned in L0..L7.
+                let operand = |offset: usize| -> [u32; 8] {
+                    std::array::from_fn(|i| self.proc.get_reg(&format!("L{}", offset + i)).u())
+                };
+                let remainder = arith::mod_256(&operand(0), &operand(8), &operand(16));
+                for (i, word) in remainder.into_iter().enumerate() {
+                    self.proc.set_reg(&format!("L{i}"), word);
+                }
+
+                Vec::new()
+            }
             instr => {
                 panic!("unknown instruction: {instr}");
             }
@@ -817,8 +909,28 @@ pub fn execute_ast<T: FieldElement>(
         label_map,
         batch_to_line_map,
         debug_files,
+        label_statements,
     } = preprocess_main_function(main_machine);
 
+    let mut profiler = match &mode {
+        ExecMode::Profile(options) => Some(Profiler::new(
+            options.clone(),
+            &debug_files,
+            &statements,
+            &label_statements,
+        )),
+        _ => None,
+    };
+    // The valid PCs are the indices of `batch_to_line_map`, except for the final entry.
+    let num_pcs = batch_to_line_map.len() as u32 - 1;
+    let (mode, mut gdb) = match mode {
+        ExecMode::Gdb(stream) => (
+            ExecMode::Fast,
+            Some(GdbStub::new(stream, label_map.clone(), num_pcs)),
+        ),
+        mode => (mode, None),
+    };
+
     let proc = match TraceBuilder::new(main_machine, &batch_to_line_map, max_steps_to_execute, mode)
     {
         Ok(proc) => proc,
@@ -839,6 +951,21 @@ pub fn execute_ast<T: FieldElement>(
 
         log::trace!("l {curr_pc}: {stm}",);
 
+        if let Some(profiler) = &mut profiler {
+            profiler.add_statement(e.proc.get_pc().u(), curr_pc, stm);
+        }
+
+        if let Some(gdb) = &mut gdb {
+            gdb.add_statement(&e.proc);
+            if matches!(stm, FunctionStatement::Instruction(i) if i.instruction == "fail") {
+                // Let the debugger inspect the state before the guest fails.
+                gdb.stop(gdb::SIGILL, &e.proc);
+            }
+            if gdb.is_killed() {
+                break;
+            }
+        }
+
         match stm {
             FunctionStatement::Assignment(a) => {
                 let results = e.eval_expression(a.rhs.as_ref());
@@ -874,12 +1001,83 @@ pub fn execute_ast<T: FieldElement>(
         };
     }
 
+    if let Some(profiler) = profiler {
+        profiler.finish();
+    }
+    if let Some(gdb) = gdb {
+        gdb.finish();
+    }
+
     e.proc.finish()
 }
 
+/// Returns the original RISC-V instruction and its source location for the
+/// statements at the given PC, as recorded by the debug directives.
+///
+/// The instruction is the last one up to the end of the batch, but after the
+/// last label. Statements generated without a corresponding RISC-V instruction
+/// return None for both.
+pub fn original_source<T: FieldElement>(
+    program: &AnalysisASMFile<T>,
+    pc: u32,
+) -> (Option<String>, Option<String>) {
+    let PreprocessedMain {
+        statements,
+        batch_to_line_map,
+        debug_files,
+        label_statements,
+        ..
+    } = preprocess_main_function(get_main_machine(program));
+    let start = batch_to_line_map[pc as usize] as usize;
+    let end = batch_to_line_map
+        .get(pc as usize + 1)
+        .map_or(statements.len(), |&line| line as usize);
+    let block_start = label_statements
+        .iter()
+        .map(|(line, _)| *line as usize)
+        .filter(|line| *line <= start)
+        .max()
+        .unwrap_or_default();
+
+    let debug_directives = |range: std::ops::Range<usize>| {
+        statements[range]
+            .iter()
+            .enumerate()
+            .rev()
+            .filter_map(|(i, statement)| match statement {
+                FunctionStatement::DebugDirective(d) => Some((i, &d.directive)),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    };
+    let Some((instruction_line, instruction)) = debug_directives(block_start..end)
+        .into_iter()
+        .find_map(|(i, directive)| match directive {
+            DebugDirective::OriginalInstruction(insn) => Some((block_start + i, insn.clone())),
+            _ => None,
+        })
+    else {
+        return (None, None);
+    };
+    let location = debug_directives(0..instruction_line)
+        .into_iter()
+        .find_map(|(_, directive)| match directive {
+            DebugDirective::Loc(file, line, column) => {
+                let (dir, file) = debug_files[file - 1];
+                Some(format!("{dir}/{file}:{line}:{column}"))
+            }
+            _ => None,
+        });
+    (Some(instruction), location)
+}
+
 pub enum ExecMode {
     Fast,
     Trace,
+    /// Like `Fast`, but also profiles the guest and writes the profiles as configured.
+    Profile(ProfilerOptions),
+    /// Like `Fast`, but lets GDB control the execution through the given connection.
+    Gdb(TcpStream),
 }
 
 /// Execute a Powdr/RISCV assembly source.
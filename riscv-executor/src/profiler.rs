@@ -0,0 +1,400 @@
+//! A profiler for guest programs that attributes the executed rows to source
+//! locations and function symbols.
+//!
+//! The function of each statement is the last (non-local) label before it, and
+//! its source location is taken from the last `.debug loc` directive in the
+//! same function. Calls and returns are tracked through writes to and jumps
+//! via the link register `x1`, as emitted by the RISC-V compiler.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use powdr_ast::{
+    asm_analysis::FunctionStatement,
+    parsed::{asm::DebugDirective, Expression, FunctionCall},
+};
+use powdr_number::FieldElement;
+
+/// Name used for statements before the first label and statements without location.
+const UNKNOWN: &str = "[unknown]";
+
+#[derive(Clone, Debug)]
+pub struct ProfilerOptions {
+    /// Directory to write the profiles to.
+    pub output_directory: PathBuf,
+    /// The profiles are written to `<file_stem>.folded` and `<file_stem>.callgrind`.
+    pub file_stem: String,
+    /// Write a folded-stack profile, which can be turned into a flamegraph
+    /// with `inferno-flamegraph` or `flamegraph.pl`.
+    pub flamegraph: bool,
+    /// Write a profile in the callgrind format, which can be inspected with
+    /// `callgrind_annotate` or KCachegrind.
+    pub callgrind: bool,
+}
+
+/// A source location, as an index into the debug files and a line number.
+type Location = Option<(usize, usize)>;
+
+/// A call that has not returned yet.
+struct Call {
+    caller: usize,
+    call_site: Location,
+    /// The function entered by the call, known after the first row of the callee.
+    callee: Option<usize>,
+    /// The number of rows executed before the callee started.
+    start_row: u64,
+}
+
+#[derive(Default)]
+struct CallCost {
+    count: u64,
+    inclusive_rows: u64,
+}
+
+pub struct Profiler {
+    options: ProfilerOptions,
+    /// The source files, as referenced by the locations.
+    files: Vec<String>,
+    /// Demangled name of each function.
+    functions: Vec<String>,
+    /// Function and location of each statement.
+    statement_info: Vec<(usize, Location)>,
+
+    /// The PC of the row of the last executed statement.
+    last_pc: Option<u32>,
+    /// Whether the row of the last executed statement was already counted.
+    row_counted: bool,
+    rows: u64,
+
+    /// The callers of the current function, followed by the current function.
+    stack: Vec<usize>,
+    calls: Vec<Call>,
+
+    self_rows: HashMap<(usize, Location), u64>,
+    stack_rows: HashMap<Vec<usize>, u64>,
+    call_costs: HashMap<(usize, Location, usize), CallCost>,
+}
+
+impl Profiler {
+    /// Creates a profiler for the given (preprocessed) statements of the main function.
+    /// `labels` contains each label with the index of the statement it points to, in order.
+    pub fn new<T: FieldElement>(
+        options: ProfilerOptions,
+        debug_files: &[(&str, &str)],
+        statements: &[&FunctionStatement<T>],
+        labels: &[(u32, &str)],
+    ) -> Self {
+        let mut functions = vec![UNKNOWN.to_string()];
+        let mut function_starts = labels
+            .iter()
+            .filter_map(|(statement, label)| {
+                let name = unescape_label(label);
+                (!is_local_label(&name)).then_some((*statement as usize, name))
+            })
+            .peekable();
+
+        let mut function = 0;
+        let mut location = None;
+        let statement_info = statements
+            .iter()
+            .enumerate()
+            .map(|(i, statement)| {
+                while let Some((_, name)) = function_starts.next_if(|(start, _)| *start <= i) {
+                    functions.push(rustc_demangle::demangle(&name).to_string());
+                    function = functions.len() - 1;
+                    location = None;
+                }
+                if let FunctionStatement::DebugDirective(d) = statement {
+                    if let DebugDirective::Loc(file, line, _) = d.directive {
+                        location = Some((file - 1, line));
+                    }
+                }
+                (function, location)
+            })
+            .collect();
+
+        Self {
+            options,
+            files: debug_files
+                .iter()
+                .map(|(dir, file)| format!("{dir}/{file}"))
+                .collect(),
+            functions,
+            statement_info,
+            last_pc: None,
+            row_counted: false,
+            rows: 0,
+            stack: vec![0],
+            calls: vec![],
+            self_rows: HashMap::new(),
+            stack_rows: HashMap::new(),
+            call_costs: HashMap::new(),
+        }
+    }
+
+    /// Called for each executed statement, before it is executed.
+    /// `pc` is the PC of the current row.
+    pub fn add_statement<T>(&mut self, pc: u32, index: u32, statement: &FunctionStatement<T>) {
+        if self.last_pc != Some(pc) {
+            self.last_pc = Some(pc);
+            self.row_counted = false;
+        }
+        if matches!(statement, FunctionStatement::DebugDirective(_)) {
+            return;
+        }
+        let (function, location) = self.statement_info[index as usize];
+        if !self.row_counted {
+            self.row_counted = true;
+            self.add_row(function, location);
+        }
+
+        match JumpKind::of(statement) {
+            Some(JumpKind::Call) => {
+                self.calls.push(Call {
+                    caller: function,
+                    call_site: location,
+                    callee: None,
+                    start_row: self.rows,
+                });
+                // Overwritten by the callee in the next row.
+                self.stack.push(function);
+            }
+            Some(JumpKind::Return) => {
+                // Returns without a call (e.g. from the entry point) are ignored.
+                if let Some(call) = self.calls.pop() {
+                    self.finish_call(call);
+                    self.stack.pop();
+                }
+            }
+            None => {}
+        }
+    }
+
+    fn add_row(&mut self, function: usize, location: Location) {
+        self.rows += 1;
+        *self.self_rows.entry((function, location)).or_default() += 1;
+
+        *self.stack.last_mut().unwrap() = function;
+        match self.stack_rows.get_mut(&self.stack[..]) {
+            Some(rows) => *rows += 1,
+            None => {
+                self.stack_rows.insert(self.stack.clone(), 1);
+            }
+        }
+
+        if let Some(call) = self.calls.last_mut() {
+            call.callee.get_or_insert(function);
+        }
+    }
+
+    fn finish_call(&mut self, call: Call) {
+        // A call is not counted if the callee did not execute a single row.
+        if let Some(callee) = call.callee {
+            let cost = self
+                .call_costs
+                .entry((call.caller, call.call_site, callee))
+                .or_default();
+            cost.count += 1;
+            cost.inclusive_rows += self.rows - call.start_row;
+        }
+    }
+
+    /// Finishes the calls that are still running and writes the profiles.
+    pub fn finish(mut self) {
+        while let Some(call) = self.calls.pop() {
+            self.finish_call(call);
+        }
+
+        self.log_summary();
+
+        if self.options.flamegraph {
+            let path = self.output_path("folded");
+            self.write_file(&path, Self::write_folded);
+        }
+        if self.options.callgrind {
+            let path = self.output_path("callgrind");
+            self.write_file(&path, Self::write_callgrind);
+        }
+    }
+
+    fn output_path(&self, extension: &str) -> PathBuf {
+        self.options
+            .output_directory
+            .join(format!("{}.{extension}", self.options.file_stem))
+    }
+
+    fn write_file(&self, path: &Path, write: impl FnOnce(&Self, &mut dyn Write) -> io::Result<()>) {
+        let mut writer = BufWriter::new(File::create(path).unwrap());
+        write(self, &mut writer).unwrap();
+        writer.flush().unwrap();
+        log::info!("Wrote {}.", path.display());
+    }
+
+    fn log_summary(&self) {
+        let mut function_rows = vec![0; self.functions.len()];
+        for ((function, _), rows) in &self.self_rows {
+            function_rows[*function] += rows;
+        }
+        let mut function_rows = function_rows.into_iter().enumerate().collect::<Vec<_>>();
+        function_rows.sort_by(|(_, a), (_, b)| b.cmp(a));
+
+        log::info!("Profiled {} rows. Functions with the most rows:", self.rows);
+        for (function, rows) in function_rows.into_iter().take(10) {
+            if rows == 0 {
+                break;
+            }
+            log::info!(
+                "  {:>5.1}% {rows:>10} {}",
+                rows as f64 * 100.0 / self.rows as f64,
+                self.functions[function]
+            );
+        }
+    }
+
+    /// Writes one line per call stack with the number of rows executed in it.
+    fn write_folded(&self, writer: &mut dyn Write) -> io::Result<()> {
+        let stacks = self
+            .stack_rows
+            .iter()
+            .map(|(stack, rows)| {
+                let stack = stack
+                    .iter()
+                    .map(|function| self.functions[*function].replace(';', ":"))
+                    .collect::<Vec<_>>()
+                    .join(";");
+                (stack, rows)
+            })
+            .collect::<BTreeMap<_, _>>();
+        for (stack, rows) in stacks {
+            writeln!(writer, "{stack} {rows}")?;
+        }
+        Ok(())
+    }
+
+    fn write_callgrind(&self, writer: &mut dyn Write) -> io::Result<()> {
+        writeln!(writer, "# callgrind format")?;
+        writeln!(writer, "version: 1")?;
+        writeln!(writer, "creator: powdr-riscv-executor")?;
+        writeln!(writer, "positions: line")?;
+        writeln!(writer, "events: Rows")?;
+        writeln!(writer, "summary: {}", self.rows)?;
+
+        let mut self_rows: BTreeMap<usize, BTreeMap<Location, u64>> = BTreeMap::new();
+        for ((function, location), rows) in &self.self_rows {
+            *self_rows
+                .entry(*function)
+                .or_default()
+                .entry(*location)
+                .or_default() += rows;
+        }
+        let mut call_costs: BTreeMap<usize, BTreeMap<(Location, usize), &CallCost>> =
+            BTreeMap::new();
+        for ((caller, call_site, callee), cost) in &self.call_costs {
+            call_costs
+                .entry(*caller)
+                .or_default()
+                .insert((*call_site, *callee), cost);
+            self_rows.entry(*caller).or_default();
+        }
+
+        for (function, locations) in self_rows {
+            let file_of = |location: &Location| {
+                location.map_or(UNKNOWN, |(file, _)| self.files[file].as_str())
+            };
+            let line_of = |location: &Location| location.map_or(0, |(_, line)| line);
+
+            let first_location = locations
+                .keys()
+                .chain(
+                    call_costs
+                        .get(&function)
+                        .into_iter()
+                        .flatten()
+                        .map(|((l, _), _)| l),
+                )
+                .next()
+                .copied()
+                .flatten();
+            let mut current_file = file_of(&first_location);
+            writeln!(writer)?;
+            writeln!(writer, "fl={current_file}")?;
+            writeln!(writer, "fn={}", self.functions[function])?;
+
+            let mut switch_file = |writer: &mut dyn Write, location: &Location| {
+                let file = file_of(location);
+                if file != current_file {
+                    current_file = file;
+                    writeln!(writer, "fi={file}")?;
+                }
+                Ok::<_, io::Error>(())
+            };
+
+            for (location, rows) in &locations {
+                switch_file(writer, location)?;
+                writeln!(writer, "{} {rows}", line_of(location))?;
+            }
+            for ((call_site, callee), cost) in call_costs.remove(&function).into_iter().flatten() {
+                switch_file(writer, &call_site)?;
+                writeln!(writer, "cfn={}", self.functions[callee])?;
+                writeln!(writer, "calls={} 0", cost.count)?;
+                writeln!(writer, "{} {}", line_of(&call_site), cost.inclusive_rows)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+enum JumpKind {
+    Call,
+    Return,
+}
+
+impl JumpKind {
+    /// Calls write the return address to `x1`, returns jump to `x1`.
+    fn of<T>(statement: &FunctionStatement<T>) -> Option<Self> {
+        let FunctionStatement::Assignment(assignment) = statement else {
+            return None;
+        };
+        let Expression::FunctionCall(FunctionCall {
+            function,
+            arguments,
+        }) = assignment.rhs.as_ref()
+        else {
+            return None;
+        };
+        let Expression::Reference(instruction) = function.as_ref() else {
+            return None;
+        };
+        let instruction = instruction.try_to_identifier()?;
+        if instruction != "jump" && instruction != "jump_dyn" {
+            return None;
+        }
+
+        let jumps_to_x1 = matches!(
+            &arguments[..],
+            [Expression::Reference(r)] if r.try_to_identifier().is_some_and(|r| r == "x1")
+        );
+        if assignment.lhs_with_reg.iter().any(|(lhs, _)| lhs == "x1") {
+            Some(JumpKind::Call)
+        } else if instruction == "jump_dyn" && jumps_to_x1 {
+            Some(JumpKind::Return)
+        } else {
+            None
+        }
+    }
+}
+
+/// Reverts the escaping of labels done by the RISC-V compiler.
+fn unescape_label(label: &str) -> String {
+    label.replace("_slash_", "/").replace("_dot_", ".")
+}
+
+/// Local labels are emitted by LLVM with an `.L` prefix and might have been
+/// prefixed by the disambiguator.
+fn is_local_label(label: &str) -> bool {
+    label.starts_with(".L") || label.contains("__.L")
+}
@@ -6,8 +6,35 @@ extern "C" {
     // during the reachability analysis.
     fn poseidon_gl_coprocessor(data: *mut [u64; 12]);
 
+    // This will be replaced by a call to the Keccak-f coprocessor,
+    // which permutes the state in place.
+    fn keccakf_coprocessor(data: *mut [u64; 25]);
+
+    // This will be replaced by a call to the SHA-256 coprocessor,
+    // which compresses the block into the state in place.
+    fn sha256_compress_coprocessor(state: *mut [u32; 8], block: *const [u32; 16]);
+
+    // These will be replaced by calls to the 256-bit arithmetic coprocessor.
+    // All numbers are [u32; 8] in little-endian order.
+    fn affine_256_coprocessor(
+        a: *const [u32; 8],
+        b: *const [u32; 8],
+        c: *const [u32; 8],
+        hi: *mut [u32; 8],
+        lo: *mut [u32; 8],
+    );
+    fn mod_256_coprocessor(
+        hi: *const [u32; 8],
+        lo: *const [u32; 8],
+        m: *const [u32; 8],
+        r: *mut [u32; 8],
+    );
+
     // This will be replaced by a call to prover input.
     fn input_coprocessor(index: u32, channel: u32) -> u32;
+
+    // This will be replaced by a commitment to a public output word.
+    fn commit_public_coprocessor(index: u32, value: u32);
 }
 
 extern crate alloc;
@@ -26,6 +53,7 @@ pub fn get_data_len(channel: u32) -> usize {
 }
 
 use serde::de::DeserializeOwned;
+use serde::Serialize;
 
 pub fn get_data_serde<T: DeserializeOwned>(channel: u32) -> T {
     let l = get_data_len(channel);
@@ -38,6 +66,50 @@ pub fn get_data_serde<T: DeserializeOwned>(channel: u32) -> T {
     serde_cbor::from_slice(&data.as_slice()).unwrap()
 }
 
+/// The number of words that can be committed as public outputs.
+/// Must be kept in sync with the compiler.
+pub const NUM_PUBLIC_OUTPUTS: usize = 16;
+
+static mut PUBLIC_OUTPUTS_COMMITTED: usize = 0;
+
+/// Appends the given words to the public outputs of the program.
+/// The verifier sees them as the first public values of the proof.
+pub fn commit(data: &[u32]) {
+    unsafe {
+        assert!(
+            PUBLIC_OUTPUTS_COMMITTED + data.len() <= NUM_PUBLIC_OUTPUTS,
+            "Too many public outputs"
+        );
+        for &d in data {
+            commit_public_coprocessor(PUBLIC_OUTPUTS_COMMITTED as u32, d);
+            PUBLIC_OUTPUTS_COMMITTED += 1;
+        }
+    }
+}
+
+/// Serializes the value and commits to it as public outputs.
+/// The first committed word is the length of the serialized data in bytes,
+/// followed by the data itself, four little-endian bytes per word.
+pub fn commit_serde<T: Serialize>(value: &T) {
+    let data = serde_cbor::to_vec(value).unwrap();
+    commit(&[data.len() as u32]);
+    for chunk in data.chunks(4) {
+        let mut word = [0u8; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        commit(&[u32::from_le_bytes(word)]);
+    }
+}
+
+/// Commits zero to all public outputs that have not been committed yet,
+/// so that the verifier does not accept arbitrary values for them.
+pub(crate) fn finalize_public_outputs() {
+    unsafe {
+        while PUBLIC_OUTPUTS_COMMITTED < NUM_PUBLIC_OUTPUTS {
+            commit(&[0]);
+        }
+    }
+}
+
 const GOLDILOCKS: u64 = 0xffffffff00000001;
 
 /// Calls the low level Poseidon coprocessor in PIL, where
@@ -65,3 +137,97 @@ pub fn poseidon_gl_unsafe(mut data: [u64; 12]) -> [u64; 4] {
 
     [data[0], data[1], data[2], data[3]]
 }
+
+/// Applies the Keccak-f[1600] permutation to the state in place,
+/// using the Keccak-f coprocessor.
+pub fn keccakf(state: &mut [u64; 25]) {
+    unsafe {
+        keccakf_coprocessor(state as *mut [u64; 25]);
+    }
+}
+
+/// Applies the SHA-256 compression function to the state and the block,
+/// using the SHA-256 coprocessor.
+pub fn sha256_compress(state: &mut [u32; 8], block: &[u32; 16]) {
+    unsafe {
+        sha256_compress_coprocessor(state as *mut [u32; 8], block as *const [u32; 16]);
+    }
+}
+
+const SHA256_INITIAL_STATE: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// Computes the SHA-256 hash of the data, using the SHA-256 coprocessor
+/// for the compression function.
+pub fn sha256(data: &[u8]) -> [u8; 32] {
+    let mut state = SHA256_INITIAL_STATE;
+
+    // The padding is a single 1 bit, followed by zeros and the length of the data
+    // in bits as a big-endian u64, such that the total length is a multiple of 64 bytes.
+    let bit_len = (data.len() as u64) * 8;
+    let padded_len = (data.len() + 9).div_ceil(64) * 64;
+    let byte = |i: usize| {
+        if i < data.len() {
+            data[i]
+        } else if i == data.len() {
+            0x80
+        } else if i >= padded_len - 8 {
+            bit_len.to_be_bytes()[i - (padded_len - 8)]
+        } else {
+            0
+        }
+    };
+
+    for offset in (0..padded_len).step_by(64) {
+        let block: [u32; 16] = core::array::from_fn(|i| {
+            let i = offset + 4 * i;
+            u32::from_be_bytes([byte(i), byte(i + 1), byte(i + 2), byte(i + 3)])
+        });
+        sha256_compress(&mut state, &block);
+    }
+
+    let mut hash = [0u8; 32];
+    for (chunk, word) in hash.chunks_mut(4).zip(state) {
+        chunk.copy_from_slice(&word.to_be_bytes());
+    }
+    hash
+}
+
+/// Computes a * b + c, where all numbers are 256-bit words given as 32-bit limbs
+/// in little-endian order. Returns the high and the low 256 bits of the result.
+pub fn affine_256(a: &[u32; 8], b: &[u32; 8], c: &[u32; 8]) -> ([u32; 8], [u32; 8]) {
+    let mut hi = [0u32; 8];
+    let mut lo = [0u32; 8];
+    unsafe {
+        affine_256_coprocessor(a, b, c, &mut hi, &mut lo);
+    }
+    (hi, lo)
+}
+
+/// Returns true if a < b, where both numbers are given as 32-bit limbs in little-endian order.
+fn lt_256(a: &[u32; 8], b: &[u32; 8]) -> bool {
+    a.iter().rev().lt(b.iter().rev())
+}
+
+/// Computes (hi * 2**256 + lo) % m, where all numbers are 256-bit words given as
+/// 32-bit limbs in little-endian order. Requires hi < m, which in particular
+/// means that m is not zero.
+pub fn mod_256(hi: &[u32; 8], lo: &[u32; 8], m: &[u32; 8]) -> [u32; 8] {
+    assert!(lt_256(hi, m));
+    let mut r = [0u32; 8];
+    unsafe {
+        mod_256_coprocessor(hi, lo, m, &mut r);
+    }
+    // The coprocessor does not check that the remainder is reduced.
+    assert!(lt_256(&r, m));
+    r
+}
+
+/// Computes a * b % m, where all numbers are 256-bit words given as
+/// 32-bit limbs in little-endian order. Requires a < m, so that the high half
+/// of a * b is smaller than m.
+pub fn modmul_256(a: &[u32; 8], b: &[u32; 8], m: &[u32; 8]) -> [u32; 8] {
+    let (hi, lo) = affine_256(a, b, &[0; 8]);
+    mod_256(&hi, &lo, m)
+}
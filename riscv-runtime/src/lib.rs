@@ -49,4 +49,5 @@ pub unsafe extern "C" fn __runtime_start() {
     unsafe {
         main();
     }
+    coprocessors::finalize_public_outputs();
 }
@@ -26,7 +26,9 @@ fn build_instruction_tests() {
     let mut test_file = BufWriter::new(File::create(destination).unwrap());
 
     let generated_path = "./tests/instruction_tests/generated";
+    let references_path = "./tests/instruction_tests/references";
     println!("cargo:rerun-if-changed={generated_path}");
+    println!("cargo:rerun-if-changed={references_path}");
     for file in read_dir(generated_path).unwrap() {
         let file = file.unwrap();
         if let Some(file_name) = file
@@ -37,13 +39,24 @@ fn build_instruction_tests() {
             .strip_suffix(".S")
         {
             println!("cargo:rerun-if-changed={generated_path}/{file_name}.S");
+            let reference = Path::new(references_path)
+                .join(format!("{file_name}.reference_output"))
+                .canonicalize()
+                .map_or("None".to_string(), |reference| {
+                    format!("Some(include_str!(\"{}\"))", reference.display())
+                });
             write!(
                 test_file,
                 r#"
 #[test]
-#[ignore = "Too slow"]
 fn {file_name}() {{
-    run_instruction_test(include_str!("{test_file}"), "{file_name}");
+    run_instruction_test("{test_file}", "{file_name}", {reference}, false);
+}}
+
+#[test]
+#[ignore = "Too slow"]
+fn {file_name}_witgen() {{
+    run_instruction_test("{test_file}", "{file_name}", {reference}, true);
 }}
 "#,
                 test_file = file.path().canonicalize().unwrap().display(),
@@ -66,16 +66,16 @@ struct RiscvArchitecture {}
 
 impl Architecture for RiscvArchitecture {
     fn instruction_ends_control_flow(instr: &str) -> bool {
-        match instr {
+        match without_ordering_suffix(instr) {
             "li" | "lui" | "la" | "mv" | "add" | "addi" | "sub" | "neg" | "mul" | "mulh"
-            | "mulhu" | "mulhsu" | "divu" | "remu" | "xor" | "xori" | "and" | "andi" | "or"
-            | "ori" | "not" | "slli" | "sll" | "srli" | "srl" | "srai" | "seqz" | "snez"
-            | "slt" | "slti" | "sltu" | "sltiu" | "sgtz" | "beq" | "beqz" | "bgeu" | "bltu"
-            | "blt" | "bge" | "bltz" | "blez" | "bgtz" | "bgez" | "bne" | "bnez" | "jal"
-            | "jalr" | "call" | "ecall" | "ebreak" | "lw" | "lb" | "lbu" | "lh" | "lhu" | "sw"
-            | "sh" | "sb" | "nop" | "fence" | "fence.i" | "amoadd.w" | "amoadd.w.aq"
-            | "amoadd.w.rl" | "amoadd.w.aqrl" | "lr.w" | "lr.w.aq" | "lr.w.rl" | "lr.w.aqrl"
-            | "sc.w" | "sc.w.aq" | "sc.w.rl" | "sc.w.aqrl" => false,
+            | "mulhu" | "mulhsu" | "div" | "divu" | "rem" | "remu" | "xor" | "xori" | "and"
+            | "andi" | "or" | "ori" | "not" | "slli" | "sll" | "srli" | "srl" | "srai" | "sra"
+            | "seqz" | "snez" | "slt" | "slti" | "sltu" | "sltiu" | "sgtz" | "beq" | "beqz"
+            | "bgeu" | "bltu" | "blt" | "bge" | "bltz" | "blez" | "bgtz" | "bgez" | "bne"
+            | "bnez" | "jal" | "jalr" | "call" | "ecall" | "ebreak" | "lw" | "lb" | "lbu"
+            | "lh" | "lhu" | "sw" | "sh" | "sb" | "nop" | "fence" | "fence.i" | "amoswap.w"
+            | "amoadd.w" | "amoxor.w" | "amoand.w" | "amoor.w" | "amomin.w" | "amomax.w"
+            | "amominu.w" | "amomaxu.w" | "lr.w" | "sc.w" => false,
             "j" | "jr" | "tail" | "ret" | "unimp" => true,
             _ => {
                 panic!("Unknown instruction: {instr}");
@@ -107,8 +107,6 @@ pub fn compile(
     coprocessors: &CoProcessors,
     with_bootloader: bool,
 ) -> String {
-    // stack grows towards zero
-    let stack_start = 0x10000;
     // data grows away from zero
     let data_start = 0x10100;
 
@@ -145,35 +143,74 @@ pub fn compile(
     statements = replace_coprocessor_stubs(statements, coprocessors).collect::<Vec<_>>();
 
     let (data_code, data_positions) =
-        store_data_objects(data_sections, data_start, &mut |addr, value| match value {
-            SingleDataValue::Value(v) => {
-                vec![format!("mstore 0x{addr:x}, 0x{v:x};")]
-            }
-            SingleDataValue::LabelReference(sym) => {
-                // TODO should be possible without temporary
-                vec![
-                    format!("tmp1 <== load_label({});", escape_label(sym)),
-                    format!("mstore 0x{addr:x}, tmp1;"),
-                ]
-            }
-            SingleDataValue::Offset(_, _) => {
-                unimplemented!();
-                /*
-                object_code.push(format!("addr <=X= 0x{pos:x};"));
-
-                I think this solution should be fine but hard to say without
-                an actual code snippet that uses it.
-
-                // TODO should be possible without temporary
-                object_code.extend([
-                    format!("tmp1 <== load_label({});", escape_label(a)),
-                    format!("tmp2 <== load_label({});", escape_label(b)),
-                    // TODO check if registers match
-                    "mstore wrap(tmp1 - tmp2);".to_string(),
-                ]);
-                */
-            }
-        });
+        store_data_objects(data_sections, data_start, &mut store_data_value);
+
+    translate_program(
+        file_ids,
+        substitute_symbols_with_values(statements, &data_positions),
+        data_code,
+        "__runtime_start",
+        coprocessors,
+        with_bootloader,
+    )
+}
+
+/// Generates the code that stores a single data word at address `addr`
+/// during the data initialization routine.
+pub(crate) fn store_data_value(addr: u32, value: SingleDataValue) -> Vec<String> {
+    match value {
+        SingleDataValue::Value(v) => {
+            vec![format!("mstore 0x{addr:x}, 0x{v:x};")]
+        }
+        SingleDataValue::LabelReference(sym) => {
+            // TODO should be possible without temporary
+            vec![
+                format!("tmp1 <== load_label({});", escape_label(sym)),
+                format!("mstore 0x{addr:x}, tmp1;"),
+            ]
+        }
+        SingleDataValue::Offset(_, _) => {
+            unimplemented!();
+            /*
+            object_code.push(format!("addr <=X= 0x{pos:x};"));
+
+            I think this solution should be fine but hard to say without
+            an actual code snippet that uses it.
+
+            // TODO should be possible without temporary
+            object_code.extend([
+                format!("tmp1 <== load_label({});", escape_label(a)),
+                format!("tmp2 <== load_label({});", escape_label(b)),
+                // TODO check if registers match
+                "mstore wrap(tmp1 - tmp2);".to_string(),
+            ]);
+            */
+        }
+    }
+}
+
+/// Generates the powdr assembly program from the resolved RISC-V statements
+/// and the data initialization code. Execution starts at `entry_point`.
+pub(crate) fn translate_program(
+    file_ids: Vec<(i64, String, String)>,
+    statements: Vec<Statement>,
+    data_code: Vec<String>,
+    entry_point: &str,
+    coprocessors: &CoProcessors,
+    with_bootloader: bool,
+) -> String {
+    // The bootloader only saves and restores the registers in `REGISTER_NAMES`,
+    // so a chunk boundary in the middle of a Keccak-f, SHA-256 or 256-bit arithmetic call
+    // would lose the state.
+    for name in ["keccakf", "sha256", "arith"] {
+        assert!(
+            !(with_bootloader && coprocessors.has(name)),
+            "The {name} coprocessor is not supported with continuations yet."
+        );
+    }
+
+    // stack grows towards zero
+    let stack_start = 0x10000;
 
     let submachine_init = call_every_submachine(coprocessors);
     let bootloader_and_shutdown_routine_lines = if with_bootloader {
@@ -187,6 +224,14 @@ pub fn compile(
         submachine_init
     };
 
+    // With the bootloader, the program terminates at the well-known `PROGRAM_END` PC, so that the
+    // final PC of the last chunk shows that the execution has terminated.
+    let program_end = if with_bootloader {
+        "branch_if_zero 0, program_end;"
+    } else {
+        "return;" // This is not "riscv ret", but "return from powdr asm function".
+    };
+
     let program: Vec<String> = file_ids
         .into_iter()
         .map(|(id, dir, file)| format!(".debug file {id} {} {};", quote(&dir), quote(&file)))
@@ -194,11 +239,11 @@ pub fn compile(
         .chain(["x1 <== jump(__data_init);".to_string()])
         .chain([
             format!("// Set stack pointer\nx2 <=X= {stack_start};"),
-            "x1 <== jump(__runtime_start);".to_string(),
-            "return;".to_string(), // This is not "riscv ret", but "return from powdr asm function".
+            format!("x1 <== jump({});", escape_label(entry_point)),
+            program_end.to_string(),
         ])
         .chain(
-            substitute_symbols_with_values(statements, &data_positions)
+            statements
                 .into_iter()
                 .flat_map(|v| process_statement(v, coprocessors)),
         )
@@ -946,8 +991,17 @@ fn try_coprocessor_substitution(label: &str, coprocessors: &CoProcessors) -> Opt
         .map(|(_, subst)| subst.to_string())
 }
 
+/// Removes the memory ordering suffix (`.aq`, `.rl` or `.aqrl`) from atomic
+/// instructions. There is only a single hart, so the ordering is irrelevant.
+fn without_ordering_suffix(instr: &str) -> &str {
+    [".aqrl", ".aq", ".rl"]
+        .iter()
+        .find_map(|suffix| instr.strip_suffix(suffix))
+        .unwrap_or(instr)
+}
+
 fn process_instruction(instr: &str, args: &[Argument], coprocessors: &CoProcessors) -> Vec<String> {
-    match instr {
+    match without_ordering_suffix(instr) {
         // load/store registers
         "li" | "la" => {
             // The difference between "li" and "la" in RISC-V is that the former
@@ -1045,10 +1099,65 @@ fn process_instruction(instr: &str, args: &[Argument], coprocessors: &CoProcesso
                 rd,
             )
         }
+        "div" => {
+            let (rd, r1, r2) = rrr(args);
+            only_if_no_write_to_zero_vec(
+                vec![
+                    format!("tmp1 <== to_signed({r1});"),
+                    format!("tmp2 <== to_signed({r2});"),
+                    // tmp3 is 1 if tmp1 is non-negative
+                    "tmp3 <== is_positive(tmp1 + 1);".into(),
+                    // tmp4 is 1 if tmp2 is non-negative
+                    "tmp4 <== is_positive(tmp2 + 1);".into(),
+                    // If tmp1 is negative, convert to positive
+                    "skip_if_zero 0, tmp3;".into(),
+                    "tmp1 <=X= 0 - tmp1;".into(),
+                    // If tmp2 is negative, convert to positive
+                    "skip_if_zero 0, tmp4;".into(),
+                    "tmp2 <=X= 0 - tmp2;".into(),
+                    // Divide the absolute values. Dividing by zero results in 0xffffffff,
+                    // and INT_MIN / -1 results in 2**31, which is INT_MIN as required.
+                    format!("{rd}, tmp1 <== divremu(tmp1, tmp2);"),
+                    // tmp3 is 1 if the signs of the operands differ
+                    "tmp3 <== is_not_equal_zero(tmp3 - tmp4);".into(),
+                    // If the signs differ and the divisor is not zero, negate the quotient
+                    "skip_if_zero tmp3, 2;".into(),
+                    "skip_if_zero tmp2, 1;".into(),
+                    format!("{rd} <== wrap_signed(0 - {rd});"),
+                ],
+                rd,
+            )
+        }
         "divu" => {
             let (rd, r1, r2) = rrr(args);
             only_if_no_write_to_zero(format!("{rd}, tmp1 <== divremu({r1}, {r2});"), rd)
         }
+        "rem" => {
+            let (rd, r1, r2) = rrr(args);
+            only_if_no_write_to_zero_vec(
+                vec![
+                    format!("tmp1 <== to_signed({r1});"),
+                    format!("tmp2 <== to_signed({r2});"),
+                    // tmp3 is 1 if tmp1 is non-negative
+                    "tmp3 <== is_positive(tmp1 + 1);".into(),
+                    // tmp4 is 1 if tmp2 is non-negative
+                    "tmp4 <== is_positive(tmp2 + 1);".into(),
+                    // If tmp1 is negative, convert to positive
+                    "skip_if_zero 0, tmp3;".into(),
+                    "tmp1 <=X= 0 - tmp1;".into(),
+                    // If tmp2 is negative, convert to positive
+                    "skip_if_zero 0, tmp4;".into(),
+                    "tmp2 <=X= 0 - tmp2;".into(),
+                    // Divide the absolute values. Dividing by zero results in the dividend
+                    // as remainder, and INT_MIN % -1 results in 0.
+                    format!("tmp1, {rd} <== divremu(tmp1, tmp2);"),
+                    // The remainder has the sign of the dividend, so negate it if the dividend is negative
+                    "skip_if_zero 0, tmp3;".into(),
+                    format!("{rd} <== wrap_signed(0 - {rd});"),
+                ],
+                rd,
+            )
+        }
         "remu" => {
             let (rd, r1, r2) = rrr(args);
             only_if_no_write_to_zero(format!("tmp1, {rd} <== divremu({r1}, {r2});"), rd)
@@ -1148,6 +1257,22 @@ fn process_instruction(instr: &str, args: &[Argument], coprocessors: &CoProcesso
                 rd,
             )
         }
+        "sra" => {
+            // arithmetic shift right, using the same equivalence as srai
+            let (rd, r1, r2) = rrr(args);
+            only_if_no_write_to_zero_vec(
+                vec![
+                    format!("tmp2 <== and({r2}, 0x1f);"),
+                    format!("tmp1 <== to_signed({r1});"),
+                    format!("tmp1 <== is_positive(0 - tmp1);"),
+                    format!("tmp1 <=X= tmp1 * 0xffffffff;"),
+                    format!("{rd} <== xor(tmp1, {r1});"),
+                    format!("{rd} <== shr({rd}, tmp2);"),
+                    format!("{rd} <== xor(tmp1, {rd});"),
+                ],
+                rd,
+            )
+        }
 
         // comparison
         "seqz" => {
@@ -1306,9 +1431,25 @@ fn process_instruction(instr: &str, args: &[Argument], coprocessors: &CoProcesso
             }
         }
         "jalr" => {
-            // TODO there is also a form that takes more arguments
-            let rs = r(args);
-            vec![format!("x1 <== jump_dyn({rs});")]
+            if let [_] = args {
+                let rs = r(args);
+                vec![format!("x1 <== jump_dyn({rs});")]
+            } else {
+                // Code addresses are positions in the powdr program, so the
+                // offset is added to the position stored in the register.
+                let (rd, rs, off) = rro(args);
+                let target = match off as i32 {
+                    0 => rs.to_string(),
+                    off if off < 0 => format!("{rs} - {}", -off),
+                    off => format!("{rs} + {off}"),
+                };
+                let statement = if rd.is_zero() {
+                    format!("tmp1 <== jump_dyn({target});")
+                } else {
+                    format!("{rd} <== jump_dyn({target});")
+                };
+                vec![statement]
+            }
         }
         "call" | "tail" => {
             // Depending on what symbol is called, the call is replaced by a
@@ -1450,22 +1591,67 @@ fn process_instruction(instr: &str, args: &[Argument], coprocessors: &CoProcesso
         "unimp" => vec!["fail;".to_string()],
 
         // atomic instructions
-        insn if insn.starts_with("amoadd.w") => {
+        // Since there is only a single hart, these are just read-modify-write
+        // sequences of regular memory accesses.
+        "amoswap.w" | "amoadd.w" | "amoxor.w" | "amoand.w" | "amoor.w" => {
             let (rd, rs2, rs1, off) = rrro(args);
             assert_eq!(off, 0);
 
+            let new_value = match instr.split('.').next().unwrap() {
+                "amoswap" => format!("tmp2 <=X= {rs2};"),
+                "amoadd" => format!("tmp2 <== wrap(tmp1 + {rs2});"),
+                "amoxor" => format!("tmp2 <== xor(tmp1, {rs2});"),
+                "amoand" => format!("tmp2 <== and(tmp1, {rs2});"),
+                "amoor" => format!("tmp2 <== or(tmp1, {rs2});"),
+                _ => unreachable!(),
+            };
             [
                 vec![
                     format!("tmp1, tmp2 <== mload({rs1});"),
-                    format!("tmp2 <== wrap(tmp1 + {rs2});"),
+                    new_value,
                     format!("mstore {rs1}, tmp2;"),
                 ],
                 only_if_no_write_to_zero(format!("{rd} <=X= tmp1;"), rd),
             ]
             .concat()
         }
+        "amomin.w" | "amomax.w" | "amominu.w" | "amomaxu.w" => {
+            let (rd, rs2, rs1, off) = rrro(args);
+            assert_eq!(off, 0);
+
+            let op = instr.split('.').next().unwrap();
+            // tmp3 is 1 if rs2 is greater than the value in memory.
+            let compare = if op.ends_with('u') {
+                vec![format!("tmp3 <=Y= is_positive({rs2} - tmp1);")]
+            } else {
+                vec![
+                    "tmp2 <== to_signed(tmp1);".to_string(),
+                    format!("tmp3 <== to_signed({rs2});"),
+                    "tmp3 <=Y= is_positive(tmp3 - tmp2);".to_string(),
+                ]
+            };
+            // tmp4 is initialized with the result for tmp3 = 0 and
+            // overwritten if tmp3 = 1.
+            let (if_not_greater, if_greater) = if op.starts_with("amomin") {
+                (rs2.to_string(), "tmp1".to_string())
+            } else {
+                ("tmp1".to_string(), rs2.to_string())
+            };
+            [
+                vec![format!("tmp1, tmp2 <== mload({rs1});")],
+                compare,
+                vec![
+                    format!("tmp4 <=X= {if_not_greater};"),
+                    "skip_if_zero tmp3, 1;".to_string(),
+                    format!("tmp4 <=X= {if_greater};"),
+                    format!("mstore {rs1}, tmp4;"),
+                ],
+                only_if_no_write_to_zero(format!("{rd} <=X= tmp1;"), rd),
+            ]
+            .concat()
+        }
 
-        insn if insn.starts_with("lr.w") => {
+        "lr.w" => {
             // Very similar to "lw":
             let (rd, rs, off) = rro(args);
             assert_eq!(off, 0);
@@ -1476,7 +1662,7 @@ fn process_instruction(instr: &str, args: &[Argument], coprocessors: &CoProcesso
             statments
         }
 
-        insn if insn.starts_with("sc.w") => {
+        "sc.w" => {
             // Some overlap with "sw", but also writes 0 to rd on success
             let (rd, rs2, rs1, off) = rrro(args);
             assert_eq!(off, 0);
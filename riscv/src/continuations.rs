@@ -1,10 +1,19 @@
-use std::collections::{BTreeSet, HashMap};
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+};
 
 use powdr_ast::{
+    analyzed::Analyzed,
     asm_analysis::{AnalysisASMFile, RegisterTy},
     parsed::asm::parse_absolute_path,
 };
-use powdr_number::FieldElement;
+use powdr_backend::{BackendType, Proof};
+use powdr_number::{DegreeType, FieldElement};
 use powdr_pipeline::{Pipeline, Stage};
 use powdr_riscv_executor::ExecutionTrace;
 
@@ -16,8 +25,8 @@ use memory_merkle_tree::MerkleTree;
 
 use crate::continuations::bootloader::{
     default_register_values, shutdown_routine_upper_bound, BOOTLOADER_INPUTS_PER_PAGE,
-    BOOTLOADER_SPECIFIC_INSTRUCTION_NAMES, DEFAULT_PC, MEMORY_HASH_START_INDEX, PAGE_INPUTS_OFFSET,
-    WORDS_PER_PAGE,
+    BOOTLOADER_SPECIFIC_INSTRUCTION_NAMES, DEFAULT_PC, MEMORY_HASH_START_INDEX, NUM_PAGES_INDEX,
+    PAGE_INPUTS_OFFSET, PROGRAM_END, WORDS_PER_PAGE,
 };
 
 fn transposed_trace<F: FieldElement>(trace: &ExecutionTrace) -> HashMap<String, Vec<F>> {
@@ -54,29 +63,19 @@ fn render_hash<F: FieldElement>(hash: &[F]) -> String {
 ///   but with the `PilWithEvaluatedFixedCols` stage already advanced to and all chunk-specific parameters set.
 /// - `bootloader_inputs`: The inputs to the bootloader and the index of the row at which the shutdown routine
 ///   is supposed to execute, for each chunk, as returned by `rust_continuations_dry_run`.
+///
+/// Fails without running any chunk if the chunks don't link up, see `check_chunk_chain`.
 pub fn rust_continuations<F: FieldElement, PipelineCallback, E>(
-    mut pipeline: Pipeline<F>,
+    pipeline: Pipeline<F>,
     pipeline_callback: PipelineCallback,
     bootloader_inputs: Vec<(Vec<F>, u64)>,
 ) -> Result<(), E>
 where
     PipelineCallback: Fn(Pipeline<F>) -> Result<(), E>,
+    E: From<Vec<String>>,
 {
     let num_chunks = bootloader_inputs.len();
-
-    // Advance the pipeline to the PilWithEvaluatedFixedCols stage and then clone it
-    // for each chunk. This is more efficient, because we'll run all steps until then
-    // only once.
-    log::info!("Advancing pipeline to PilWithEvaluatedFixedCols stage...");
-    pipeline
-        .advance_to(Stage::PilWithEvaluatedFixedCols)
-        .unwrap();
-
-    let length = pipeline
-        .pil_with_evaluated_fixed_cols_ref()
-        .unwrap()
-        .pil
-        .degree();
+    let (pipeline, length) = prepare_chunks(pipeline, &bootloader_inputs)?;
 
     bootloader_inputs
         .into_iter()
@@ -84,30 +83,222 @@ where
         .map(
             |(i, (bootloader_inputs, start_of_shutdown_routine))| -> Result<(), E> {
                 log::info!("\nRunning chunk {} / {}...", i + 1, num_chunks);
-                let pipeline = pipeline.clone();
-                let name = format!("{}_chunk_{}", pipeline.name(), i);
-                let pipeline = pipeline.with_name(name);
-                // The `jump_to_shutdown_routine` column indicates when the execution should jump to the shutdown routine.
-                // In that row, the normal PC update is ignored and the PC is set to the address of the shutdown routine.
-                // In other words, it should be a one-hot encoding of `start_of_shutdown_routine`.
-                let jump_to_shutdown_routine = (0..length)
-                    .map(|i| (i == start_of_shutdown_routine - 1).into())
-                    .collect();
-                let pipeline = pipeline.add_external_witness_values(vec![
-                    ("main.bootloader_input_value".to_string(), bootloader_inputs),
-                    (
-                        "main.jump_to_shutdown_routine".to_string(),
-                        jump_to_shutdown_routine,
-                    ),
-                ]);
-                pipeline_callback(pipeline)?;
-                Ok(())
+                pipeline_callback(chunk_pipeline(
+                    &pipeline,
+                    length,
+                    i,
+                    bootloader_inputs,
+                    start_of_shutdown_routine,
+                ))
             },
         )
         .collect::<Result<Vec<_>, E>>()?;
     Ok(())
 }
 
+/// Like `rust_continuations`, but calls `pipeline_callback` for up to `num_workers` chunks in parallel.
+/// Each worker only works on one chunk at a time, so at most `num_workers` chunks are in memory at once.
+///
+/// Returns the results of `pipeline_callback` (e.g. the proofs) in chunk order, or the error of the
+/// first chunk that failed. After a chunk failed, no new chunks are started.
+pub fn rust_continuations_parallel<F: FieldElement, PipelineCallback, R, E>(
+    pipeline: Pipeline<F>,
+    pipeline_callback: PipelineCallback,
+    bootloader_inputs: Vec<(Vec<F>, u64)>,
+    num_workers: usize,
+) -> Result<Vec<R>, E>
+where
+    PipelineCallback: Fn(Pipeline<F>) -> Result<R, E> + Sync,
+    R: Send,
+    E: Send + From<Vec<String>>,
+{
+    if num_workers == 0 {
+        return Err(vec!["Need at least one worker.".to_string()].into());
+    }
+    let num_chunks = bootloader_inputs.len();
+    let (pipeline, length) = prepare_chunks(pipeline, &bootloader_inputs)?;
+
+    let next_chunk = AtomicUsize::new(0);
+    let failed = AtomicBool::new(false);
+    let results = (0..num_chunks)
+        .map(|_| Mutex::new(None))
+        .collect::<Vec<_>>();
+
+    thread::scope(|scope| {
+        for _ in 0..num_workers.min(num_chunks) {
+            scope.spawn(|| {
+                while !failed.load(Ordering::Relaxed) {
+                    let i = next_chunk.fetch_add(1, Ordering::Relaxed);
+                    let Some((bootloader_inputs, start_of_shutdown_routine)) =
+                        bootloader_inputs.get(i)
+                    else {
+                        break;
+                    };
+                    log::info!("\nRunning chunk {} / {}...", i + 1, num_chunks);
+                    let result = pipeline_callback(chunk_pipeline(
+                        &pipeline,
+                        length,
+                        i,
+                        bootloader_inputs.clone(),
+                        *start_of_shutdown_routine,
+                    ));
+                    if result.is_err() {
+                        failed.store(true, Ordering::Relaxed);
+                    }
+                    *results[i].lock().unwrap() = Some(result);
+                }
+            });
+        }
+    });
+
+    // Chunks are started in order, so the chunks that have not run (because another one failed)
+    // all come after the ones that did.
+    results
+        .into_iter()
+        .map_while(|result| result.into_inner().unwrap())
+        .collect()
+}
+
+/// Checks that the chunks link up and advances the pipeline to the `PilWithEvaluatedFixedCols` stage,
+/// so that the steps until then only run once for all chunks.
+/// Returns the advanced pipeline and the degree.
+fn prepare_chunks<F: FieldElement>(
+    mut pipeline: Pipeline<F>,
+    bootloader_inputs: &[(Vec<F>, u64)],
+) -> Result<(Pipeline<F>, DegreeType), Vec<String>> {
+    // The public values of each chunk are the first bootloader inputs, so we can check
+    // that the chunks link up before proving them.
+    let chunk_publics = bootloader_inputs
+        .iter()
+        .map(|(inputs, _)| inputs[..NUM_PAGES_INDEX].to_vec())
+        .collect::<Vec<_>>();
+    let final_memory_hash = check_chunk_chain(&chunk_publics).map_err(|e| vec![e])?;
+    log::info!(
+        "Final memory root hash: {}",
+        render_hash(&final_memory_hash)
+    );
+
+    log::info!("Advancing pipeline to PilWithEvaluatedFixedCols stage...");
+    pipeline.advance_to(Stage::PilWithEvaluatedFixedCols)?;
+
+    let length = pipeline.pil_with_evaluated_fixed_cols_ref()?.pil.degree();
+
+    Ok((pipeline, length))
+}
+
+/// Sets the chunk-specific parameters on a clone of the prepared pipeline.
+fn chunk_pipeline<F: FieldElement>(
+    pipeline: &Pipeline<F>,
+    length: DegreeType,
+    chunk_index: usize,
+    bootloader_inputs: Vec<F>,
+    start_of_shutdown_routine: u64,
+) -> Pipeline<F> {
+    let pipeline = pipeline.clone();
+    let name = format!("{}_chunk_{}", pipeline.name(), chunk_index);
+    let pipeline = pipeline.with_name(name);
+    // The `pc_jump_flag` column indicates when the execution should jump to the shutdown routine.
+    // In that row, the normal PC update is ignored and the PC is set to the address of the shutdown routine.
+    // In other words, it should be a one-hot encoding of `start_of_shutdown_routine - 1`.
+    let pc_jump_flag = (0..length)
+        .map(|i| (i == start_of_shutdown_routine - 1).into())
+        .collect();
+    pipeline.add_external_witness_values(vec![
+        ("main.bootloader_input_value".to_string(), bootloader_inputs),
+        ("main.pc_jump_flag".to_string(), pc_jump_flag),
+    ])
+}
+
+/// Verifies the proof of each chunk against its public values with the given backend and
+/// verification key, and checks that the chunks link up to a single, terminated execution,
+/// see `check_chunk_chain`.
+///
+/// `pil` and `fixed` are the PIL and the fixed columns, which are the same for all chunks.
+/// `chunks` contains the proof and all public values of each chunk, in declaration order.
+///
+/// Returns the memory root hash after the last chunk.
+pub fn verify_chunk_chain<F: FieldElement>(
+    pil: &Analyzed<F>,
+    fixed: &[(String, Vec<F>)],
+    backend_type: BackendType,
+    verification_key: &[u8],
+    chunks: &[(Proof, Vec<F>)],
+) -> Result<Vec<F>, String> {
+    let backend = backend_type
+        .factory::<F>()
+        .create(pil, fixed, None, None, Some(&mut &verification_key[..]))
+        .map_err(|e| format!("Could not create the verifier: {e}"))?;
+    for (i, (proof, publics)) in chunks.iter().enumerate() {
+        backend
+            .verify(proof, &[publics.clone()])
+            .map_err(|e| format!("The proof of chunk {i} is invalid: {e}"))?;
+    }
+
+    let chunk_publics = chunks
+        .iter()
+        .map(|(_, publics)| publics.clone())
+        .collect::<Vec<_>>();
+    check_chunk_chain(&chunk_publics)
+}
+
+/// Checks that the public values of consecutive chunks link up to a single, terminated
+/// execution, i.e.:
+/// - The first chunk starts with the default register values and an all-zero memory.
+/// - Every other chunk starts with the final register values and memory root hash of the
+///   previous chunk.
+/// - The last chunk ends at `PROGRAM_END`, i.e. the program has terminated.
+///
+/// `chunk_publics` contains the public values of each chunk, in declaration order. The
+/// bootloader declares its public values first, so other public values (like the outputs of
+/// the program) may follow them. Note that this only checks the public values, see
+/// `verify_chunk_chain` to also verify the proofs.
+///
+/// Returns the memory root hash after the last chunk.
+pub fn check_chunk_chain<F: FieldElement>(chunk_publics: &[Vec<F>]) -> Result<Vec<F>, String> {
+    if chunk_publics.is_empty() {
+        return Err("No chunks to verify.".to_string());
+    }
+
+    let mut registers = default_register_values::<F>();
+    let mut memory_hash = MerkleTree::<F>::empty_hash().to_vec();
+    for (i, publics) in chunk_publics.iter().enumerate() {
+        if publics.len() < NUM_PAGES_INDEX {
+            return Err(format!(
+                "Chunk {i} has {} public values, expected at least {NUM_PAGES_INDEX}.",
+                publics.len()
+            ));
+        }
+        let (initial_registers, publics) =
+            publics[..NUM_PAGES_INDEX].split_at(REGISTER_NAMES.len());
+        let (final_registers, publics) = publics.split_at(REGISTER_NAMES.len());
+        let (initial_memory_hash, final_memory_hash) = publics.split_at(4);
+
+        if initial_registers != registers {
+            return Err(format!(
+                "Chunk {i} does not start with the expected register values."
+            ));
+        }
+        if initial_memory_hash != memory_hash {
+            return Err(format!(
+                "Chunk {i} starts with memory root hash {}, expected {}.",
+                render_hash(initial_memory_hash),
+                render_hash(&memory_hash)
+            ));
+        }
+
+        registers = final_registers.to_vec();
+        memory_hash = final_memory_hash.to_vec();
+    }
+
+    if registers[PC_INDEX] != F::from(PROGRAM_END) {
+        return Err(format!(
+            "The last chunk ends at PC {}, so the execution has not terminated.",
+            registers[PC_INDEX]
+        ));
+    }
+    Ok(memory_hash)
+}
+
 fn sanity_check<T>(program: &AnalysisASMFile<T>) {
     let main_machine = program.items[&parse_absolute_path("::Main")]
         .try_to_machine()
@@ -144,7 +335,7 @@ fn sanity_check<T>(program: &AnalysisASMFile<T>) {
 
 /// Runs the entire execution using the RISC-V executor. For each chunk, it collects:
 /// - The inputs to the bootloader, needed to restore the correct state.
-/// - The number of rows after which the prover should jump to the shutdown routine.
+/// - The index of the row at which the shutdown routine should start.
 pub fn rust_continuations_dry_run<F: FieldElement>(
     pipeline: &mut Pipeline<F>,
 ) -> Vec<(Vec<F>, u64)> {
@@ -339,8 +530,10 @@ pub fn rust_continuations_dry_run<F: FieldElement>(
             )
         );
 
-        let actual_num_rows = chunk_trace["main.pc"].len();
-        bootloader_inputs_and_num_rows.push((bootloader_inputs.clone(), actual_num_rows as u64));
+        // The final state is the state in the last row, so the shutdown routine has to start there.
+        let start_of_shutdown_routine = chunk_trace["main.pc"].len() - 1;
+        bootloader_inputs_and_num_rows
+            .push((bootloader_inputs.clone(), start_of_shutdown_routine as u64));
 
         log::info!("Chunk trace length: {}", chunk_trace["main.pc"].len());
         log::info!("Validating chunk...");
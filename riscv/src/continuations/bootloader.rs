@@ -64,15 +64,36 @@ pub fn bootloader_preamble() -> String {
         pc' = tmp_bootloader_value
     }
 
+"#.to_string();
+
+    preamble.push_str(&format!(
+        r#"
     // ============== Shutdown routine constraints =======================
-    // Insert a `jump_to_shutdown_routine` witness column, which will let the prover indicate that
-    // the normal PC update rule should be bypassed and instead set to the start of the shutdown routine.
-    // Nothing of this is enforced yet, and the flag will be ignored.
-    let jump_to_shutdown_routine;
-    jump_to_shutdown_routine * (1 - jump_to_shutdown_routine) = 0;
+    // The `pc_jump_flag` witness column lets the prover indicate that the normal PC update rule
+    // should be bypassed and the PC set to the start of the shutdown routine instead.
+    let pc_jump_flag;
+    pc_jump_flag * (1 - pc_jump_flag) = 0;
+    let pc_jump_target = {SHUTDOWN_START};
+    override_pc pc_jump_flag => pc_jump_target;
+
+    // The jump is only allowed after the bootloader has finished, i.e., after
+    // `jump_to_bootloader_input` has been executed.
+    let bootloader_done;
+    bootloader_done' = (1 - first_step') * (bootloader_done + instr_jump_to_bootloader_input);
+    pc_jump_flag * (1 - bootloader_done) = 0;
+
+    // The shutdown routine can't assert the final PC, because it has already changed.
+    // Instead, we assert that the PC the execution would have continued with is as claimed.
+    pc_jump_flag {{ {}, pc_update }} in {{ BOOTLOADER_INPUT_ADDRESS, bootloader_input_value }};
+
+    // The execution has to end in the shutdown sink, which is only reachable via the shutdown routine.
+    col fixed is_last_row = [0]* + [1];
+    is_last_row * (pc - {SHUTDOWN_SINK}) = 0;
 
     // Expose initial register values as public outputs
-"#.to_string();
+"#,
+        REGISTER_NAMES.len() + PC_INDEX
+    ));
 
     for (i, reg) in REGISTER_NAMES.iter().enumerate() {
         let reg = reg.strip_prefix("main.").unwrap();
@@ -118,8 +139,8 @@ pub fn bootloader_preamble() -> String {
 /// The bootloader: An assembly program that can be executed at the beginning a RISC-V execution.
 /// It lets the prover provide arbitrary memory pages and writes them to memory, as well as values for
 /// the registers (including the PC, which is set last).
-/// This can be used to implement continuations. Note that this is completely non-sound. Progress to
-/// make it sound is tracked in https://github.com/powdr-labs/powdr/issues/814.
+/// This can be used to implement continuations: The claimed initial and final state of each chunk is
+/// exposed as public values, and the shutdown routine validates that the final state is as claimed.
 /// Bootloader inputs are in the format:
 /// - First 49 values: Values of x1-x31, tmp1-tmp4, lr_sc_reservation, P0-P11, and the PC
 /// - Second 49 values: The same values, but after this chunk's execution
@@ -146,12 +167,17 @@ tmp1 <== jump(submachine_init);
 tmp1 <== jump(computation_start);
 
 // Similarly, this instruction has a known fixed PC ({SHUTDOWN_START}) and just jumps
-// to the shutdown routine.
-tmp1 <== jump(shutdown_start);
+// to the shutdown routine. Unlike `jump`, it does not write to any register, which
+// would change the final register values before they are asserted.
+branch_if_zero 0, shutdown_start;
 
 shutdown_sink:
 tmp1 <== jump(shutdown_sink);
 
+// The program jumps here when it terminates, so this has a known fixed PC ({PROGRAM_END}).
+program_end:
+return;
+
 // Submachine initialization: Calls each submachine once, because that helps witness
 // generation figure out default values that can be used if the machine is never used.
 submachine_init:
@@ -371,7 +397,7 @@ jump_to_bootloader_input {PC_INDEX};
         r#"
 // START OF SHUTDOWN ROUTINE
 //
-// This code is currently never executed in practice!
+// The prover jumps here by setting `pc_jump_flag`, once the chunk's computation is done.
 //
 // The shutdown routine is responsible for:
 // - Validating that the final register values are equal to those in the bootloader inputs
@@ -389,8 +415,8 @@ shutdown_start:
 
 // Assert final register values are as claimed
 // Note that we cannot assert that the final PC is correct, because it will already
-// have changed at this point. This will need to be done by whatever mechanism is used
-// to jump to the shutdown routine.
+// have changed at this point. This is done by a constraint on the row in which
+// `pc_jump_flag` is set.
 "#,
     );
 
@@ -557,6 +583,13 @@ pub const DEFAULT_PC: u64 = 3;
 /// Analogous to the `DEFAULT_PC`, this well-known PC jumps to the shutdown routine.
 pub const SHUTDOWN_START: u64 = 4;
 
+/// The PC of the infinite loop that the shutdown routine ends in.
+pub const SHUTDOWN_SINK: u64 = 5;
+
+/// The PC of the `return` that the program jumps to when it terminates.
+/// A chunk that ends at this PC ends the whole execution.
+pub const PROGRAM_END: u64 = 6;
+
 pub fn default_register_values<T: FieldElement>() -> Vec<T> {
     let mut register_values = vec![T::zero(); REGISTER_NAMES.len()];
     register_values[PC_INDEX] = T::from(DEFAULT_PC);
@@ -581,8 +614,8 @@ pub fn default_input<T: FieldElement>(accessed_pages: &[u64]) -> Vec<T> {
     } else {
         // TODO: We don't have a way to know the memory state *after* the execution.
         // For now, we'll just claim that the memory doesn't change.
-        // This is fine for now, because the bootloader does not yet enforce that the memory
-        // state is actually as claimed. In the future, the `accessed_pages` argument won't be
+        // This is fine for now, because these inputs are only used to run the executor,
+        // which does not check the claims. In the future, the `accessed_pages` argument won't be
         // supported anymore (it's anyway only used by the benchmark).
         let merkle_tree = MerkleTree::<T>::new();
         bootloader_inputs.extend(merkle_tree.root_hash());
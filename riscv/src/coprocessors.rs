@@ -5,13 +5,17 @@ use std::{
 
 type RuntimeFunctionImpl = (&'static str, fn() -> String);
 
+/// The number of words a program can commit to as public outputs.
+/// Must be kept in sync with the runtime.
+pub const NUM_PUBLIC_OUTPUTS: usize = 16;
+
 #[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq)]
 struct CoProcessor {
     name: &'static str,
     ty: &'static str,
     import: &'static str,
     instructions: &'static str,
-    runtime_function_impl: Option<RuntimeFunctionImpl>,
+    runtime_function_impls: &'static [RuntimeFunctionImpl],
 }
 
 static BINARY_COPROCESSOR: CoProcessor = CoProcessor {
@@ -25,7 +29,7 @@ static BINARY_COPROCESSOR: CoProcessor = CoProcessor {
     instr xor Y, Z -> X = binary.xor;
 
             "#,
-    runtime_function_impl: None,
+    runtime_function_impls: &[],
 };
 
 static SHIFT_COPROCESSOR: CoProcessor = CoProcessor {
@@ -38,7 +42,7 @@ static SHIFT_COPROCESSOR: CoProcessor = CoProcessor {
     instr shr Y, Z -> X = shift.shr;
 
             "#,
-    runtime_function_impl: None,
+    runtime_function_impls: &[],
 };
 
 static SPLIT_GL_COPROCESSOR: CoProcessor = CoProcessor {
@@ -50,7 +54,7 @@ static SPLIT_GL_COPROCESSOR: CoProcessor = CoProcessor {
 instr split_gl Z -> X, Y = split_gl.split;
 
     "#,
-    runtime_function_impl: None,
+    runtime_function_impls: &[],
 };
 
 static POSEIDON_GL_COPROCESSOR: CoProcessor = CoProcessor {
@@ -62,7 +66,47 @@ static POSEIDON_GL_COPROCESSOR: CoProcessor = CoProcessor {
 instr poseidon_gl A0, A1, A2, A3, A4, A5, A6, A7, A8, A9, A10, A11 -> X, Y, Z, W = poseidon_gl.poseidon_permutation;
 
 "#,
-    runtime_function_impl: Some(("poseidon_gl_coprocessor", poseidon_gl_call)),
+    runtime_function_impls: &[("poseidon_gl_coprocessor", poseidon_gl_call)],
+};
+
+static KECCAKF_COPROCESSOR: CoProcessor = CoProcessor {
+    name: "keccakf",
+    ty: "KeccakF",
+    import: "use std::hash::keccakf::KeccakF;",
+    instructions: r#"
+// ================== keccak instructions ==============
+instr keccakf = keccakf.keccakf K0, K1, K2, K3, K4, K5, K6, K7, K8, K9, K10, K11, K12, K13, K14, K15, K16, K17, K18, K19, K20, K21, K22, K23, K24, K25, K26, K27, K28, K29, K30, K31, K32, K33, K34, K35, K36, K37, K38, K39, K40, K41, K42, K43, K44, K45, K46, K47, K48, K49 -> K0, K1, K2, K3, K4, K5, K6, K7, K8, K9, K10, K11, K12, K13, K14, K15, K16, K17, K18, K19, K20, K21, K22, K23, K24, K25, K26, K27, K28, K29, K30, K31, K32, K33, K34, K35, K36, K37, K38, K39, K40, K41, K42, K43, K44, K45, K46, K47, K48, K49;
+
+"#,
+    runtime_function_impls: &[("keccakf_coprocessor", keccakf_call)],
+};
+
+static SHA256_COPROCESSOR: CoProcessor = CoProcessor {
+    name: "sha256",
+    ty: "Sha256",
+    import: "use std::hash::sha256::Sha256;",
+    instructions: r#"
+// ================== sha256 instructions ==============
+instr sha256_compress = sha256.compress H0, H1, H2, H3, H4, H5, H6, H7, M0, M1, M2, M3, M4, M5, M6, M7, M8, M9, M10, M11, M12, M13, M14, M15 -> H0, H1, H2, H3, H4, H5, H6, H7;
+
+"#,
+    runtime_function_impls: &[("sha256_compress_coprocessor", sha256_compress_call)],
+};
+
+static ARITH_COPROCESSOR: CoProcessor = CoProcessor {
+    name: "arith",
+    ty: "Arith",
+    import: "use std::arith::Arith;",
+    instructions: r#"
+// ================== 256-bit arithmetic instructions ==============
+instr affine_256 = arith.affine_256 L0, L1, L2, L3, L4, L5, L6, L7, L8, L9, L10, L11, L12, L13, L14, L15, L16, L17, L18, L19, L20, L21, L22, L23 -> L0, L1, L2, L3, L4, L5, L6, L7, L8, L9, L10, L11, L12, L13, L14, L15;
+instr mod_256 = arith.mod_256 L0, L1, L2, L3, L4, L5, L6, L7, L8, L9, L10, L11, L12, L13, L14, L15, L16, L17, L18, L19, L20, L21, L22, L23 -> L0, L1, L2, L3, L4, L5, L6, L7;
+
+"#,
+    runtime_function_impls: &[
+        ("affine_256_coprocessor", affine_256_call),
+        ("mod_256_coprocessor", mod_256_call),
+    ],
 };
 
 static INPUT_COPROCESSOR: CoProcessor = CoProcessor {
@@ -70,15 +114,35 @@ static INPUT_COPROCESSOR: CoProcessor = CoProcessor {
     ty: "",
     import: "",
     instructions: "",
-    runtime_function_impl: Some(("input_coprocessor", prover_input_call)),
+    runtime_function_impls: &[("input_coprocessor", prover_input_call)],
 };
 
-static ALL_COPROCESSORS: [(&str, &CoProcessor); 5] = [
+static PUBLIC_OUTPUTS_COPROCESSOR: CoProcessor = CoProcessor {
+    name: "public_outputs",
+    ty: "",
+    import: "",
+    instructions: r#"
+// ================== public outputs ==============
+// Write-once memory holding the words committed by the program,
+// exposed as public values below.
+let PUBLIC_OUTPUT_INDEX = |i| i;
+let public_output_value;
+instr commit_public X, Y { {X, Y} in {PUBLIC_OUTPUT_INDEX, public_output_value} }
+
+"#,
+    runtime_function_impls: &[("commit_public_coprocessor", commit_public_call)],
+};
+
+static ALL_COPROCESSORS: [(&str, &CoProcessor); 9] = [
     (BINARY_COPROCESSOR.name, &BINARY_COPROCESSOR),
     (SHIFT_COPROCESSOR.name, &SHIFT_COPROCESSOR),
     (SPLIT_GL_COPROCESSOR.name, &SPLIT_GL_COPROCESSOR),
     (POSEIDON_GL_COPROCESSOR.name, &POSEIDON_GL_COPROCESSOR),
+    (KECCAKF_COPROCESSOR.name, &KECCAKF_COPROCESSOR),
+    (SHA256_COPROCESSOR.name, &SHA256_COPROCESSOR),
+    (ARITH_COPROCESSOR.name, &ARITH_COPROCESSOR),
     (INPUT_COPROCESSOR.name, &INPUT_COPROCESSOR),
+    (PUBLIC_OUTPUTS_COPROCESSOR.name, &PUBLIC_OUTPUTS_COPROCESSOR),
 ];
 
 /// Defines which coprocessors should be used by the RISCV machine.
@@ -119,13 +183,15 @@ impl TryFrom<Vec<&str>> for CoProcessors {
 }
 
 impl CoProcessors {
-    /// The base version only adds the commonly used bitwise and shift operations.
+    /// The base version only adds the commonly used bitwise and shift operations,
+    /// prover inputs and public outputs.
     pub fn base() -> CoProcessors {
         Self {
             coprocessors: BTreeMap::from([
                 (BINARY_COPROCESSOR.name, &BINARY_COPROCESSOR),
                 (SHIFT_COPROCESSOR.name, &SHIFT_COPROCESSOR),
                 (INPUT_COPROCESSOR.name, &INPUT_COPROCESSOR),
+                (PUBLIC_OUTPUTS_COPROCESSOR.name, &PUBLIC_OUTPUTS_COPROCESSOR),
             ]),
         }
     }
@@ -139,6 +205,24 @@ impl CoProcessors {
         self
     }
 
+    pub fn with_keccakf(mut self) -> Self {
+        self.coprocessors
+            .insert(KECCAKF_COPROCESSOR.name, &KECCAKF_COPROCESSOR);
+        self
+    }
+
+    pub fn with_sha256(mut self) -> Self {
+        self.coprocessors
+            .insert(SHA256_COPROCESSOR.name, &SHA256_COPROCESSOR);
+        self
+    }
+
+    pub fn with_arith(mut self) -> Self {
+        self.coprocessors
+            .insert(ARITH_COPROCESSOR.name, &ARITH_COPROCESSOR);
+        self
+    }
+
     pub fn has(&self, key: &str) -> bool {
         self.coprocessors.contains_key(key)
     }
@@ -161,12 +245,28 @@ impl CoProcessors {
             .map(|c| c.instructions)
             .collect::<Vec<&str>>()
             .join("")
+            + &self.public_declarations()
+    }
+
+    /// Declares the committed words as public values, in the order
+    /// in which they are committed.
+    fn public_declarations(&self) -> String {
+        if !self
+            .coprocessors
+            .contains_key(PUBLIC_OUTPUTS_COPROCESSOR.name)
+        {
+            return String::new();
+        }
+
+        (0..NUM_PUBLIC_OUTPUTS)
+            .map(|i| format!("public output_{i} = public_output_value({i});\n"))
+            .collect()
     }
 
     pub fn runtime_names(&self) -> Vec<&str> {
         self.coprocessors
             .values()
-            .filter_map(|c| c.runtime_function_impl)
+            .flat_map(|c| c.runtime_function_impls)
             .map(|f| f.0)
             .collect()
     }
@@ -191,26 +291,43 @@ impl CoProcessors {
     pub fn substitutions(&self) -> Vec<(&'static str, String)> {
         self.coprocessors
             .values()
-            .filter_map(|c| c.runtime_function_impl)
+            .flat_map(|c| c.runtime_function_impls)
             .map(|f| (f.0, f.1()))
             .collect()
     }
 
     pub fn registers(&self) -> String {
+        let mut regs = vec![];
+
         // Poseidon has 12 inputs and 4 outputs.
         // The base RISCV machine has 4 assignment registers.
         // Therefore we need to add 12 assignment registers when using Poseidon.
         // Moreover, we also need 12 extra general purpose registers to store the
         // input values.
+        if self.coprocessors.contains_key(POSEIDON_GL_COPROCESSOR.name) {
+            regs.extend((0..12).map(|i| format!("reg A{}[<=];", i)));
+            regs.extend((0..12).map(|i| format!("reg P{};", i)));
+        }
 
-        if !self.coprocessors.contains_key(POSEIDON_GL_COPROCESSOR.name) {
-            return String::new();
+        // Keccak-f reads and writes its 50-word state directly from and to
+        // general purpose registers, so no assignment registers are needed.
+        if self.coprocessors.contains_key(KECCAKF_COPROCESSOR.name) {
+            regs.extend((0..50).map(|i| format!("reg K{};", i)));
+        }
+
+        // Likewise, SHA-256 reads the state and the block from general purpose
+        // registers and writes the new state back to the state registers.
+        if self.coprocessors.contains_key(SHA256_COPROCESSOR.name) {
+            regs.extend((0..8).map(|i| format!("reg H{};", i)));
+            regs.extend((0..16).map(|i| format!("reg M{};", i)));
         }
 
-        let a_regs: Vec<String> = (0..12).map(|i| format!("reg A{}[<=];", i)).collect();
-        let p_regs: Vec<String> = (0..12).map(|i| format!("reg P{};", i)).collect();
+        // The arith machine takes three 256-bit operands, i.e. 24 words.
+        if self.coprocessors.contains_key(ARITH_COPROCESSOR.name) {
+            regs.extend((0..24).map(|i| format!("reg L{};", i)));
+        }
 
-        [a_regs, p_regs].concat().join("\n")
+        regs.join("\n")
     }
 }
 
@@ -253,10 +370,76 @@ fn poseidon_gl_call() -> String {
         .collect()
 }
 
+fn keccakf_call() -> String {
+    // The x10 register holds the address of the state, a [u64; 25] which
+    // is laid out in memory as 50 little-endian 32-bit words.
+    let load = |i| format!("K{i}, tmp2 <== mload({} + x10);\n", i * 4);
+    let store = |i| format!("mstore {} + x10, K{i};\n", i * 4);
+
+    (0..50)
+        .map(load)
+        .chain(std::iter::once("keccakf;\n".to_string()))
+        .chain((0..50).map(store))
+        .collect()
+}
+
+fn sha256_compress_call() -> String {
+    // The x10 register holds the address of the state ([u32; 8]) and
+    // the x11 register holds the address of the block ([u32; 16]).
+    let load_state = |i| format!("H{i}, tmp2 <== mload({} + x10);\n", i * 4);
+    let load_block = |i| format!("M{i}, tmp2 <== mload({} + x11);\n", i * 4);
+    let store = |i| format!("mstore {} + x10, H{i};\n", i * 4);
+
+    (0..8)
+        .map(load_state)
+        .chain((0..16).map(load_block))
+        .chain(std::iter::once("sha256_compress;\n".to_string()))
+        .chain((0..8).map(store))
+        .collect()
+}
+
+fn affine_256_call() -> String {
+    // The x10, x11 and x12 registers hold the addresses of the operands a, b and c
+    // ([u32; 8] each, little-endian). The high and the low half of a * b + c
+    // are written to the addresses in x13 and x14, respectively.
+    let load = |i, reg| format!("L{i}, tmp2 <== mload({} + {reg});\n", (i % 8) * 4);
+    let store = |i, reg| format!("mstore {} + {reg}, L{i};\n", (i % 8) * 4);
+
+    (0..8)
+        .map(|i| load(i, "x10"))
+        .chain((8..16).map(|i| load(i, "x11")))
+        .chain((16..24).map(|i| load(i, "x12")))
+        .chain(std::iter::once("affine_256;\n".to_string()))
+        .chain((0..8).map(|i| store(i, "x13")))
+        .chain((8..16).map(|i| store(i, "x14")))
+        .collect()
+}
+
+fn mod_256_call() -> String {
+    // The x10 and x11 registers hold the addresses of the high and the low half
+    // of the dividend, x12 the address of the modulus. The remainder is written
+    // to the address in x13.
+    let load = |i, reg| format!("L{i}, tmp2 <== mload({} + {reg});\n", (i % 8) * 4);
+    let store = |i| format!("mstore {} + x13, L{i};\n", i * 4);
+
+    (0..8)
+        .map(|i| load(i, "x10"))
+        .chain((8..16).map(|i| load(i, "x11")))
+        .chain((16..24).map(|i| load(i, "x12")))
+        .chain(std::iter::once("mod_256;\n".to_string()))
+        .chain((0..8).map(store))
+        .collect()
+}
+
 fn prover_input_call() -> String {
     "x10 <=X= ${ (\"data_identifier\", x11, x10) };".to_string()
 }
 
+fn commit_public_call() -> String {
+    // The runtime passes the index of the output word in x10 and its value in x11.
+    "commit_public x10, x11;".to_string()
+}
+
 // This could also potentially go in the impl of CoProcessors,
 // but I purposefully left it outside because it should be removed eventually.
 pub fn call_every_submachine(coprocessors: &CoProcessors) -> Vec<String> {
@@ -285,6 +468,16 @@ pub fn call_every_submachine(coprocessors: &CoProcessors) -> Vec<String> {
         ]);
     }
 
+    if coprocessors.has(KECCAKF_COPROCESSOR.name) {
+        calls.push("keccakf;".to_string());
+    }
+    if coprocessors.has(SHA256_COPROCESSOR.name) {
+        calls.push("sha256_compress;".to_string());
+    }
+    if coprocessors.has(ARITH_COPROCESSOR.name) {
+        calls.push("affine_256;".to_string());
+    }
+
     calls.extend(vec!["x10 <=X= 0;".to_string(), "x11 <=X= 0;".to_string()]);
 
     calls
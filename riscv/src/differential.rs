@@ -0,0 +1,290 @@
+//! Differential testing of the RISC-V executor against the witness generator.
+//!
+//! Both implement the semantics of the same machine: the executor directly, the
+//! witness generator by solving the constraints. This runs a program through both
+//! and compares the register and memory state row by row, so that the two cannot
+//! drift apart silently.
+
+use std::{collections::HashMap, fmt};
+
+use itertools::Itertools;
+use powdr_ast::asm_analysis::AnalysisASMFile;
+use powdr_executor::witgen::WitgenError;
+use powdr_number::FieldElement;
+use powdr_pipeline::{Pipeline, Stage};
+use powdr_riscv_executor::{ExecMode, ExecutionTrace, MemOperationKind};
+
+/// The first point where the executor and the witness generator disagree.
+#[derive(Debug)]
+pub struct Divergence {
+    /// The row of the instruction that led to the divergent state.
+    pub row: usize,
+    /// The PC of that row.
+    pub pc: u32,
+    /// The differing values, one per line.
+    pub differences: Vec<String>,
+    /// The original RISC-V instruction at the PC, if known.
+    pub instruction: Option<String>,
+    /// The source location of the instruction, if known.
+    pub location: Option<String>,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Executor and witness generation diverge at row {} (pc = {}):",
+            self.row, self.pc
+        )?;
+        if let Some(instruction) = &self.instruction {
+            writeln!(f, "  Instruction: {instruction}")?;
+        }
+        if let Some(location) = &self.location {
+            writeln!(f, "  Location: {location}")?;
+        }
+        for difference in &self.differences {
+            writeln!(f, "  {difference}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Divergence {
+    fn new<T: FieldElement>(
+        program: &AnalysisASMFile<T>,
+        row: usize,
+        pc: u32,
+        differences: Vec<String>,
+    ) -> Box<Self> {
+        let (instruction, location) = powdr_riscv_executor::original_source(program, pc);
+        Box::new(Self {
+            row,
+            pc,
+            differences,
+            instruction,
+            location,
+        })
+    }
+}
+
+/// A memory operation: whether it is a write, the address and the value.
+type MemoryAccess = (bool, u32, u64);
+
+/// Runs the program of the pipeline through the executor and advances the
+/// pipeline to the `GeneratedWitness` stage, then compares the register values
+/// in each row and the memory operations of each row.
+///
+/// Assumes that the program was compiled without a bootloader.
+/// If the witness generation fails, this is reported as a divergence at the
+/// failing row of the main machine, or at the last row of the execution if the
+/// failure is not in the main machine.
+pub fn compare_executor_with_witgen<F: FieldElement>(
+    pipeline: &mut Pipeline<F>,
+) -> Result<(), Box<Divergence>> {
+    let program = pipeline.analyzed_asm_ref().unwrap().clone();
+    let (trace, _) = powdr_riscv_executor::execute_ast(
+        &program,
+        pipeline.data_callback().unwrap(),
+        &[],
+        usize::MAX,
+        ExecMode::Trace,
+    );
+
+    if let Err(errors) = pipeline.advance_to(Stage::GeneratedWitness) {
+        let last_row = trace.len.saturating_sub(1);
+        let (row, error) = match pipeline.witgen_error() {
+            // Only the rows of the main machine correspond to rows of the execution.
+            Some(WitgenError::RowFailed(failure)) if failure.machine == "Main Machine" => {
+                (failure.row as usize, failure.to_string())
+            }
+            Some(error) => (last_row, error.to_string()),
+            None => (last_row, errors.join("\n")),
+        };
+        let differences = ["Witness generation failed:".to_string()]
+            .into_iter()
+            .chain(error.lines().map(|line| format!("  {line}")))
+            .collect();
+        return Err(Divergence::new(
+            &program,
+            row,
+            executor_pc(&trace, row),
+            differences,
+        ));
+    }
+    let generated_witness = pipeline.artifact().unwrap().to_generated_witness().unwrap();
+    compare_trace_with_witness(
+        &program,
+        &trace,
+        generated_witness.witness.as_ref().unwrap(),
+    )
+}
+
+/// Compares an execution trace of the program with a witness for it, see
+/// `compare_executor_with_witgen`.
+pub fn compare_trace_with_witness<F: FieldElement>(
+    program: &AnalysisASMFile<F>,
+    trace: &ExecutionTrace,
+    witness: &[(String, Vec<F>)],
+) -> Result<(), Box<Divergence>> {
+    let witness = witness
+        .iter()
+        .map(|(name, values)| (name.as_str(), values))
+        .collect::<HashMap<_, _>>();
+
+    let pc_column = witness["main.pc"];
+    let divergence = |row: usize, differences: Vec<String>| {
+        let pc = pc_column[row].to_degree() as u32;
+        Err(Divergence::new(program, row, pc, differences))
+    };
+
+    if trace.len > pc_column.len() {
+        return divergence(
+            pc_column.len() - 1,
+            vec![format!(
+                "The execution needs {} rows, but the witness only has {}.",
+                trace.len,
+                pc_column.len()
+            )],
+        );
+    }
+
+    let register_divergence = first_register_divergence(trace, &witness);
+    let memory_divergence = first_memory_divergence(trace, &witness);
+    match (register_divergence, memory_divergence) {
+        (None, None) => Ok(()),
+        (Some((row, differences)), None) | (None, Some((row, differences))) => {
+            divergence(row, differences)
+        }
+        (Some(registers), Some(memory)) => {
+            let (row, differences) = if registers.0 <= memory.0 {
+                registers
+            } else {
+                memory
+            };
+            divergence(row, differences)
+        }
+    }
+}
+
+/// Returns the PC of the given row of the execution, or of its last row if the
+/// execution is shorter.
+fn executor_pc(trace: &ExecutionTrace, row: usize) -> u32 {
+    let pc_index = trace.reg_map["pc"] as usize;
+    let mut rows = trace.replay();
+    let mut pc = 0;
+    let mut row_index = 0;
+    while let Some(values) = rows.next_row() {
+        pc = values[pc_index].0 as u32;
+        if row_index == row {
+            break;
+        }
+        row_index += 1;
+    }
+    pc
+}
+
+/// Returns the row of the instruction that wrote the first differing register
+/// values, together with these values.
+fn first_register_divergence<F: FieldElement>(
+    trace: &ExecutionTrace,
+    witness: &HashMap<&str, &Vec<F>>,
+) -> Option<(usize, Vec<String>)> {
+    // Assignment registers only exist in the witness.
+    let registers = trace
+        .reg_map
+        .iter()
+        .filter_map(|(name, &index)| {
+            let column = witness.get(format!("main.{name}").as_str())?;
+            Some((name, index as usize, *column))
+        })
+        .sorted()
+        .collect::<Vec<_>>();
+
+    let mut rows = trace.replay();
+    let mut row_index = 0;
+    while let Some(row) = rows.next_row() {
+        let differences = registers
+            .iter()
+            .filter_map(|(name, index, column)| {
+                let expected = F::from(row[*index].0);
+                (column[row_index] != expected)
+                    .then(|| format!("{name}: executor {expected}, witness {}", column[row_index]))
+            })
+            .collect::<Vec<_>>();
+        if !differences.is_empty() {
+            // The registers of a row are the result of the previous row.
+            return Some((row_index.saturating_sub(1), differences));
+        }
+        row_index += 1;
+    }
+    None
+}
+
+/// Returns the first row in which the memory operations differ, together with
+/// the differing operations.
+fn first_memory_divergence<F: FieldElement>(
+    trace: &ExecutionTrace,
+    witness: &HashMap<&str, &Vec<F>>,
+) -> Option<(usize, Vec<String>)> {
+    let mut executor_accesses: HashMap<usize, Vec<MemoryAccess>> = HashMap::new();
+    for op in &trace.mem_ops {
+        // Memory operations are recorded with the length of the trace at that point.
+        let access = (
+            matches!(op.kind, MemOperationKind::Write),
+            op.address,
+            op.value as u64,
+        );
+        executor_accesses
+            .entry(op.row - 1)
+            .or_default()
+            .push(access);
+    }
+
+    let column = |name: &str| witness.get(format!("main.{name}").as_str()).copied();
+    let (addresses, steps, values) = (column("m_addr")?, column("m_step")?, column("m_value")?);
+    let flags = ["m_is_write", "m_is_bootloader_write"]
+        .into_iter()
+        .filter_map(column)
+        .map(|column| (true, column))
+        .chain(column("m_is_read").map(|column| (false, column)))
+        .collect::<Vec<_>>();
+    let mut witness_accesses: HashMap<usize, Vec<MemoryAccess>> = HashMap::new();
+    for i in 0..addresses.len() {
+        for (is_write, flag) in &flags {
+            if flag[i].is_one() {
+                let access = (
+                    *is_write,
+                    addresses[i].to_degree() as u32,
+                    values[i].to_degree(),
+                );
+                let step = steps[i].to_degree() as usize;
+                witness_accesses.entry(step).or_default().push(access);
+            }
+        }
+    }
+
+    let render = |accesses: &[MemoryAccess]| {
+        accesses
+            .iter()
+            .map(|(is_write, address, value)| {
+                let kind = if *is_write { "write" } else { "read" };
+                format!("{kind} 0x{address:08x} = 0x{value:08x}")
+            })
+            .join(", ")
+    };
+    (0..trace.len).find_map(|row| {
+        let mut executor = executor_accesses.get(&row).cloned().unwrap_or_default();
+        let mut witness = witness_accesses.get(&row).cloned().unwrap_or_default();
+        executor.sort();
+        witness.sort();
+        (executor != witness).then(|| {
+            (
+                row,
+                vec![
+                    format!("memory (executor): [{}]", render(&executor)),
+                    format!("memory (witness): [{}]", render(&witness)),
+                ],
+            )
+        })
+    })
+}
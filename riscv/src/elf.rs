@@ -0,0 +1,453 @@
+//! Translation of RISC-V ELF executables into powdr assembly.
+//!
+//! Instead of re-parsing textual assembly, the executable sections of the ELF
+//! file are decoded instruction by instruction, and the allocated data sections
+//! are stored in memory at their link addresses. The result is fed into the same
+//! code generation as the assembly frontend.
+//!
+//! Code addresses do not exist as such in powdr: jumps go to positions in the
+//! powdr program. Therefore, every place where the executable computes or stores
+//! the address of an instruction has to be turned into a label reference. This is
+//! the case for direct jumps and branches, `lui`/`auipc` pairs that compute the
+//! address of an instruction, and pointers into the code that are stored in data,
+//! like vtables or jump tables. The latter can only be recognized through
+//! relocations, so the executable should be linked with `--emit-relocs`.
+
+mod decoder;
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::Path,
+};
+
+use goblin::elf::{
+    header::{EM_RISCV, ET_EXEC},
+    reloc::{R_RISCV_32, R_RISCV_CALL, R_RISCV_CALL_PLT, R_RISCV_HI20},
+    section_header::{SHF_ALLOC, SHF_EXECINSTR, SHN_UNDEF, SHT_NOBITS},
+    sym::STT_FUNC,
+    Elf,
+};
+use powdr_asm_utils::data_storage::SingleDataValue;
+
+use crate::compiler::{store_data_value, translate_program, Register};
+use crate::{Argument, CoProcessors, Expression, Statement};
+
+use self::decoder::Instruction;
+
+/// Compiles a RISC-V ELF executable to a powdr assembly file.
+/// Returns an error if the file cannot be read or is not a supported executable.
+pub fn translate(
+    file_name: &Path,
+    coprocessors: &CoProcessors,
+    with_bootloader: bool,
+) -> Result<String, String> {
+    let file_buffer =
+        fs::read(file_name).map_err(|e| format!("Could not read {}: {e}", file_name.display()))?;
+    let program = ElfProgram::load(&file_buffer)?;
+    let runtime_names = coprocessors.runtime_names();
+    if let Some(name) = program
+        .external_calls
+        .values()
+        .find(|name| !runtime_names.contains(&name.as_str()))
+    {
+        return Err(format!("Call to undefined symbol {name}."));
+    }
+
+    let labels = program.labels(&runtime_names);
+    let statements = program.statements(&labels);
+    let data_code = program
+        .data
+        .iter()
+        .flat_map(|(&addr, &value)| {
+            if let Some(target) = program.code_pointers.get(&addr) {
+                store_data_value(addr, SingleDataValue::LabelReference(&labels[target]))
+            } else if value != 0 {
+                store_data_value(addr, SingleDataValue::Value(value))
+            } else {
+                // Memory is zero-initialized, which also takes care of .bss.
+                vec![]
+            }
+        })
+        .collect();
+
+    Ok(translate_program(
+        vec![],
+        statements,
+        data_code,
+        &labels[&program.entry_point],
+        coprocessors,
+        with_bootloader,
+    ))
+}
+
+/// Computation of an address of an instruction by two consecutive instructions.
+enum AddressPair {
+    /// `lui`/`auipc` followed by `addi`, loading the address into a register.
+    Load(u32),
+    /// `lui`/`auipc` followed by `jalr`, i.e. a call or a tail call.
+    Call(CallTarget),
+}
+
+enum CallTarget {
+    Address(u32),
+    /// A symbol that is not defined in the executable, like a coprocessor stub.
+    External(String),
+}
+
+/// The contents of an ELF file relevant for the translation.
+struct ElfProgram {
+    /// The decoded instructions, by address.
+    instructions: BTreeMap<u32, Instruction>,
+    /// The initial memory contents, by word-aligned address.
+    data: BTreeMap<u32, u32>,
+    /// Maps addresses in data to the code address stored there.
+    code_pointers: BTreeMap<u32, u32>,
+    /// Names of function symbols with a unique name, by address.
+    function_names: BTreeMap<u32, String>,
+    /// Maps the address of the first instruction of a call sequence to the
+    /// name of the called symbol, if that symbol is not defined.
+    external_calls: BTreeMap<u32, String>,
+    /// Addresses of `lui` instructions that compute the upper part of a symbol address.
+    /// Only available if the executable was linked with relocations.
+    address_relocations: Option<BTreeSet<u32>>,
+    entry_point: u32,
+}
+
+impl ElfProgram {
+    fn load(file_buffer: &[u8]) -> Result<Self, String> {
+        let elf = Elf::parse(file_buffer).map_err(|e| format!("Invalid ELF file: {e}"))?;
+        if elf.is_64 || elf.header.e_machine != EM_RISCV {
+            return Err("Only 32-bit RISC-V ELF files are supported.".to_string());
+        }
+        if elf.header.e_type != ET_EXEC {
+            return Err("Only statically linked executables are supported.".to_string());
+        }
+
+        let mut instructions = BTreeMap::new();
+        let mut data = BTreeMap::new();
+        for section in &elf.section_headers {
+            if section.sh_flags & u64::from(SHF_ALLOC) == 0 || section.sh_type == SHT_NOBITS {
+                continue;
+            }
+            let addr = section.sh_addr as u32;
+            let contents = section
+                .file_range()
+                .and_then(|range| file_buffer.get(range))
+                .ok_or_else(|| format!("Section at 0x{addr:08x} is not contained in the file."))?;
+            if section.sh_flags & u64::from(SHF_EXECINSTR) != 0 {
+                decode_section(addr, contents, &mut instructions);
+            } else {
+                for (i, byte) in contents.iter().enumerate() {
+                    let byte_addr = addr + i as u32;
+                    *data.entry(byte_addr & !0b11).or_default() |=
+                        u32::from(*byte) << (8 * (byte_addr % 4));
+                }
+            }
+        }
+
+        let mut function_names = BTreeMap::new();
+        let mut name_counts = BTreeMap::<&str, usize>::new();
+        for sym in elf.syms.iter() {
+            let name = elf.strtab.get_at(sym.st_name).unwrap_or_default();
+            if sym.st_type() == STT_FUNC && is_valid_label(name) {
+                *name_counts.entry(name).or_default() += 1;
+                function_names.insert(sym.st_value as u32, name);
+            }
+        }
+        // Local functions of different compilation units can have the same name.
+        let function_names = function_names
+            .into_iter()
+            .filter(|(_, name)| name_counts[name] == 1)
+            .map(|(addr, name)| (addr, name.to_string()))
+            .collect();
+
+        let mut code_pointers = BTreeMap::new();
+        let mut external_calls = BTreeMap::new();
+        let mut address_relocations = BTreeSet::new();
+        if elf.shdr_relocs.is_empty() {
+            log::warn!(
+                "The ELF file does not contain relocations. Code addresses stored in data \
+                 cannot be recognized. Link with --emit-relocs to fix this."
+            );
+        }
+        for (_, relocations) in &elf.shdr_relocs {
+            for reloc in relocations.iter() {
+                let sym = elf.syms.get(reloc.r_sym).ok_or_else(|| {
+                    format!("Relocation refers to invalid symbol {}.", reloc.r_sym)
+                })?;
+                let addr = reloc.r_offset as u32;
+                match reloc.r_type {
+                    R_RISCV_32 => {
+                        let target =
+                            (sym.st_value as i64 + reloc.r_addend.unwrap_or_default()) as u32;
+                        if instructions.contains_key(&target) && data.contains_key(&(addr & !0b11))
+                        {
+                            assert_eq!(addr % 4, 0, "Misaligned code pointer in data.");
+                            code_pointers.insert(addr, target);
+                        }
+                    }
+                    R_RISCV_CALL | R_RISCV_CALL_PLT if sym.st_shndx == SHN_UNDEF as usize => {
+                        let name = elf.strtab.get_at(sym.st_name).ok_or_else(|| {
+                            format!("Symbol {} has an invalid name.", reloc.r_sym)
+                        })?;
+                        external_calls.insert(addr, name.to_string());
+                    }
+                    R_RISCV_HI20 => {
+                        address_relocations.insert(addr);
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        Ok(Self {
+            instructions,
+            data,
+            code_pointers,
+            function_names,
+            external_calls,
+            address_relocations: (!elf.shdr_relocs.is_empty()).then_some(address_relocations),
+            entry_point: elf.header.e_entry as u32,
+        })
+    }
+
+    /// Returns the instruction following the one at `addr`.
+    fn next_instruction(&self, addr: u32) -> Option<&Instruction> {
+        let insn = &self.instructions[&addr];
+        self.instructions.get(&(addr + insn.len))
+    }
+
+    /// Returns the address of the target of a jump or branch instruction.
+    fn jump_target(addr: u32, insn: &Instruction) -> Option<u32> {
+        matches!(
+            insn.op,
+            "jal" | "beq" | "bne" | "blt" | "bge" | "bltu" | "bgeu"
+        )
+        .then(|| addr.wrapping_add(insn.imm as u32))
+    }
+
+    /// Finds `lui`/`auipc` instructions that, together with the following
+    /// instruction, compute the address of an instruction.
+    fn address_pair(&self, addr: u32, insn: &Instruction) -> Option<AddressPair> {
+        let base = match insn.op {
+            "lui" => insn.imm as u32,
+            "auipc" => addr.wrapping_add(insn.imm as u32),
+            _ => return None,
+        };
+        let next = self.next_instruction(addr)?;
+        if insn.rd == 0 || next.rs1 != insn.rd {
+            return None;
+        }
+        if let (Some(address_relocations), "lui") = (&self.address_relocations, insn.op) {
+            // Without a relocation, the pair computes a constant that is not an address.
+            // PC-relative address computations of the same section might be resolved
+            // by the assembler, so we cannot apply this to `auipc`.
+            if !address_relocations.contains(&addr) {
+                return None;
+            }
+        }
+        let target = base.wrapping_add(next.imm as u32);
+        match next.op {
+            "addi" if self.instructions.contains_key(&target) => Some(AddressPair::Load(target)),
+            "jalr" => Some(AddressPair::Call(
+                if let Some(name) = self.external_calls.get(&addr) {
+                    CallTarget::External(name.clone())
+                } else {
+                    assert!(
+                        self.instructions.contains_key(&target),
+                        "Call to 0x{target:08x} at 0x{addr:08x} does not target an instruction."
+                    );
+                    CallTarget::Address(target)
+                },
+            )),
+            _ => None,
+        }
+    }
+
+    /// Assigns a label to every code address that is referenced.
+    /// Labels are derived from function names where possible, but prefixed
+    /// so that they do not collide with labels and registers of the generated
+    /// code. The exception are coprocessor stubs, whose calls are substituted
+    /// by name.
+    fn labels(&self, runtime_names: &[&str]) -> BTreeMap<u32, String> {
+        let referenced = self
+            .instructions
+            .iter()
+            .flat_map(|(&addr, insn)| {
+                let pair_target = match self.address_pair(addr, insn) {
+                    Some(AddressPair::Load(target))
+                    | Some(AddressPair::Call(CallTarget::Address(target))) => Some(target),
+                    _ => None,
+                };
+                Self::jump_target(addr, insn).into_iter().chain(pair_target)
+            })
+            .chain(self.code_pointers.values().copied())
+            .chain([self.entry_point])
+            .collect::<BTreeSet<_>>();
+
+        referenced
+            .into_iter()
+            .map(|addr| {
+                assert!(
+                    self.instructions.contains_key(&addr),
+                    "Code address 0x{addr:08x} is not the start of an instruction."
+                );
+                let label = match self.function_names.get(&addr) {
+                    Some(name) if runtime_names.contains(&name.as_str()) => name.clone(),
+                    Some(name) => format!("__elf_{name}"),
+                    None => format!("__elf_0x{addr:08x}"),
+                };
+                (addr, label)
+            })
+            .collect()
+    }
+
+    /// Translates the instructions to RISC-V assembly statements.
+    fn statements(&self, labels: &BTreeMap<u32, String>) -> Vec<Statement> {
+        let mut statements = vec![];
+        let mut skip_next = false;
+        for (&addr, insn) in &self.instructions {
+            if let Some(label) = labels.get(&addr) {
+                assert!(
+                    !skip_next,
+                    "Jump into the middle of an address computation at 0x{addr:08x}."
+                );
+                statements.push(Statement::Label(label.clone()));
+            }
+            if std::mem::take(&mut skip_next) {
+                continue;
+            }
+            if let Some(pair) = self.address_pair(addr, insn) {
+                let next = self.next_instruction(addr).unwrap();
+                statements.extend(pair_statements(addr, insn, next, pair, labels));
+                skip_next = true;
+            } else {
+                statements.push(instruction_statement(addr, insn, labels));
+            }
+        }
+        statements
+    }
+}
+
+/// Returns true if `name` can be used as a label in powdr assembly.
+fn is_valid_label(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_' || c == '.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '$' | '@' | '.' | '/'))
+}
+
+fn decode_section(addr: u32, contents: &[u8], instructions: &mut BTreeMap<u32, Instruction>) {
+    let mut offset = 0;
+    while offset + 2 <= contents.len() {
+        let insn_addr = addr + offset as u32;
+        let insn = decoder::decode(&contents[offset..]).unwrap_or_else(|| {
+            log::warn!("Unsupported instruction at 0x{insn_addr:08x}, replacing it by unimp.");
+            let len = if contents[offset] & 0b11 == 0b11 {
+                4
+            } else {
+                2
+            };
+            Instruction {
+                op: "unimp",
+                rd: 0,
+                rs1: 0,
+                rs2: 0,
+                imm: 0,
+                len,
+            }
+        });
+        offset += insn.len as usize;
+        instructions.insert(insn_addr, insn);
+    }
+}
+
+fn reg(r: u8) -> Argument {
+    Argument::Register(Register::new(r))
+}
+
+fn num(n: impl Into<i64>) -> Argument {
+    Argument::Expression(Expression::Number(n.into()))
+}
+
+fn label(name: &str) -> Argument {
+    Argument::Expression(Expression::Symbol(name.to_string()))
+}
+
+fn reg_offset(r: u8, offset: i32) -> Argument {
+    Argument::RegOffset(Some(Expression::Number(offset.into())), Register::new(r))
+}
+
+/// Jumps with a link register of `x1` or `x0` are translated to `call` and
+/// `tail`, so that calls to coprocessor stubs are substituted.
+fn jump_statement(link: u8, target: &str) -> Statement {
+    match link {
+        1 => Statement::Instruction("call".to_string(), vec![label(target)]),
+        0 => Statement::Instruction("tail".to_string(), vec![label(target)]),
+        _ => Statement::Instruction("jal".to_string(), vec![reg(link), label(target)]),
+    }
+}
+
+fn pair_statements(
+    addr: u32,
+    first: &Instruction,
+    second: &Instruction,
+    pair: AddressPair,
+    labels: &BTreeMap<u32, String>,
+) -> Vec<Statement> {
+    match pair {
+        AddressPair::Load(target) => {
+            let mut statements = vec![];
+            if first.rd != second.rd {
+                statements.push(instruction_statement(addr, first, labels));
+            }
+            statements.push(Statement::Instruction(
+                "la".to_string(),
+                vec![reg(second.rd), label(&labels[&target])],
+            ));
+            statements
+        }
+        // Like the `call` and `tail` pseudoinstructions, we do not set the
+        // temporary register used to compute the address.
+        AddressPair::Call(CallTarget::Address(target)) => {
+            vec![jump_statement(second.rd, &labels[&target])]
+        }
+        AddressPair::Call(CallTarget::External(name)) => vec![jump_statement(second.rd, &name)],
+    }
+}
+
+fn instruction_statement(
+    addr: u32,
+    insn: &Instruction,
+    labels: &BTreeMap<u32, String>,
+) -> Statement {
+    let args = match insn.op {
+        "lui" => vec![reg(insn.rd), num((insn.imm as u32) >> 12)],
+        "auipc" => {
+            let value = addr.wrapping_add(insn.imm as u32);
+            return Statement::Instruction("li".to_string(), vec![reg(insn.rd), num(value)]);
+        }
+        "jal" => {
+            let target = ElfProgram::jump_target(addr, insn).unwrap();
+            return jump_statement(insn.rd, &labels[&target]);
+        }
+        "beq" | "bne" | "blt" | "bge" | "bltu" | "bgeu" => {
+            let target = ElfProgram::jump_target(addr, insn).unwrap();
+            vec![reg(insn.rs1), reg(insn.rs2), label(&labels[&target])]
+        }
+        "jalr" | "lb" | "lh" | "lw" | "lbu" | "lhu" | "lr.w" => {
+            vec![reg(insn.rd), reg_offset(insn.rs1, insn.imm)]
+        }
+        "sb" | "sh" | "sw" => vec![reg(insn.rs2), reg_offset(insn.rs1, insn.imm)],
+        op if op == "sc.w" || op.starts_with("amo") => {
+            vec![reg(insn.rd), reg(insn.rs2), reg_offset(insn.rs1, 0)]
+        }
+        "addi" | "slti" | "sltiu" | "xori" | "ori" | "andi" | "slli" | "srli" | "srai" => {
+            vec![reg(insn.rd), reg(insn.rs1), num(insn.imm)]
+        }
+        "fence" | "fence.i" | "ecall" | "ebreak" | "unimp" => vec![],
+        _ => vec![reg(insn.rd), reg(insn.rs1), reg(insn.rs2)],
+    };
+    Statement::Instruction(insn.op.to_string(), args)
+}
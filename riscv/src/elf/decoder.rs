@@ -0,0 +1,446 @@
+//! Decoder for RV32IMAC machine code.
+//!
+//! Compressed instructions are expanded to their base instruction set
+//! equivalents, so that the rest of the frontend only has to deal with
+//! a single instruction format.
+
+/// A decoded instruction. Operands that are not used by the instruction are zero.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Instruction {
+    pub op: &'static str,
+    pub rd: u8,
+    pub rs1: u8,
+    pub rs2: u8,
+    /// The sign-extended immediate. For `lui` and `auipc`, this is the value
+    /// after shifting, i.e. the lower 12 bits are zero.
+    pub imm: i32,
+    /// The size of the encoded instruction in bytes, 2 or 4.
+    pub len: u32,
+}
+
+impl Instruction {
+    fn new(op: &'static str, rd: u32, rs1: u32, rs2: u32, imm: i32) -> Self {
+        Self {
+            op,
+            rd: rd as u8,
+            rs1: rs1 as u8,
+            rs2: rs2 as u8,
+            imm,
+            len: 4,
+        }
+    }
+
+    fn compressed(mut self) -> Self {
+        self.len = 2;
+        self
+    }
+}
+
+/// Decodes the instruction at the start of `code`.
+/// Returns `None` if the bytes do not encode a supported instruction.
+pub fn decode(code: &[u8]) -> Option<Instruction> {
+    let low = u16::from_le_bytes(code.get(0..2)?.try_into().unwrap());
+    if low & 0b11 != 0b11 {
+        decode_compressed(low).map(Instruction::compressed)
+    } else {
+        decode_full(u32::from_le_bytes(code.get(0..4)?.try_into().unwrap()))
+    }
+}
+
+/// Returns the bits `hi..=lo` of `value`, shifted to the least significant position.
+fn bits(value: u32, hi: u32, lo: u32) -> u32 {
+    (value >> lo) & ((1 << (hi - lo + 1)) - 1)
+}
+
+/// Sign-extends the lowest `width` bits of `value`.
+fn sign_extend(value: u32, width: u32) -> i32 {
+    ((value << (32 - width)) as i32) >> (32 - width)
+}
+
+fn decode_full(w: u32) -> Option<Instruction> {
+    let rd = bits(w, 11, 7);
+    let funct3 = bits(w, 14, 12);
+    let rs1 = bits(w, 19, 15);
+    let rs2 = bits(w, 24, 20);
+    let funct7 = bits(w, 31, 25);
+
+    let imm_i = sign_extend(bits(w, 31, 20), 12);
+    let imm_s = sign_extend((bits(w, 31, 25) << 5) | bits(w, 11, 7), 12);
+    let imm_b = sign_extend(
+        (bits(w, 31, 31) << 12)
+            | (bits(w, 7, 7) << 11)
+            | (bits(w, 30, 25) << 5)
+            | (bits(w, 11, 8) << 1),
+        13,
+    );
+    let imm_u = (w & 0xfffff000) as i32;
+    let imm_j = sign_extend(
+        (bits(w, 31, 31) << 20)
+            | (bits(w, 19, 12) << 12)
+            | (bits(w, 20, 20) << 11)
+            | (bits(w, 30, 21) << 1),
+        21,
+    );
+
+    let insn = match bits(w, 6, 0) {
+        0b0110111 => Instruction::new("lui", rd, 0, 0, imm_u),
+        0b0010111 => Instruction::new("auipc", rd, 0, 0, imm_u),
+        0b1101111 => Instruction::new("jal", rd, 0, 0, imm_j),
+        0b1100111 if funct3 == 0 => Instruction::new("jalr", rd, rs1, 0, imm_i),
+        0b1100011 => {
+            let op = match funct3 {
+                0b000 => "beq",
+                0b001 => "bne",
+                0b100 => "blt",
+                0b101 => "bge",
+                0b110 => "bltu",
+                0b111 => "bgeu",
+                _ => return None,
+            };
+            Instruction::new(op, 0, rs1, rs2, imm_b)
+        }
+        0b0000011 => {
+            let op = match funct3 {
+                0b000 => "lb",
+                0b001 => "lh",
+                0b010 => "lw",
+                0b100 => "lbu",
+                0b101 => "lhu",
+                _ => return None,
+            };
+            Instruction::new(op, rd, rs1, 0, imm_i)
+        }
+        0b0100011 => {
+            let op = match funct3 {
+                0b000 => "sb",
+                0b001 => "sh",
+                0b010 => "sw",
+                _ => return None,
+            };
+            Instruction::new(op, 0, rs1, rs2, imm_s)
+        }
+        0b0010011 => match (funct3, funct7) {
+            (0b000, _) => Instruction::new("addi", rd, rs1, 0, imm_i),
+            (0b010, _) => Instruction::new("slti", rd, rs1, 0, imm_i),
+            (0b011, _) => Instruction::new("sltiu", rd, rs1, 0, imm_i),
+            (0b100, _) => Instruction::new("xori", rd, rs1, 0, imm_i),
+            (0b110, _) => Instruction::new("ori", rd, rs1, 0, imm_i),
+            (0b111, _) => Instruction::new("andi", rd, rs1, 0, imm_i),
+            (0b001, 0b0000000) => Instruction::new("slli", rd, rs1, 0, rs2 as i32),
+            (0b101, 0b0000000) => Instruction::new("srli", rd, rs1, 0, rs2 as i32),
+            (0b101, 0b0100000) => Instruction::new("srai", rd, rs1, 0, rs2 as i32),
+            _ => return None,
+        },
+        0b0110011 => {
+            let op = match (funct7, funct3) {
+                (0b0000000, 0b000) => "add",
+                (0b0100000, 0b000) => "sub",
+                (0b0000000, 0b001) => "sll",
+                (0b0000000, 0b010) => "slt",
+                (0b0000000, 0b011) => "sltu",
+                (0b0000000, 0b100) => "xor",
+                (0b0000000, 0b101) => "srl",
+                (0b0100000, 0b101) => "sra",
+                (0b0000000, 0b110) => "or",
+                (0b0000000, 0b111) => "and",
+                (0b0000001, 0b000) => "mul",
+                (0b0000001, 0b001) => "mulh",
+                (0b0000001, 0b010) => "mulhsu",
+                (0b0000001, 0b011) => "mulhu",
+                (0b0000001, 0b100) => "div",
+                (0b0000001, 0b101) => "divu",
+                (0b0000001, 0b110) => "rem",
+                (0b0000001, 0b111) => "remu",
+                _ => return None,
+            };
+            Instruction::new(op, rd, rs1, rs2, 0)
+        }
+        0b0001111 => match funct3 {
+            0b000 => Instruction::new("fence", 0, 0, 0, 0),
+            0b001 => Instruction::new("fence.i", 0, 0, 0, 0),
+            _ => return None,
+        },
+        0b1110011 => match w {
+            0x00000073 => Instruction::new("ecall", 0, 0, 0, 0),
+            0x00100073 => Instruction::new("ebreak", 0, 0, 0, 0),
+            // `csrrw x0, cycle, x0`, the canonical encoding of `unimp`.
+            0xc0001073 => Instruction::new("unimp", 0, 0, 0, 0),
+            _ => return None,
+        },
+        0b0101111 if funct3 == 0b010 => {
+            let op = match bits(w, 31, 27) {
+                0b00010 if rs2 == 0 => "lr.w",
+                0b00011 => "sc.w",
+                0b00001 => "amoswap.w",
+                0b00000 => "amoadd.w",
+                0b00100 => "amoxor.w",
+                0b01100 => "amoand.w",
+                0b01000 => "amoor.w",
+                0b10000 => "amomin.w",
+                0b10100 => "amomax.w",
+                0b11000 => "amominu.w",
+                0b11100 => "amomaxu.w",
+                _ => return None,
+            };
+            Instruction::new(op, rd, rs1, rs2, 0)
+        }
+        _ => return None,
+    };
+    Some(insn)
+}
+
+fn decode_compressed(h: u16) -> Option<Instruction> {
+    let h = h as u32;
+    // The all-zero instruction is defined to be illegal.
+    if h == 0 {
+        return Some(Instruction::new("unimp", 0, 0, 0, 0));
+    }
+
+    // Full register numbers.
+    let rd = bits(h, 11, 7);
+    let rs2 = bits(h, 6, 2);
+    // Register numbers of the "popular" registers x8-x15.
+    let rd_c = 8 + bits(h, 4, 2);
+    let rs1_c = 8 + bits(h, 9, 7);
+
+    let imm6 = sign_extend((bits(h, 12, 12) << 5) | bits(h, 6, 2), 6);
+    let imm_j = sign_extend(
+        (bits(h, 12, 12) << 11)
+            | (bits(h, 11, 11) << 4)
+            | (bits(h, 10, 9) << 8)
+            | (bits(h, 8, 8) << 10)
+            | (bits(h, 7, 7) << 6)
+            | (bits(h, 6, 6) << 7)
+            | (bits(h, 5, 3) << 1)
+            | (bits(h, 2, 2) << 5),
+        12,
+    );
+    let imm_b = sign_extend(
+        (bits(h, 12, 12) << 8)
+            | (bits(h, 11, 10) << 3)
+            | (bits(h, 6, 5) << 6)
+            | (bits(h, 4, 3) << 1)
+            | (bits(h, 2, 2) << 5),
+        9,
+    );
+    // Offset of c.lw and c.sw.
+    let uimm_w = (bits(h, 12, 10) << 3) | (bits(h, 6, 6) << 2) | (bits(h, 5, 5) << 6);
+
+    let insn = match (bits(h, 1, 0), bits(h, 15, 13)) {
+        // c.addi4spn
+        (0b00, 0b000) => {
+            let imm = (bits(h, 10, 7) << 6)
+                | (bits(h, 12, 11) << 4)
+                | (bits(h, 5, 5) << 3)
+                | (bits(h, 6, 6) << 2);
+            if imm == 0 {
+                return None;
+            }
+            Instruction::new("addi", rd_c, 2, 0, imm as i32)
+        }
+        // c.lw
+        (0b00, 0b010) => Instruction::new("lw", rd_c, rs1_c, 0, uimm_w as i32),
+        // c.sw
+        (0b00, 0b110) => Instruction::new("sw", 0, rs1_c, rd_c, uimm_w as i32),
+        // c.addi, c.nop
+        (0b01, 0b000) => Instruction::new("addi", rd, rd, 0, imm6),
+        // c.jal
+        (0b01, 0b001) => Instruction::new("jal", 1, 0, 0, imm_j),
+        // c.li
+        (0b01, 0b010) => Instruction::new("addi", rd, 0, 0, imm6),
+        // c.addi16sp
+        (0b01, 0b011) if rd == 2 => {
+            let imm = sign_extend(
+                (bits(h, 12, 12) << 9)
+                    | (bits(h, 6, 6) << 4)
+                    | (bits(h, 5, 5) << 6)
+                    | (bits(h, 4, 3) << 7)
+                    | (bits(h, 2, 2) << 5),
+                10,
+            );
+            if imm == 0 {
+                return None;
+            }
+            Instruction::new("addi", 2, 2, 0, imm)
+        }
+        // c.lui
+        (0b01, 0b011) => {
+            if imm6 == 0 {
+                return None;
+            }
+            Instruction::new("lui", rd, 0, 0, imm6 << 12)
+        }
+        (0b01, 0b100) => match bits(h, 11, 10) {
+            // c.srli, c.srai: shift amounts of 32 or more are reserved on RV32.
+            0b00 if bits(h, 12, 12) == 0 => Instruction::new("srli", rs1_c, rs1_c, 0, rs2 as i32),
+            0b01 if bits(h, 12, 12) == 0 => Instruction::new("srai", rs1_c, rs1_c, 0, rs2 as i32),
+            // c.andi
+            0b10 => Instruction::new("andi", rs1_c, rs1_c, 0, imm6),
+            0b11 if bits(h, 12, 12) == 0 => {
+                let op = match bits(h, 6, 5) {
+                    0b00 => "sub",
+                    0b01 => "xor",
+                    0b10 => "or",
+                    _ => "and",
+                };
+                Instruction::new(op, rs1_c, rs1_c, rd_c, 0)
+            }
+            _ => return None,
+        },
+        // c.j
+        (0b01, 0b101) => Instruction::new("jal", 0, 0, 0, imm_j),
+        // c.beqz
+        (0b01, 0b110) => Instruction::new("beq", 0, rs1_c, 0, imm_b),
+        // c.bnez
+        (0b01, 0b111) => Instruction::new("bne", 0, rs1_c, 0, imm_b),
+        // c.slli
+        (0b10, 0b000) if bits(h, 12, 12) == 0 => Instruction::new("slli", rd, rd, 0, rs2 as i32),
+        // c.lwsp
+        (0b10, 0b010) if rd != 0 => {
+            let imm = (bits(h, 12, 12) << 5) | (bits(h, 6, 4) << 2) | (bits(h, 3, 2) << 6);
+            Instruction::new("lw", rd, 2, 0, imm as i32)
+        }
+        (0b10, 0b100) => match (bits(h, 12, 12), rd, rs2) {
+            // c.jr
+            (0, rs1, 0) if rs1 != 0 => Instruction::new("jalr", 0, rs1, 0, 0),
+            // c.mv
+            (0, rd, rs2) if rs2 != 0 => Instruction::new("add", rd, 0, rs2, 0),
+            // c.ebreak
+            (1, 0, 0) => Instruction::new("ebreak", 0, 0, 0, 0),
+            // c.jalr
+            (1, rs1, 0) => Instruction::new("jalr", 1, rs1, 0, 0),
+            // c.add
+            (1, rd, rs2) => Instruction::new("add", rd, rd, rs2, 0),
+            _ => return None,
+        },
+        // c.swsp
+        (0b10, 0b110) => {
+            let imm = (bits(h, 12, 9) << 2) | (bits(h, 8, 7) << 6);
+            Instruction::new("sw", 0, 2, rs2, imm as i32)
+        }
+        _ => return None,
+    };
+    Some(insn)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn decode_word(w: u32) -> Instruction {
+        decode(&w.to_le_bytes()).unwrap()
+    }
+
+    fn decode_half(h: u16) -> Instruction {
+        decode(&h.to_le_bytes()).unwrap()
+    }
+
+    #[test]
+    fn full_instructions() {
+        // addi a0, a0, -1
+        assert_eq!(
+            decode_word(0xfff50513),
+            Instruction::new("addi", 10, 10, 0, -1)
+        );
+        // lui a5, 0x12345
+        assert_eq!(
+            decode_word(0x123457b7),
+            Instruction::new("lui", 15, 0, 0, 0x12345000)
+        );
+        // sw ra, 12(sp)
+        assert_eq!(decode_word(0x00112623), Instruction::new("sw", 0, 2, 1, 12));
+        // lw a0, -8(s0)
+        assert_eq!(
+            decode_word(0xff842503),
+            Instruction::new("lw", 10, 8, 0, -8)
+        );
+        // bne a0, a1, -16
+        assert_eq!(
+            decode_word(0xfeb518e3),
+            Instruction::new("bne", 0, 10, 11, -16)
+        );
+        // jal ra, 2048
+        assert_eq!(
+            decode_word(0x001000ef),
+            Instruction::new("jal", 1, 0, 0, 2048)
+        );
+        // srai a0, a0, 3
+        assert_eq!(
+            decode_word(0x40355513),
+            Instruction::new("srai", 10, 10, 0, 3)
+        );
+        // div a0, a1, a2
+        assert_eq!(
+            decode_word(0x02c5c533),
+            Instruction::new("div", 10, 11, 12, 0)
+        );
+        // amoadd.w a0, a1, (a2)
+        assert_eq!(
+            decode_word(0x00b6252f),
+            Instruction::new("amoadd.w", 10, 12, 11, 0)
+        );
+        assert_eq!(decode_word(0xc0001073).op, "unimp");
+        // csrrs a0, mstatus, x0 is not supported
+        assert_eq!(decode(&0x30002573u32.to_le_bytes()), None);
+    }
+
+    #[test]
+    fn compressed_instructions() {
+        // c.li a0, -3
+        assert_eq!(
+            decode_half(0x5575),
+            Instruction::new("addi", 10, 0, 0, -3).compressed()
+        );
+        // c.addi sp, -32
+        assert_eq!(
+            decode_half(0x1101),
+            Instruction::new("addi", 2, 2, 0, -32).compressed()
+        );
+        // c.addi16sp sp, -32
+        assert_eq!(
+            decode_half(0x713d),
+            Instruction::new("addi", 2, 2, 0, -32).compressed()
+        );
+        // c.lwsp ra, 28(sp)
+        assert_eq!(
+            decode_half(0x40f2),
+            Instruction::new("lw", 1, 2, 0, 28).compressed()
+        );
+        // c.swsp ra, 28(sp)
+        assert_eq!(
+            decode_half(0xce06),
+            Instruction::new("sw", 0, 2, 1, 28).compressed()
+        );
+        // c.lw a0, 4(a1)
+        assert_eq!(
+            decode_half(0x41c8),
+            Instruction::new("lw", 10, 11, 0, 4).compressed()
+        );
+        // c.beqz a0, -8
+        assert_eq!(
+            decode_half(0xdd65),
+            Instruction::new("beq", 0, 10, 0, -8).compressed()
+        );
+        // c.j 6
+        assert_eq!(
+            decode_half(0xa019),
+            Instruction::new("jal", 0, 0, 0, 6).compressed()
+        );
+        // c.jr ra
+        assert_eq!(
+            decode_half(0x8082),
+            Instruction::new("jalr", 0, 1, 0, 0).compressed()
+        );
+        // c.mv a0, a1
+        assert_eq!(
+            decode_half(0x852e),
+            Instruction::new("add", 10, 0, 11, 0).compressed()
+        );
+        // c.sub a0, a1
+        assert_eq!(
+            decode_half(0x8d0d),
+            Instruction::new("sub", 10, 10, 11, 0).compressed()
+        );
+        assert_eq!(decode_half(0x0000).op, "unimp");
+    }
+}
@@ -13,13 +13,17 @@ use serde_json::Value as JsonValue;
 use std::fs;
 
 use crate::compiler::{FunctionKind, Register};
-pub use crate::coprocessors::CoProcessors;
+pub use crate::coprocessors::{CoProcessors, NUM_PUBLIC_OUTPUTS};
+pub use crate::session::{Outputs, Session};
 
 pub mod compiler;
 pub mod continuations;
 mod coprocessors;
+pub mod differential;
 mod disambiguator;
+pub mod elf;
 pub mod parser;
+pub mod session;
 
 type Statement = powdr_asm_utils::ast::Statement<Register, FunctionKind>;
 type Argument = powdr_asm_utils::ast::Argument<Register, FunctionKind>;
@@ -79,7 +83,6 @@ pub fn compile_rust(
     )
 }
 
-#[allow(clippy::print_stderr)]
 pub fn compile_riscv_asm_bundle(
     original_file_name: &str,
     riscv_asm_files: BTreeMap<String, String>,
@@ -87,6 +90,38 @@ pub fn compile_riscv_asm_bundle(
     force_overwrite: bool,
     coprocessors: &CoProcessors,
     with_bootloader: bool,
+) -> Option<(PathBuf, String)> {
+    write_powdr_asm(original_file_name, output_dir, force_overwrite, || {
+        compiler::compile(riscv_asm_files, coprocessors, with_bootloader)
+    })
+}
+
+/// Compiles a RISC-V ELF executable to powdr assembly.
+/// The executable should be linked with `--emit-relocs`.
+pub fn compile_riscv_elf(
+    input_file: &str,
+    output_dir: &Path,
+    force_overwrite: bool,
+    coprocessors: &CoProcessors,
+    with_bootloader: bool,
+) -> Result<Option<(PathBuf, String)>, String> {
+    let powdr_asm = elf::translate(Path::new(input_file), coprocessors, with_bootloader)?;
+    Ok(write_powdr_asm(
+        input_file,
+        output_dir,
+        force_overwrite,
+        || powdr_asm,
+    ))
+}
+
+/// Writes the powdr assembly generated by `compile` to a file in `output_dir`
+/// named after `original_file_name`.
+#[allow(clippy::print_stderr)]
+fn write_powdr_asm(
+    original_file_name: &str,
+    output_dir: &Path,
+    force_overwrite: bool,
+    compile: impl FnOnce() -> String,
 ) -> Option<(PathBuf, String)> {
     let powdr_asm_file_name = output_dir.join(format!(
         "{}.asm",
@@ -104,7 +139,7 @@ pub fn compile_riscv_asm_bundle(
         return None;
     }
 
-    let powdr_asm = compiler::compile(riscv_asm_files, coprocessors, with_bootloader);
+    let powdr_asm = compile();
 
     fs::write(powdr_asm_file_name.clone(), &powdr_asm).unwrap();
     log::info!("Wrote {}", powdr_asm_file_name.to_str().unwrap());
@@ -0,0 +1,188 @@
+//! A host-side API to run RISC-V guests with typed inputs and outputs.
+//!
+//! A [Session] compiles a guest once and can then run it any number of times.
+//! Inputs are serialized into data channels, which the guest reads with
+//! `get_data_serde`. The outputs are the words the guest commits as public
+//! outputs, and values committed with `commit_serde` can be read back with
+//! [Outputs::read].
+
+use std::path::{Path, PathBuf};
+
+use mktemp::Temp;
+use powdr_executor::witgen::extract_publics;
+use powdr_number::FieldElement;
+use powdr_pipeline::{pipeline::ProofResult, BackendType, Pipeline};
+use powdr_riscv_executor::ExecMode;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{compile_rust, CoProcessors, NUM_PUBLIC_OUTPUTS};
+
+/// A compiled guest together with the inputs for its next run.
+pub struct Session<F: FieldElement> {
+    name: String,
+    powdr_asm: String,
+    output_dir: PathBuf,
+    /// Keeps the output directory alive if the session created it.
+    _temp_dir: Option<Temp>,
+    prover_inputs: Vec<F>,
+    data: Vec<(u32, serde_cbor::Value)>,
+}
+
+impl<F: FieldElement> Session<F> {
+    /// Compiles the guest, which is either a single rust file or a crate,
+    /// and writes the intermediate files to a temporary directory.
+    pub fn new(guest: &str, coprocessors: &CoProcessors) -> Result<Self, Vec<String>> {
+        let temp_dir = Temp::new_dir()
+            .map_err(|e| vec![format!("Could not create a temporary directory: {e}")])?;
+        let mut session = Self::new_with_output(guest, coprocessors, &temp_dir)?;
+        session._temp_dir = Some(temp_dir);
+        Ok(session)
+    }
+
+    /// Compiles the guest, which is either a single rust file or a crate,
+    /// and writes the intermediate files and proofs to `output_dir`.
+    pub fn new_with_output(
+        guest: &str,
+        coprocessors: &CoProcessors,
+        output_dir: &Path,
+    ) -> Result<Self, Vec<String>> {
+        let name = Path::new(guest.strip_suffix("/Cargo.toml").unwrap_or(guest))
+            .file_stem()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| vec![format!("Invalid guest path: {guest}")])?
+            .to_string();
+        let (_, powdr_asm) = compile_rust(guest, output_dir, true, coprocessors, false)
+            .ok_or_else(|| vec![format!("Could not compile the guest {guest}")])?;
+        Ok(Self::from_powdr_asm(
+            name,
+            powdr_asm,
+            output_dir.to_path_buf(),
+        ))
+    }
+
+    /// Creates a session for an already compiled guest.
+    pub fn from_powdr_asm(name: String, powdr_asm: String, output_dir: PathBuf) -> Self {
+        Self {
+            name,
+            powdr_asm,
+            output_dir,
+            _temp_dir: None,
+            prover_inputs: vec![],
+            data: vec![],
+        }
+    }
+
+    /// The compiled guest.
+    pub fn powdr_asm(&self) -> &str {
+        &self.powdr_asm
+    }
+
+    /// Serializes `data` into the data channel `channel`, replacing what was
+    /// written to it before.
+    pub fn write<S: Serialize>(
+        &mut self,
+        channel: u32,
+        data: &S,
+    ) -> Result<&mut Self, Vec<String>> {
+        let value = serde_cbor::value::to_value(data)
+            .map_err(|e| vec![format!("Error serializing data for channel {channel}: {e}")])?;
+        self.data.retain(|(c, _)| *c != channel);
+        self.data.push((channel, value));
+        Ok(self)
+    }
+
+    /// Sets the field elements the guest reads with `get_prover_input`.
+    pub fn with_prover_inputs(&mut self, inputs: Vec<F>) -> &mut Self {
+        self.prover_inputs = inputs;
+        self
+    }
+
+    /// Removes all inputs, so that the session can be run again with others.
+    pub fn clear_inputs(&mut self) -> &mut Self {
+        self.prover_inputs.clear();
+        self.data.clear();
+        self
+    }
+
+    /// Returns a pipeline for the compiled guest with the current inputs.
+    pub fn pipeline(&self) -> Pipeline<F> {
+        self.data.iter().fold(
+            Pipeline::default()
+                .with_name(self.name.clone())
+                .with_output(self.output_dir.clone(), true)
+                .from_asm_string(self.powdr_asm.clone(), None)
+                .with_prover_inputs(self.prover_inputs.clone()),
+            |pipeline, (channel, value)| pipeline.add_data(*channel, value),
+        )
+    }
+
+    /// Runs the guest in the executor and returns its outputs.
+    pub fn execute(&self) -> Result<Outputs, Vec<String>> {
+        let mut pipeline = self.pipeline();
+        let program = pipeline.analyzed_asm_ref()?.clone();
+        let data_callback = pipeline
+            .data_callback()
+            .ok_or_else(|| vec!["The pipeline has no data callback".to_string()])?;
+        let (trace, _) = powdr_riscv_executor::execute_ast(
+            &program,
+            data_callback,
+            &[],
+            usize::MAX,
+            ExecMode::Fast,
+        );
+        Ok(Outputs {
+            words: (0..NUM_PUBLIC_OUTPUTS as u32)
+                .map(|i| trace.public_outputs.get(&i).copied().unwrap_or_default())
+                .collect(),
+        })
+    }
+
+    /// Generates the witness and a proof with the given backend, and returns
+    /// the proof together with the outputs it attests to.
+    pub fn prove(&self, backend: BackendType) -> Result<(ProofResult<F>, Outputs), Vec<String>> {
+        let mut pipeline = self.pipeline().with_backend(backend);
+        let pil = pipeline.optimized_pil_ref()?.clone();
+        let proof_result = pipeline.proof()?;
+        let witness = proof_result
+            .witness
+            .as_ref()
+            .ok_or_else(|| vec!["The proof result has no witness".to_string()])?;
+        let words = extract_publics(witness, &pil)
+            .into_iter()
+            .map(|(_, value)| value.to_degree() as u32)
+            .collect();
+        Ok((proof_result, Outputs { words }))
+    }
+}
+
+/// The words committed as public outputs by a guest, in commit order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outputs {
+    words: Vec<u32>,
+}
+
+impl Outputs {
+    pub fn words(&self) -> &[u32] {
+        &self.words
+    }
+
+    /// Deserializes a value the guest committed with `commit_serde` as its
+    /// first output.
+    pub fn read<D: DeserializeOwned>(&self) -> Result<D, String> {
+        let (len, data) = self
+            .words
+            .split_first()
+            .ok_or_else(|| "No public outputs".to_string())?;
+        let bytes = data
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .take(*len as usize)
+            .collect::<Vec<_>>();
+        if bytes.len() < *len as usize {
+            return Err(format!(
+                "Committed data of {len} bytes does not fit in the public outputs"
+            ));
+        }
+        serde_cbor::from_slice(&bytes).map_err(|e| format!("Error deserializing outputs: {e}"))
+    }
+}
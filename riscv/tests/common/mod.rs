@@ -1,8 +1,10 @@
 use powdr_number::GoldilocksField;
-use powdr_pipeline::{test_util::verify_pipeline, Pipeline, Stage};
+use powdr_pipeline::{test_util::verify_pipeline, Pipeline};
+use powdr_riscv::differential::compare_executor_with_witgen;
 use std::path::PathBuf;
 
-/// Like compiler::test_util::verify_asm_string, but also runs RISCV executor.
+/// Like compiler::test_util::verify_asm_string, but also runs RISCV executor
+/// and checks that it agrees with the witness generation.
 pub fn verify_riscv_asm_string(file_name: &str, contents: &str, inputs: Vec<GoldilocksField>) {
     let temp_dir = mktemp::Temp::new_dir().unwrap().release();
 
@@ -10,15 +12,9 @@ pub fn verify_riscv_asm_string(file_name: &str, contents: &str, inputs: Vec<Gold
         .with_prover_inputs(inputs.clone())
         .with_output(temp_dir.to_path_buf(), false)
         .from_asm_string(contents.to_string(), Some(PathBuf::from(file_name)));
-    pipeline.advance_to(Stage::AnalyzedAsm).unwrap();
-    let analyzed = pipeline.artifact().unwrap().to_analyzed_asm().unwrap();
-    powdr_riscv_executor::execute_ast(
-        analyzed,
-        pipeline.data_callback().unwrap(),
-        // Assume the RISC-V program was compiled without a bootloader, otherwise this will fail.
-        &[],
-        usize::MAX,
-        powdr_riscv_executor::ExecMode::Fast,
-    );
+    // Assume the RISC-V program was compiled without a bootloader, otherwise this will fail.
+    if let Err(divergence) = compare_executor_with_witgen(&mut pipeline) {
+        panic!("{divergence}");
+    }
     verify_pipeline(pipeline);
 }
@@ -40,19 +40,28 @@ __runtime_start:				\
 #define RVTEST_PASS			\
 	la x10,__return_pointer;\
 	lw x1,0(x10);			\
+	la x10,begin_signature;	\
+	la x11,end_signature;	\
 	ret;
 
-// TODO we could (and should?) also output something
+// On failure also returns from __runtime_start, but without a signature
+// (x10 = 0), so that the failing test case can be read from TESTNUM.
 #define RVTEST_FAIL			\
-	unimp;
+	la x10,__return_pointer;\
+	lw x1,0(x10);			\
+	li x10,0;				\
+	ret;
 
 #define RVTEST_CODE_END
 #define RVTEST_DATA_BEGIN	\
 	.balign 4;				\
 __return_pointer:			\
-	.word 0;
+	.word 0;				\
+begin_signature:
 
-#define RVTEST_DATA_END
+#define RVTEST_DATA_END		\
+	.balign 4;				\
+end_signature:
 
 // Aparently they expect the __riscv_xlen to be defined by the preprocessor
 // itself.
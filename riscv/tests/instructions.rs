@@ -2,19 +2,82 @@ mod common;
 
 mod instruction_tests {
     use crate::common::verify_riscv_asm_string;
-    use powdr_riscv::compiler::compile;
-    use powdr_riscv::CoProcessors;
+    use powdr_number::GoldilocksField;
+    use powdr_pipeline::parse_query;
+    use powdr_riscv::{compile_riscv_asm, CoProcessors};
+    use powdr_riscv_executor::ExecMode;
     use test_log::test;
 
-    fn run_instruction_test(assembly: &str, name: &str) {
-        // TODO Should we create one powdr-asm from all tests or keep them separate?
-        let powdr_asm = compile(
-            [(name.to_string(), assembly.to_string())].into(),
+    /// Compiles a test program of the riscv-tests suite and runs it in the
+    /// executor, checking that all its test cases pass and, if a reference is
+    /// given, that its signature matches it. With `full_witgen`, the program
+    /// is also run through the witness generation and the backend.
+    fn run_instruction_test(
+        file: &str,
+        name: &str,
+        reference_signature: Option<&str>,
+        full_witgen: bool,
+    ) {
+        let temp_dir = mktemp::Temp::new_dir().unwrap();
+        let (_, powdr_asm) = compile_riscv_asm(
+            name,
+            [file.to_string()].into_iter(),
+            &temp_dir,
+            true,
             &CoProcessors::base(),
             false,
+        )
+        .unwrap();
+
+        let signature = execute_and_read_signature(&powdr_asm, name);
+        if let Some(reference) = reference_signature {
+            assert_eq!(
+                signature,
+                reference.lines().collect::<Vec<_>>(),
+                "Signature of {name} does not match the reference."
+            );
+        }
+
+        if full_witgen {
+            verify_riscv_asm_string(&format!("{name}.asm"), &powdr_asm, Default::default());
+        }
+    }
+
+    /// Runs the program in the executor and returns the memory between the
+    /// labels `begin_signature` and `end_signature` at the end of the execution,
+    /// as one hex-encoded word per line like the reference signatures.
+    ///
+    /// The test environment passes these addresses in `x10` and `x11` when all
+    /// test cases passed. Otherwise, `x10` is zero and `x28` holds the number of
+    /// the failing test case.
+    fn execute_and_read_signature(powdr_asm: &str, name: &str) -> Vec<String> {
+        // Each test case prints its number through `ebreak`, which we ignore.
+        let callback = |query: &str| -> Result<Option<GoldilocksField>, String> {
+            match &parse_query(query)?[..] {
+                ["\"print_char\"", _] => Ok(Some(0.into())),
+                k => Err(format!("Unsupported query: {}", k.join(", "))),
+            }
+        };
+
+        let (trace, memory) = powdr_riscv_executor::execute::<GoldilocksField>(
+            powdr_asm,
+            &callback,
+            &[],
+            ExecMode::Trace,
         );
 
-        verify_riscv_asm_string(&format!("{name}.asm"), &powdr_asm, Default::default());
+        let mut rows = trace.replay();
+        let mut last_row = vec![];
+        while let Some(row) = rows.next_row() {
+            last_row = row.to_vec();
+        }
+        let register = |name: &str| last_row[trace.reg_map[name] as usize].0 as u32;
+        let (begin, end) = (register("x10"), register("x11"));
+        assert_ne!(begin, 0, "{name}: test case {} failed.", register("x28"));
+        (begin..end)
+            .step_by(4)
+            .map(|address| format!("{:08x}", memory.get(&address).copied().unwrap_or(0)))
+            .collect()
     }
 
     include!(concat!(env!("OUT_DIR"), "/instruction_tests.rs"));
@@ -2,19 +2,33 @@ mod common;
 
 use common::verify_riscv_asm_string;
 use mktemp::Temp;
-use powdr_backend::BackendType;
+use powdr_backend::{BackendType, Proof};
+use powdr_executor::witgen::extract_publics;
 use powdr_number::GoldilocksField;
-use powdr_pipeline::{test_util::verify_asm_string, verify::verify, Pipeline, Stage};
-use std::path::PathBuf;
+use powdr_pipeline::{
+    inputs_to_query_callback,
+    test_util::{verify_asm_string, verify_pipeline},
+    Pipeline, Stage,
+};
+use powdr_riscv_executor::{profiler::ProfilerOptions, ExecMode};
+use std::{
+    io::{Read, Write},
+    net::{TcpListener, TcpStream},
+    path::{Path, PathBuf},
+};
 use test_log::test;
 
 use powdr_riscv::{
-    continuations::{rust_continuations, rust_continuations_dry_run},
-    CoProcessors,
+    continuations::{
+        bootloader::NUM_PAGES_INDEX, check_chunk_chain, rust_continuations,
+        rust_continuations_dry_run, rust_continuations_parallel, verify_chunk_chain,
+    },
+    differential::{compare_executor_with_witgen, compare_trace_with_witness},
+    CoProcessors, Session,
 };
 
 /// Compiles and runs a rust file with continuations, runs the full
-/// witness generation & verifies it using Pilcom.
+/// witness generation & verifies it using the native constraint checker.
 pub fn test_continuations(case: &str) {
     let rust_file = format!("{case}.rs");
     let coprocessors = CoProcessors::base().with_poseidon();
@@ -22,20 +36,21 @@ pub fn test_continuations(case: &str) {
     let riscv_asm =
         powdr_riscv::compile_rust_to_riscv_asm(&format!("tests/riscv_data/{rust_file}"), &temp_dir);
     let powdr_asm = powdr_riscv::compiler::compile(riscv_asm, &coprocessors, true);
+    verify_continuations(&rust_file, powdr_asm);
+}
 
+/// Runs the full witness generation with continuations for a powdr-asm program
+/// compiled with the bootloader & verifies each chunk using the native constraint checker.
+fn verify_continuations(file_name: &str, powdr_asm: String) {
     // Manually create tmp dir, so that it is the same in all chunks.
     let tmp_dir = mktemp::Temp::new_dir().unwrap();
 
     let mut pipeline = Pipeline::<GoldilocksField>::default()
-        .from_asm_string(powdr_asm.clone(), Some(PathBuf::from(&rust_file)))
+        .from_asm_string(powdr_asm, Some(PathBuf::from(file_name)))
         .with_prover_inputs(Default::default())
         .with_output(tmp_dir.to_path_buf(), false);
-    let pipeline_callback = |pipeline: Pipeline<GoldilocksField>| -> Result<(), ()> {
-        // Can't use `verify_pipeline`, because the pipeline was renamed in the middle of after
-        // computing the constants file.
-        let mut pipeline = pipeline.with_backend(BackendType::PilStarkCli);
-        pipeline.advance_to(Stage::Proof).unwrap();
-        verify(pipeline.output_dir().unwrap(), pipeline.name(), Some(case));
+    let pipeline_callback = |pipeline: Pipeline<GoldilocksField>| -> Result<(), Vec<String>> {
+        verify_pipeline(pipeline);
         Ok(())
     };
     let bootloader_inputs = rust_continuations_dry_run(&mut pipeline);
@@ -67,6 +82,253 @@ fn test_poseidon_gl() {
     );
 }
 
+#[test]
+#[ignore = "Too slow"]
+fn test_keccakf() {
+    let case = "keccakf_via_coprocessor.rs";
+    verify_riscv_file(
+        case,
+        Default::default(),
+        &CoProcessors::base().with_keccakf(),
+    );
+}
+
+const KECCAKF_ASM: &str = r#"
+.globl __runtime_start
+__runtime_start:
+    # Permutes the (zero-initialized) state at 0x1000.
+    li a0, 0x1000
+    call keccakf_coprocessor
+    li a0, 0x1000
+    lw a1, 0(a0)
+    li a2, 0x40e1dde7
+    bne a1, a2, fail
+    lw a1, 4(a0)
+    li a2, 0xf1258f79
+    bne a1, a2, fail
+    lw a1, 8(a0)
+    li a2, 0x33c0478a
+    bne a1, a2, fail
+    lw a1, 12(a0)
+    li a2, 0x84d5ccf9
+    bne a1, a2, fail
+    lw a1, 192(a0)
+    li a2, 0x5ceca249
+    bne a1, a2, fail
+    lw a1, 196(a0)
+    li a2, 0xeaf1ff7b
+    bne a1, a2, fail
+    ret
+fail:
+    unimp
+"#;
+
+#[test]
+fn test_keccakf_asm_execute() {
+    let powdr_asm = powdr_riscv::compiler::compile(
+        [("keccakf".to_string(), KECCAKF_ASM.to_string())].into(),
+        &CoProcessors::base().with_keccakf(),
+        false,
+    );
+    powdr_riscv_executor::execute::<GoldilocksField>(
+        &powdr_asm,
+        &inputs_to_query_callback(vec![]),
+        &[],
+        powdr_riscv_executor::ExecMode::Fast,
+    );
+}
+
+#[test]
+#[ignore = "Too slow"]
+fn test_keccakf_asm() {
+    let powdr_asm = powdr_riscv::compiler::compile(
+        [("keccakf".to_string(), KECCAKF_ASM.to_string())].into(),
+        &CoProcessors::base().with_keccakf(),
+        false,
+    );
+    verify_riscv_asm_string("keccakf.asm", &powdr_asm, vec![]);
+}
+
+const SHA256_ASM: &str = r#"
+.globl __runtime_start
+__runtime_start:
+    # Compresses the padded message "abc" into the initial state,
+    # which gives the SHA-256 hash of "abc".
+    li a0, 0x1000
+    li a1, 0x1100
+    li t0, 0x6a09e667
+    sw t0, 0(a0)
+    li t0, 0xbb67ae85
+    sw t0, 4(a0)
+    li t0, 0x3c6ef372
+    sw t0, 8(a0)
+    li t0, 0xa54ff53a
+    sw t0, 12(a0)
+    li t0, 0x510e527f
+    sw t0, 16(a0)
+    li t0, 0x9b05688c
+    sw t0, 20(a0)
+    li t0, 0x1f83d9ab
+    sw t0, 24(a0)
+    li t0, 0x5be0cd19
+    sw t0, 28(a0)
+    li t0, 0x61626380
+    sw t0, 0(a1)
+    li t0, 0x00000018
+    sw t0, 60(a1)
+    call sha256_compress_coprocessor
+    li a0, 0x1000
+    lw t0, 0(a0)
+    li t1, 0xba7816bf
+    bne t0, t1, fail
+    lw t0, 4(a0)
+    li t1, 0x8f01cfea
+    bne t0, t1, fail
+    lw t0, 8(a0)
+    li t1, 0x414140de
+    bne t0, t1, fail
+    lw t0, 12(a0)
+    li t1, 0x5dae2223
+    bne t0, t1, fail
+    lw t0, 16(a0)
+    li t1, 0xb00361a3
+    bne t0, t1, fail
+    lw t0, 20(a0)
+    li t1, 0x96177a9c
+    bne t0, t1, fail
+    lw t0, 24(a0)
+    li t1, 0xb410ff61
+    bne t0, t1, fail
+    lw t0, 28(a0)
+    li t1, 0xf20015ad
+    bne t0, t1, fail
+    ret
+fail:
+    unimp
+"#;
+
+#[test]
+fn test_sha256_asm_execute() {
+    let powdr_asm = powdr_riscv::compiler::compile(
+        [("sha256".to_string(), SHA256_ASM.to_string())].into(),
+        &CoProcessors::base().with_sha256(),
+        false,
+    );
+    powdr_riscv_executor::execute::<GoldilocksField>(
+        &powdr_asm,
+        &inputs_to_query_callback(vec![]),
+        &[],
+        powdr_riscv_executor::ExecMode::Fast,
+    );
+}
+
+#[test]
+#[ignore = "Too slow"]
+fn test_sha256_asm() {
+    let powdr_asm = powdr_riscv::compiler::compile(
+        [("sha256".to_string(), SHA256_ASM.to_string())].into(),
+        &CoProcessors::base().with_sha256(),
+        false,
+    );
+    verify_riscv_asm_string("sha256.asm", &powdr_asm, vec![]);
+}
+
+#[test]
+#[ignore = "Too slow"]
+fn test_sha256() {
+    let case = "sha256_via_coprocessor.rs";
+    verify_riscv_file(
+        case,
+        Default::default(),
+        &CoProcessors::base().with_sha256(),
+    );
+}
+
+const ARITH_ASM: &str = r#"
+.globl __runtime_start
+__runtime_start:
+    # Computes (2**255 + 0xffffffff) * 4 + 5 = 2 * 2**256 + 0x4_00000001,
+    # then reduces the result modulo 1000003.
+    li a0, 0x1000
+    li a1, 0x1020
+    li a2, 0x1040
+    li a3, 0x1060
+    li a4, 0x1080
+    li t0, 0xffffffff
+    sw t0, 0(a0)
+    li t0, 0x80000000
+    sw t0, 28(a0)
+    li t0, 4
+    sw t0, 0(a1)
+    li t0, 5
+    sw t0, 0(a2)
+    call affine_256_coprocessor
+    li a3, 0x1060
+    lw t0, 0(a3)
+    li t1, 2
+    bne t0, t1, fail
+    lw t0, 4(a3)
+    bne t0, zero, fail
+    li a4, 0x1080
+    lw t0, 0(a4)
+    li t1, 1
+    bne t0, t1, fail
+    lw t0, 4(a4)
+    li t1, 4
+    bne t0, t1, fail
+    lw t0, 28(a4)
+    bne t0, zero, fail
+    li a0, 0x1060
+    li a1, 0x1080
+    li a2, 0x10a0
+    li a3, 0x10c0
+    li t0, 1000003
+    sw t0, 0(a2)
+    call mod_256_coprocessor
+    li a3, 0x10c0
+    lw t0, 0(a3)
+    li t1, 0x1ff7f
+    bne t0, t1, fail
+    lw t0, 4(a3)
+    bne t0, zero, fail
+    ret
+fail:
+    unimp
+"#;
+
+#[test]
+fn test_arith_asm_execute() {
+    let powdr_asm = powdr_riscv::compiler::compile(
+        [("arith".to_string(), ARITH_ASM.to_string())].into(),
+        &CoProcessors::base().with_arith(),
+        false,
+    );
+    powdr_riscv_executor::execute::<GoldilocksField>(
+        &powdr_asm,
+        &inputs_to_query_callback(vec![]),
+        &[],
+        powdr_riscv_executor::ExecMode::Fast,
+    );
+}
+
+#[test]
+#[ignore = "Too slow"]
+fn test_arith_asm() {
+    let powdr_asm = powdr_riscv::compiler::compile(
+        [("arith".to_string(), ARITH_ASM.to_string())].into(),
+        &CoProcessors::base().with_arith(),
+        false,
+    );
+    verify_riscv_asm_string("arith.asm", &powdr_asm, vec![]);
+}
+
+#[test]
+#[ignore = "Too slow"]
+fn test_arith() {
+    let case = "arith_via_coprocessor.rs";
+    verify_riscv_file(case, Default::default(), &CoProcessors::base().with_arith());
+}
+
 #[test]
 #[ignore = "Too slow"]
 fn test_sum() {
@@ -161,6 +423,146 @@ fn test_function_pointer() {
     );
 }
 
+#[test]
+fn test_elf_execute() {
+    // See tests/riscv_data/elf_program/build.sh for how the ELF file is built.
+    let powdr_asm = powdr_riscv::elf::translate(
+        Path::new("tests/riscv_data/elf_program/program.elf"),
+        &CoProcessors::base(),
+        false,
+    )
+    .unwrap();
+    // The call to the undefined coprocessor stub has been substituted.
+    assert!(powdr_asm.contains("data_identifier"));
+
+    powdr_riscv_executor::execute::<GoldilocksField>(
+        &powdr_asm,
+        &inputs_to_query_callback(vec![954.into()]),
+        &[],
+        powdr_riscv_executor::ExecMode::Fast,
+    );
+}
+
+#[test]
+fn test_elf_rust_execute() {
+    // See tests/riscv_data/elf_rust/build.sh for how the ELF file is built.
+    let powdr_asm = powdr_riscv::elf::translate(
+        Path::new("tests/riscv_data/elf_rust/program.elf"),
+        &CoProcessors::base(),
+        false,
+    )
+    .unwrap();
+
+    // The program shifts the second input right by the third one and
+    // computes the first one from it.
+    let inputs = vec![6.into(), (-1000i32 as u32).into(), 3.into()];
+    powdr_riscv_executor::execute::<GoldilocksField>(
+        &powdr_asm,
+        &inputs_to_query_callback(inputs),
+        &[],
+        powdr_riscv_executor::ExecMode::Fast,
+    );
+}
+
+#[test]
+fn test_elf_invalid_file() {
+    let err = powdr_riscv::elf::translate(
+        Path::new("tests/riscv_data/elf_program/program.s"),
+        &CoProcessors::base(),
+        false,
+    )
+    .unwrap_err();
+    assert!(err.starts_with("Invalid ELF file"), "{err}");
+}
+
+#[test]
+#[ignore = "Too slow"]
+fn test_elf() {
+    let powdr_asm = powdr_riscv::elf::translate(
+        Path::new("tests/riscv_data/elf_program/program.elf"),
+        &CoProcessors::base(),
+        false,
+    )
+    .unwrap();
+    verify_riscv_asm_string("elf_program.asm", &powdr_asm, vec![954.into()]);
+}
+
+#[test]
+#[ignore = "Too slow"]
+fn test_public_outputs() {
+    let riscv_asm = r#"
+.globl __runtime_start
+__runtime_start:
+    li a0, 0
+    li a1, 42
+    call commit_public_coprocessor
+    li a0, 1
+    li a1, 0xdeadbeef
+    call commit_public_coprocessor
+    ret
+"#;
+    let powdr_asm = powdr_riscv::compiler::compile(
+        [("public_outputs".to_string(), riscv_asm.to_string())].into(),
+        &CoProcessors::base(),
+        false,
+    );
+
+    let mut pipeline = Pipeline::<GoldilocksField>::default()
+        .from_asm_string(powdr_asm, Some(PathBuf::from("public_outputs.asm")))
+        .with_prover_inputs(Default::default())
+        .with_backend(BackendType::EStark);
+    let pil = pipeline.optimized_pil_ref().unwrap().clone();
+    let proof_result = pipeline.proof().unwrap();
+
+    let mut publics = extract_publics(proof_result.witness.as_ref().unwrap(), &pil)
+        .into_iter()
+        .map(|(_, value)| value)
+        .collect::<Vec<_>>();
+    assert_eq!(publics.len(), powdr_riscv::NUM_PUBLIC_OUTPUTS);
+    assert_eq!(publics[..2], [42.into(), 0xdeadbeefu32.into()]);
+
+    let proof = proof_result.proof.unwrap();
+    let backend = BackendType::EStark
+        .factory::<GoldilocksField>()
+        .create(&pil, &proof_result.fixed_cols, None, None, None)
+        .unwrap();
+    backend.verify(&proof, &[publics.clone()]).unwrap();
+
+    // The proof does not attest to different outputs.
+    publics[0] = 43.into();
+    assert!(backend.verify(&proof, &[publics]).is_err());
+}
+
+#[test]
+fn test_session_execute() {
+    let mut session =
+        Session::<GoldilocksField>::new("tests/riscv_data/session", &CoProcessors::base()).unwrap();
+    session
+        .write(1, &vec![1u32, 2, 3])
+        .unwrap()
+        .write(2, &10u32)
+        .unwrap();
+    assert_eq!(session.execute().unwrap().read::<u32>(), Ok(60));
+
+    session.write(2, &2u32).unwrap();
+    assert_eq!(session.execute().unwrap().read::<u32>(), Ok(12));
+}
+
+#[test]
+#[ignore = "Too slow"]
+fn test_session() {
+    let mut session =
+        Session::<GoldilocksField>::new("tests/riscv_data/session", &CoProcessors::base()).unwrap();
+    session
+        .write(1, &vec![1u32, 2, 3])
+        .unwrap()
+        .write(2, &2u32)
+        .unwrap();
+    let (_, outputs) = session.prove(BackendType::PilVerify).unwrap();
+    assert_eq!(outputs, session.execute().unwrap());
+    assert_eq!(outputs.read::<u32>(), Ok(12));
+}
+
 /*
 mstore(0, 666)
 return(0, 32)
@@ -187,7 +589,7 @@ fn test_evm() {
 
 #[test]
 #[ignore = "Too slow"]
-#[should_panic(expected = "Witness generation failed.")]
+#[should_panic(expected = "Witness generation failed in machine Main Machine at row")]
 fn test_print() {
     let case = "print.rs";
     verify_file(case, Default::default(), &CoProcessors::base());
@@ -211,6 +613,118 @@ fn test_many_chunks_dry() {
     rust_continuations_dry_run::<GoldilocksField>(&mut pipeline);
 }
 
+const MANY_CHUNKS_ASM: &str = r#"
+.globl __runtime_start
+__runtime_start:
+    # Writes the loop counter to a 1KB ring buffer at 0x2000, for long enough
+    # to need more than one chunk.
+    li a0, 0x2000
+    li a1, 0
+    li a2, 50000
+loop:
+    andi a3, a1, 255
+    slli a3, a3, 2
+    add a3, a3, a0
+    sw a1, 0(a3)
+    addi a1, a1, 1
+    bne a1, a2, loop
+    # The last value written to the start of the buffer is 49920.
+    lw a3, 0(a0)
+    li a4, 49920
+    bne a3, a4, fail
+    ret
+fail:
+    unimp
+"#;
+
+fn many_chunks_asm() -> String {
+    powdr_riscv::compiler::compile(
+        [("many_chunks".to_string(), MANY_CHUNKS_ASM.to_string())].into(),
+        &CoProcessors::base().with_poseidon(),
+        true,
+    )
+}
+
+#[test]
+fn test_many_chunks_asm_dry() {
+    let mut pipeline = Pipeline::<GoldilocksField>::default()
+        .from_asm_string(many_chunks_asm(), Some(PathBuf::from("many_chunks.asm")))
+        .with_prover_inputs(Default::default());
+    let bootloader_inputs = rust_continuations_dry_run(&mut pipeline);
+    assert!(bootloader_inputs.len() > 1);
+
+    // The public values of each chunk are the first bootloader inputs.
+    let mut chunk_publics = bootloader_inputs
+        .iter()
+        .map(|(inputs, _)| inputs[..NUM_PAGES_INDEX].to_vec())
+        .collect::<Vec<_>>();
+    check_chunk_chain(&chunk_publics).unwrap();
+
+    // Without the last chunk, the execution has not terminated.
+    assert!(check_chunk_chain(&chunk_publics[..chunk_publics.len() - 1]).is_err());
+
+    // Tampering with the state passed from one chunk to the next breaks the chain.
+    chunk_publics[1][0] += GoldilocksField::from(1);
+    assert!(check_chunk_chain(&chunk_publics).is_err());
+}
+
+#[test]
+#[ignore = "Too slow"]
+fn test_many_chunks_asm() {
+    verify_continuations("many_chunks.asm", many_chunks_asm());
+}
+
+/// Proves a chunk and returns the proof together with all public values of the chunk.
+fn prove_chunk(
+    mut pipeline: Pipeline<GoldilocksField>,
+) -> Result<(Proof, Vec<GoldilocksField>), Vec<String>> {
+    pipeline.advance_to(Stage::GeneratedWitness)?;
+    let generated_witness = pipeline.artifact().unwrap().to_generated_witness().unwrap();
+    let publics = extract_publics(
+        generated_witness.witness.as_ref().unwrap(),
+        &generated_witness.pil,
+    )
+    .into_iter()
+    .map(|(_, value)| value)
+    .collect();
+    let proof = pipeline.proof()?.proof.unwrap();
+    Ok((proof, publics))
+}
+
+#[test]
+#[ignore = "Too slow"]
+fn test_many_chunks_asm_parallel() {
+    let tmp_dir = mktemp::Temp::new_dir().unwrap();
+    let mut pipeline = Pipeline::<GoldilocksField>::default()
+        .from_asm_string(many_chunks_asm(), Some(PathBuf::from("many_chunks.asm")))
+        .with_prover_inputs(Default::default())
+        .with_output(tmp_dir.to_path_buf(), true)
+        .with_backend(BackendType::EStark);
+    let bootloader_inputs = rust_continuations_dry_run(&mut pipeline);
+    let num_chunks = bootloader_inputs.len();
+
+    // All chunks share the same PIL and fixed columns, and therefore the same verification key.
+    let mut vkey_pipeline = pipeline.clone();
+    let mut verification_key = vec![];
+    vkey_pipeline
+        .export_verification_key(&mut verification_key)
+        .unwrap();
+
+    let chunks = rust_continuations_parallel(pipeline, prove_chunk, bootloader_inputs, 2).unwrap();
+
+    // The results are returned in chunk order, so the chain still links up.
+    assert_eq!(chunks.len(), num_chunks);
+    let pil_with_fixed_cols = vkey_pipeline.pil_with_evaluated_fixed_cols_ref().unwrap();
+    verify_chunk_chain(
+        &pil_with_fixed_cols.pil,
+        &pil_with_fixed_cols.fixed_cols,
+        BackendType::EStark,
+        &verification_key,
+        &chunks,
+    )
+    .unwrap();
+}
+
 #[test]
 #[ignore = "Too slow"]
 fn test_many_chunks() {
@@ -223,6 +737,261 @@ fn test_many_chunks_memory() {
     test_continuations("many_chunks_memory")
 }
 
+const PROFILE_ASM: &str = r#"
+.file 1 "/guest/src" "main.rs"
+.globl __runtime_start
+__runtime_start:
+    mv s0, ra
+    li a0, 0
+    li a1, 10
+.Lloop:
+    .loc 1 5 0
+    call add_one
+    addi a1, a1, -1
+    bnez a1, .Lloop
+    mv ra, s0
+    ret
+.globl add_one
+add_one:
+    .loc 1 12 0
+    addi a0, a0, 1
+    ret
+"#;
+
+#[test]
+fn test_profiler() {
+    let powdr_asm = powdr_riscv::compiler::compile(
+        [("profile".to_string(), PROFILE_ASM.to_string())].into(),
+        &CoProcessors::base(),
+        false,
+    );
+    let temp_dir = Temp::new_dir().unwrap();
+    powdr_riscv_executor::execute::<GoldilocksField>(
+        &powdr_asm,
+        &inputs_to_query_callback(vec![]),
+        &[],
+        powdr_riscv_executor::ExecMode::Profile(ProfilerOptions {
+            output_directory: temp_dir.to_path_buf(),
+            file_stem: "profile".to_string(),
+            flamegraph: true,
+            callgrind: true,
+        }),
+    );
+
+    // `add_one` is called 10 times and executes 2 rows each time. The entry
+    // point is called from the code before the first label.
+    let folded = std::fs::read_to_string(temp_dir.join("profile.folded")).unwrap();
+    assert!(folded
+        .lines()
+        .any(|l| l == "[unknown];__runtime_start;add_one 20"));
+
+    let callgrind = std::fs::read_to_string(temp_dir.join("profile.callgrind")).unwrap();
+    assert!(callgrind.contains("fl=/guest/src/main.rs\nfn=add_one\n12 20\n"));
+    assert!(callgrind.contains("cfn=add_one\ncalls=10 0\n5 20\n"));
+}
+
+const GDB_ASM: &str = r#"
+.globl __runtime_start
+__runtime_start:
+    mv s0, ra
+    li a0, 0
+    li a1, 0x100
+    li a2, 3
+.Lloop:
+    call add_one
+    sw a0, 0(a1)
+    addi a2, a2, -1
+    bnez a2, .Lloop
+    mv ra, s0
+    ret
+.globl add_one
+add_one:
+    addi a0, a0, 1
+    ret
+"#;
+
+/// A minimal client for the GDB remote serial protocol.
+struct GdbClient(TcpStream);
+
+impl GdbClient {
+    fn connect(port: u16) -> Self {
+        Self(TcpStream::connect(("127.0.0.1", port)).unwrap())
+    }
+
+    fn send(&mut self, packet: &str) {
+        let checksum = packet.bytes().fold(0u8, |sum, b| sum.wrapping_add(b));
+        write!(self.0, "${packet}#{checksum:02x}").unwrap();
+    }
+
+    fn receive(&mut self) -> String {
+        let mut byte = [0u8];
+        while byte[0] != b'$' {
+            self.0.read_exact(&mut byte).unwrap();
+        }
+        let mut packet = vec![];
+        loop {
+            self.0.read_exact(&mut byte).unwrap();
+            if byte[0] == b'#' {
+                break;
+            }
+            packet.push(byte[0]);
+        }
+        let mut checksum = [0u8; 2];
+        self.0.read_exact(&mut checksum).unwrap();
+        self.0.write_all(b"+").unwrap();
+        String::from_utf8(packet).unwrap()
+    }
+
+    fn request(&mut self, packet: &str) -> String {
+        self.send(packet);
+        self.receive()
+    }
+}
+
+#[test]
+fn test_gdb_stub() {
+    let powdr_asm = powdr_riscv::compiler::compile(
+        [("gdb".to_string(), GDB_ASM.to_string())].into(),
+        &CoProcessors::base(),
+        false,
+    );
+    // Keep the listener bound until GDB connected, so that no one else can take the port.
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let executor = std::thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        powdr_riscv_executor::execute::<GoldilocksField>(
+            &powdr_asm,
+            &inputs_to_query_callback(vec![]),
+            &[],
+            powdr_riscv_executor::ExecMode::Gdb(stream),
+        );
+    });
+
+    let mut gdb = GdbClient::connect(port);
+    assert_eq!(gdb.request("?"), "S05");
+
+    // There are no RISC-V addresses, so breakpoints there are rejected.
+    assert_eq!(gdb.request("Z0,10074,4"), "E01");
+
+    let command = "break add_one"
+        .bytes()
+        .map(|b| format!("{b:02x}"))
+        .collect::<String>();
+    gdb.send(&format!("qRcmd,{command}"));
+    assert!(gdb.receive().starts_with('O'));
+    assert_eq!(gdb.receive(), "OK");
+
+    // At the first call, a0 and the memory at 0x100 are still zero.
+    assert_eq!(gdb.request("c"), "S05");
+    assert_eq!(gdb.request("p0a"), "00000000");
+    assert_eq!(gdb.request("m100,4"), "00000000");
+
+    // At the second call, both have been incremented once.
+    assert_eq!(gdb.request("c"), "S05");
+    assert_eq!(gdb.request("p0a"), "01000000");
+    assert_eq!(gdb.request("m100,4"), "01000000");
+    assert_eq!(gdb.request("g").len(), 33 * 8);
+
+    // Reads are limited to what fits into a packet.
+    assert_eq!(gdb.request("m100,800").len(), 2 * 0x800);
+    assert_eq!(gdb.request("m100,801"), "E01");
+    assert_eq!(gdb.request("m100,ffffffff"), "E01");
+
+    assert_eq!(gdb.request("D"), "OK");
+    executor.join().unwrap();
+}
+
+#[test]
+#[ignore = "Too slow"]
+fn test_compare_executor_with_witgen() {
+    let powdr_asm = powdr_riscv::compiler::compile(
+        [("gdb".to_string(), GDB_ASM.to_string())].into(),
+        &CoProcessors::base(),
+        false,
+    );
+    let mut pipeline = Pipeline::<GoldilocksField>::default()
+        .from_asm_string(powdr_asm, Some(PathBuf::from("gdb.asm")))
+        .with_prover_inputs(vec![]);
+    if let Err(divergence) = compare_executor_with_witgen(&mut pipeline) {
+        panic!("{divergence}");
+    }
+}
+
+#[test]
+fn test_compare_executor_with_tampered_witness() {
+    let powdr_asm = powdr_riscv::compiler::compile(
+        [("gdb".to_string(), GDB_ASM.to_string())].into(),
+        &CoProcessors::base(),
+        false,
+    );
+    let program = Pipeline::<GoldilocksField>::default()
+        .from_asm_string(powdr_asm, Some(PathBuf::from("gdb.asm")))
+        .analyzed_asm_ref()
+        .unwrap()
+        .clone();
+    let (trace, _) = powdr_riscv_executor::execute_ast(
+        &program,
+        &inputs_to_query_callback(vec![]),
+        &[],
+        usize::MAX,
+        ExecMode::Trace,
+    );
+
+    // A witness that matches the execution exactly.
+    let registers = trace
+        .reg_map
+        .iter()
+        .map(|(name, &index)| (format!("main.{name}"), index as usize))
+        .collect::<Vec<_>>();
+    let mut witness = registers
+        .iter()
+        .map(|(name, _)| (name.clone(), vec![]))
+        .collect::<Vec<(String, Vec<GoldilocksField>)>>();
+    let mut rows = trace.replay();
+    while let Some(row) = rows.next_row() {
+        for ((_, index), (_, column)) in registers.iter().zip(&mut witness) {
+            column.push(row[*index].0.into());
+        }
+    }
+    assert!(compare_trace_with_witness(&program, &trace, &witness).is_ok());
+
+    // The registers of a row are written by the previous row.
+    let (_, a0) = witness
+        .iter_mut()
+        .find(|(name, _)| name == "main.x10")
+        .unwrap();
+    let value = a0[5];
+    let tampered = value + GoldilocksField::from(1);
+    a0[5] = tampered;
+    let divergence = compare_trace_with_witness(&program, &trace, &witness).unwrap_err();
+    assert_eq!(divergence.row, 4);
+    assert_eq!(
+        divergence.differences,
+        vec![format!("x10: executor {value}, witness {tampered}")]
+    );
+}
+
+#[test]
+#[ignore = "Too slow"]
+fn test_compare_executor_with_failing_witgen() {
+    let powdr_asm = powdr_riscv::compiler::compile(
+        [("gdb".to_string(), GDB_ASM.to_string())].into(),
+        &CoProcessors::base(),
+        false,
+    );
+    // Witness generation cannot follow the execution if a0 takes a different value in row 5.
+    let mut a0 = vec![GoldilocksField::from(0); 6];
+    a0[5] = 42.into();
+    let mut pipeline = Pipeline::<GoldilocksField>::default()
+        .from_asm_string(powdr_asm, Some(PathBuf::from("gdb.asm")))
+        .with_prover_inputs(vec![])
+        .add_external_witness_values(vec![("main.x10".to_string(), a0)]);
+    let divergence = compare_executor_with_witgen(&mut pipeline).unwrap_err();
+    assert_eq!(divergence.row, 4);
+    assert_eq!(divergence.differences[0], "Witness generation failed:");
+}
+
 fn verify_file(case: &str, inputs: Vec<GoldilocksField>, coprocessors: &CoProcessors) {
     let temp_dir = Temp::new_dir().unwrap();
     let riscv_asm =
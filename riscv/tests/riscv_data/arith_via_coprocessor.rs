@@ -0,0 +1,33 @@
+#![no_std]
+
+use powdr_riscv_runtime::coprocessors::{affine_256, mod_256, modmul_256};
+
+#[no_mangle]
+fn main() {
+    // (2**256 - 1) * 1 + (2**256 - 1) = 2**256 + (2**256 - 2)
+    let max = [0xffffffff; 8];
+    let one = [1, 0, 0, 0, 0, 0, 0, 0];
+    let (hi, lo) = affine_256(&max, &one, &max);
+    assert_eq!(hi, one);
+    assert_eq!(
+        lo,
+        [
+            0xfffffffe, 0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff,
+            0xffffffff
+        ]
+    );
+
+    // (2**256 + 1) % (2**256 - 1) = 2
+    assert_eq!(mod_256(&one, &one, &max), [2, 0, 0, 0, 0, 0, 0, 0]);
+
+    // (p - 1) * (p - 1) % p = 1, where p is the secp256k1 field prime.
+    let p = [
+        0xfffffc2f, 0xfffffffe, 0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff,
+        0xffffffff,
+    ];
+    let p_minus_one = [
+        0xfffffc2e, 0xfffffffe, 0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff,
+        0xffffffff,
+    ];
+    assert_eq!(modmul_256(&p_minus_one, &p_minus_one, &p), one);
+}
@@ -0,0 +1,10 @@
+#!/bin/sh
+# Builds program.elf from program.s. Any RISC-V toolchain works, as long as
+# the executable is linked with --emit-relocs. Here we use LLVM and the lld
+# that ships with rustc.
+set -e
+cd "$(dirname "$0")"
+llvm-mc -triple=riscv32 -mattr=+m,+a,+c -filetype=obj -o program.o program.s
+rust-lld -flavor gnu -m elf32lriscv --emit-relocs --unresolved-symbols=ignore-all \
+    -e _start -o program.elf program.o
+rm program.o
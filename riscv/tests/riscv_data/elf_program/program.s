@@ -0,0 +1,108 @@
+# A small program to test the ELF frontend, see build.sh.
+# Computes a value through direct calls, tail calls and function pointers
+# stored in registers and in data, and compares it to the first prover input.
+
+    .text
+    .globl _start
+    .type _start, @function
+_start:
+    addi sp, sp, -16
+    sw ra, 12(sp)
+    sw s0, 8(sp)
+    sw s1, 4(sp)
+
+    # Call the functions in the `ops` table.
+    la s1, ops
+    lw t0, 0(s1)
+    li a0, 20
+    li a1, 6
+    jalr t0
+    mv s0, a0
+    lw t0, 4(s1)
+    li a0, -20
+    li a1, 6
+    jalr t0
+    add s0, s0, a0
+
+    # Call a function through a pointer in a register.
+    la t0, negate
+    mv a0, s0
+    jalr t0
+
+    # A call that results in a tail call.
+    call twice
+    mv s0, a0
+
+    # Read-only data.
+    lui t1, %hi(magic)
+    lw t1, %lo(magic)(t1)
+    add s0, s0, t1
+
+    # Zero-initialized data.
+    la t2, counter
+    lw t1, 0(t2)
+    add s0, s0, t1
+    sw s0, 0(t2)
+    lw s0, 0(t2)
+
+    # Compare with the first prover input.
+    li a0, 0
+    ecall
+    beq a0, s0, 1f
+    unimp
+1:
+    lw s1, 4(sp)
+    lw s0, 8(sp)
+    lw ra, 12(sp)
+    addi sp, sp, 16
+    ret
+
+    .type add_fn, @function
+add_fn:
+    add a0, a0, a1
+    ret
+
+    .type div_fn, @function
+div_fn:
+    div a0, a0, a1
+    ret
+
+    .type negate, @function
+negate:
+    neg a0, a0
+    ret
+
+    .type twice, @function
+twice:
+    tail twice_impl
+
+    .type twice_impl, @function
+twice_impl:
+    slli a0, a0, 1
+    ret
+
+# Never called, but the call to the undefined coprocessor stub
+# has to be substituted.
+    .type read_input, @function
+read_input:
+    addi sp, sp, -16
+    sw ra, 12(sp)
+    call input_coprocessor
+    lw ra, 12(sp)
+    addi sp, sp, 16
+    ret
+
+    .section .rodata
+    .p2align 2
+magic:
+    .word 1000
+
+    .data
+    .p2align 2
+ops:
+    .word add_fn, div_fn
+
+    .bss
+    .p2align 2
+counter:
+    .zero 4
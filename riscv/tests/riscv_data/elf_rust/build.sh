@@ -0,0 +1,9 @@
+#!/bin/sh
+# Builds program.elf from the Rust crate in this directory. The executable has
+# to be linked with --emit-relocs, so that code pointers in data are recognized.
+set -e
+cd "$(dirname "$0")"
+RUSTFLAGS="-C link-arg=--emit-relocs" cargo +nightly build --release \
+    --target riscv32imac-unknown-none-elf --target-dir target
+cp target/riscv32imac-unknown-none-elf/release/elf_rust program.elf
+rm -rf target Cargo.lock
@@ -0,0 +1,69 @@
+//! A small Rust program to test the ELF frontend on compiler output, see build.sh.
+//! Uses arithmetic shifts by a register, function pointers stored in data
+//! and a jump table, and compares the result to the first prover input.
+#![no_std]
+#![no_main]
+
+use core::arch::asm;
+use core::panic::PanicInfo;
+
+#[panic_handler]
+fn panic(_: &PanicInfo<'_>) -> ! {
+    unsafe {
+        asm!("unimp");
+    }
+    loop {}
+}
+
+fn get_prover_input(index: u32) -> u32 {
+    let mut value: u32;
+    unsafe {
+        asm!("ecall", lateout("a0") value, in("a0") index);
+    }
+    value
+}
+
+#[inline(never)]
+fn shift_right(value: i32, amount: u32) -> i32 {
+    value >> amount
+}
+
+#[inline(never)]
+fn add(a: i32, b: i32) -> i32 {
+    a + b
+}
+
+#[inline(never)]
+fn sub(a: i32, b: i32) -> i32 {
+    a - b
+}
+
+static OPS: [fn(i32, i32) -> i32; 2] = [add, sub];
+
+#[inline(never)]
+fn select(selector: u32, value: i32) -> i32 {
+    match selector {
+        0 => value * 3,
+        1 => value - 7,
+        2 => value ^ 0x55,
+        3 => value / 5,
+        4 => value + 100,
+        _ => value,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn _start() {
+    let value = get_prover_input(1) as i32;
+    let amount = get_prover_input(2);
+
+    let mut result = shift_right(value, amount);
+    for (i, op) in OPS.iter().enumerate() {
+        result = op(result, i as i32 + 1);
+    }
+    for selector in 0..6 {
+        result = select(selector, result);
+    }
+
+    assert_eq!(result, get_prover_input(0) as i32);
+}
@@ -0,0 +1,14 @@
+#![no_std]
+
+extern crate alloc;
+use alloc::vec::Vec;
+
+use powdr_riscv_runtime::coprocessors::{commit_serde, get_data_serde};
+
+#[no_mangle]
+pub fn main() {
+    let values: Vec<u32> = get_data_serde(1);
+    let factor: u32 = get_data_serde(2);
+    let scaled_sum: u32 = values.iter().map(|v| v * factor).sum();
+    commit_serde(&scaled_sum);
+}
@@ -0,0 +1,41 @@
+#![no_std]
+
+use powdr_riscv_runtime::coprocessors::{sha256, sha256_compress};
+
+#[no_mangle]
+fn main() {
+    // The padded message "abc", compressed into the initial state.
+    let mut state = [
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+        0x5be0cd19,
+    ];
+    let mut block = [0u32; 16];
+    block[0] = 0x61626380;
+    block[15] = 24;
+    sha256_compress(&mut state, &block);
+    assert_eq!(
+        state,
+        [
+            0xba7816bf, 0x8f01cfea, 0x414140de, 0x5dae2223, 0xb00361a3, 0x96177a9c, 0xb410ff61,
+            0xf20015ad
+        ]
+    );
+
+    assert_eq!(
+        sha256(b"abc"),
+        [
+            0xba, 0x78, 0x16, 0xbf, 0x8f, 0x01, 0xcf, 0xea, 0x41, 0x41, 0x40, 0xde, 0x5d, 0xae,
+            0x22, 0x23, 0xb0, 0x03, 0x61, 0xa3, 0x96, 0x17, 0x7a, 0x9c, 0xb4, 0x10, 0xff, 0x61,
+            0xf2, 0x00, 0x15, 0xad
+        ]
+    );
+    // Needs two blocks, because the padding does not fit into the first one.
+    assert_eq!(
+        sha256(&[b'a'; 60]),
+        [
+            0x11, 0xee, 0x39, 0x12, 0x11, 0xc6, 0x25, 0x64, 0x60, 0xb6, 0xed, 0x37, 0x59, 0x57,
+            0xfa, 0xdd, 0x80, 0x61, 0xca, 0xfb, 0xb3, 0x1d, 0xaf, 0x96, 0x7d, 0xb8, 0x75, 0xae,
+            0xbd, 0x5a, 0xaa, 0xd4
+        ]
+    );
+}
@@ -4,7 +4,7 @@ use std::utils::force_bool;
 use std::utils::sum;
 
 // Arithmetic machine, ported mainly from Polygon: https://github.com/0xPolygonHermez/zkevm-proverjs/blob/main/pil/arith.pil
-// Currently only supports "Equation 0", i.e., 256-Bit addition and multiplication.
+// Supports 256-Bit addition and multiplication ("Equation 0") and secp256k1 point addition and doubling.
 machine Arith(CLK32_31, operation_id){
     
     // The operation ID will be bit-decomosed to yield selEq[], controlling which equations are activated.
@@ -15,20 +15,121 @@ machine Arith(CLK32_31, operation_id){
     // Operation ID is 1 = 0b0001, i.e., we activate equation 0.
     operation affine_256<1> x1c[0], x1c[1], x1c[2], x1c[3], x1c[4], x1c[5], x1c[6], x1c[7], y1c[0], y1c[1], y1c[2], y1c[3], y1c[4], y1c[5], y1c[6], y1c[7], x2c[0], x2c[1], x2c[2], x2c[3], x2c[4], x2c[5], x2c[6], x2c[7] -> y2c[0], y2c[1], y2c[2], y2c[3], y2c[4], y2c[5], y2c[6], y2c[7], y3c[0], y3c[1], y3c[2], y3c[3], y3c[4], y3c[5], y3c[6], y3c[7];
     
-    // Performs elliptic curve addition of points (x1, y2) and (x2, y2).
+    // Computes the remainder of a 512-bit number modulo a 256-bit number.
+    // More precisely, mod_256(y2, y3, x1) = x2, where y2 * 2**256 + y3 = x1 * y1 + x2 for some y1.
+    // The quotient y1 and the remainder x2 are provided by the prover. Note that this does not
+    // check that x2 < x1, which is up to the caller. Also, y2 needs to be smaller than x1,
+    // so that the quotient fits into 256 bits.
+    // Like affine_256, this activates equation 0 only.
+    operation mod_256<1> y2c[0], y2c[1], y2c[2], y2c[3], y2c[4], y2c[5], y2c[6], y2c[7], y3c[0], y3c[1], y3c[2], y3c[3], y3c[4], y3c[5], y3c[6], y3c[7], x1c[0], x1c[1], x1c[2], x1c[3], x1c[4], x1c[5], x1c[6], x1c[7] -> x2c[0], x2c[1], x2c[2], x2c[3], x2c[4], x2c[5], x2c[6], x2c[7];
+
+    // Performs elliptic curve addition of points (x1, y1) and (x2, y2) on secp256k1.
     // Operation ID is 10 = 0b1010, i.e., we activate equations 1, 3, and 4.
-    // TODO: Witgen doesn't work for that yet.
+    // The points need to be different and not the negation of each other.
     operation ec_add<10> x1c[0], x1c[1], x1c[2], x1c[3], x1c[4], x1c[5], x1c[6], x1c[7], y1c[0], y1c[1], y1c[2], y1c[3], y1c[4], y1c[5], y1c[6], y1c[7], x2c[0], x2c[1], x2c[2], x2c[3], x2c[4], x2c[5], x2c[6], x2c[7], y2c[0], y2c[1], y2c[2], y2c[3], y2c[4], y2c[5], y2c[6], y2c[7] -> x3c[0], x3c[1], x3c[2], x3c[3], x3c[4], x3c[5], x3c[6], x3c[7], y3c[0], y3c[1], y3c[2], y3c[3], y3c[4], y3c[5], y3c[6], y3c[7];
     
-    // Performs elliptic curve doubling of point (x1, y2).
+    // Performs elliptic curve doubling of point (x1, y1) on secp256k1.
     // Operation ID is 12 = 0b1100, i.e., we activate equations 2, 3, and 4.
-    // TODO: Witgen doesn't work for that yet.
+    // The point needs to have a non-zero y coordinate.
     operation ec_double<12> x1c[0], x1c[1], x1c[2], x1c[3], x1c[4], x1c[5], x1c[6], x1c[7], y1c[0], y1c[1], y1c[2], y1c[3], y1c[4], y1c[5], y1c[6], y1c[7] -> x3c[0], x3c[1], x3c[2], x3c[3], x3c[4], x3c[5], x3c[6], x3c[7], y3c[0], y3c[1], y3c[2], y3c[3], y3c[4], y3c[5], y3c[6], y3c[7];
     
     let BYTE = |i| i & 0xff;
     let BYTE2 = |i| i & 0xffff;
 
-    pol commit x1[16], y1[16], x2[16], y2[16], x3[16], y3[16], s[16], q0[16], q1[16], q2[16];
+    pol commit x1[16], y2[16];
+
+    /****
+    *
+    * PROVER HINTS
+    *
+    *****/
+
+    // Most columns can't be solved from the constraints directly, so the prover provides them,
+    // limb by limb. The hints are only queried for cells that are still unknown, i.e. for the
+    // outputs of the current operation.
+
+    let limbs_to_int: expr[], int -> int = |limbs, row| sum(16, |j| std::convert::int(limbs[j](row)) * 2**(16 * j));
+    let select_limb: int, int -> int = |value, limb| (value >> (16 * limb)) % 2**16;
+    let operation_id_int: int -> int = |row| std::convert::int(operation_id(row));
+
+    let x1_int: int -> int = |row| limbs_to_int(x1, row);
+    let y1_int: int -> int = |row| limbs_to_int(y1, row);
+    let x2_int: int -> int = |row| limbs_to_int(x2, row);
+    let y2_int: int -> int = |row| limbs_to_int(y2, row);
+    let y3_int: int -> int = |row| limbs_to_int(y3, row);
+
+    // For mod_256, y1 and x2 are the quotient and the remainder of y2 * 2**256 + y3 divided by x1.
+    // For the other operations, they are inputs (or equal to x1 in the case of x2 for ec_double).
+    let dividend: int -> int = |row| y2_int(row) * 2**256 + y3_int(row);
+    let quotient_hint: int -> int = |row| if x1_int(row) == 0 {
+        0
+    } else {
+        dividend(row) / x1_int(row)
+    };
+    let remainder_hint: int -> int = |row| if x1_int(row) == 0 {
+        dividend(row)
+    } else {
+        dividend(row) % x1_int(row)
+    };
+
+    // The secp256k1 base field modulus.
+    let secp_modulus = 2**256 - 2**32 - 977;
+    let mod_secp: int -> int = |x| ((x % secp_modulus) + secp_modulus) % secp_modulus;
+    /// Returns an inverse of a modulo m (not necessarily in the range [0, m)), where gcd(a, m) = 1,
+    /// using the extended Euclidean algorithm.
+    let inverse: int, int -> int = |a, m| if a == 1 {
+        1
+    } else {
+        ((1 - m * inverse(m % a, a)) / a) % m
+    };
+    /// Returns the inverse of x modulo the secp256k1 modulus, or 0 if x is zero.
+    let inverse_secp: int -> int = |x| if mod_secp(x) == 0 { 0 } else { mod_secp(inverse(mod_secp(x), secp_modulus)) };
+
+    let is_ec_add: int -> bool = |row| operation_id_int(row) == 10;
+    let is_ec_double: int -> bool = |row| operation_id_int(row) == 12;
+    // For ec_double, x2 is constrained to be x1, but it might not be known yet.
+    let ec_x2: int -> int = |row| if is_ec_double(row) { x1_int(row) } else { x2_int(row) };
+
+    // The slope of the line through both points (ec_add) or of the tangent (ec_double).
+    let slope: int -> int = |row| if is_ec_add(row) {
+        mod_secp((y2_int(row) - y1_int(row)) * inverse_secp(ec_x2(row) - x1_int(row)))
+    } else {
+        mod_secp(3 * x1_int(row) * x1_int(row) * inverse_secp(2 * y1_int(row)))
+    };
+    let ec_x3: int -> int = |row| mod_secp(slope(row) * slope(row) - x1_int(row) - ec_x2(row));
+    let ec_y3: int -> int = |row| mod_secp(slope(row) * (x1_int(row) - ec_x3(row)) - y1_int(row));
+
+    // Equations 1 to 4 hold over the integers, with a multiple of p added, where the factor is
+    // q - 2**258. Given the other values, this computes q from the sum of the remaining terms.
+    let q_from_sum: int -> int = |terms| 2**258 - terms / secp_modulus;
+    let ec_q0: int -> int = |row| q_from_sum(if is_ec_add(row) {
+        slope(row) * ec_x2(row) - slope(row) * x1_int(row) - y2_int(row) + y1_int(row)
+    } else {
+        2 * slope(row) * y1_int(row) - 3 * x1_int(row) * x1_int(row)
+    });
+    let ec_q1: int -> int = |row| q_from_sum(slope(row) * slope(row) - x1_int(row) - ec_x2(row) - ec_x3(row));
+    let ec_q2: int -> int = |row| q_from_sum(slope(row) * x1_int(row) - slope(row) * ec_x3(row) - y1_int(row) - ec_y3(row));
+
+    // Returns the hint for EC operations and 0 (i.e. the value unconstrained cells default to) otherwise.
+    let ec_only: (int -> int), int -> int = |f, row| if operation_id_int(row) == 1 { 0 } else { f(row) };
+    // For affine_256, y3 is the low part of x1 * y1 + x2. For mod_256, it is an input.
+    let y3_hint: int -> int = |row| if operation_id_int(row) == 1 {
+        (x1_int(row) * y1_int(row) + x2_int(row)) % 2**256
+    } else {
+        ec_y3(row)
+    };
+    // The most significant limb of q0-q2 is 32 bits wide, see below.
+    let select_q_limb: int, int -> int = |value, limb| if limb == 15 { value >> 240 } else { select_limb(value, limb) };
+
+    // The queries of column arrays receive the index of the limb and the row.
+    col witness y1[16](j, i) query ("hint", select_limb(quotient_hint(i), j));
+    col witness x2[16](j, i) query ("hint", select_limb(remainder_hint(i), j));
+    col witness x3[16](j, i) query ("hint", select_limb(ec_only(ec_x3, i), j));
+    col witness y3[16](j, i) query ("hint", select_limb(y3_hint(i), j));
+    col witness s[16](j, i) query ("hint", select_limb(ec_only(slope, i), j));
+    col witness q0[16](j, i) query ("hint", select_q_limb(ec_only(ec_q0, i), j));
+    col witness q1[16](j, i) query ("hint", select_q_limb(ec_only(ec_q1, i), j));
+    col witness q2[16](j, i) query ("hint", select_q_limb(ec_only(ec_q2, i), j));
 
     let combine: expr[] -> expr[] = |x| array::new(array::len(x) / 2, |i| x[2 * i + 1] * 2**16 + x[2 * i]);
     // Intermediate polynomials, arrays of 8 columns, 32 bit per column.
@@ -83,19 +184,18 @@ machine Arith(CLK32_31, operation_id){
     // Having a larger range-constraint is fine, because we're only multiplying it with 16-bit
     // limbs of the prime, so the result is within 48 bits, still far from overflowing the
     // Goldilocks field.
-    pol witness q0_15_high, q0_15_low, q1_15_high, q1_15_low, q2_15_high, q2_15_low;
-    q0_15_high * CLK32[0] + q0_15_low * CLK32[1] + q1_15_high * CLK32[2] + q1_15_low * CLK32[3] + q2_15_high * CLK32[4] + q2_15_low * CLK32[5] in BYTE2;
-
-    fixed_inside_32_block(q0_15_high);
-    fixed_inside_32_block(q0_15_low);
-    fixed_inside_32_block(q1_15_high);
-    fixed_inside_32_block(q1_15_low);
-    fixed_inside_32_block(q2_15_high);
-    fixed_inside_32_block(q2_15_low);
-
-    q0[15] = 2**16 * q0_15_high + q0_15_low;
-    q1[15] = 2**16 * q1_15_high + q1_15_low;
-    q2[15] = 2**16 * q2_15_high + q2_15_low;
+    // q_15_high[k] and q_15_low[k] are the two limbs of the most significant limb of qk.
+    let ec_q = [ec_q0, ec_q1, ec_q2];
+    col witness q_15_high[3](k, i) query ("hint", select_limb(ec_only(ec_q[k], i), 16));
+    col witness q_15_low[3](k, i) query ("hint", select_limb(ec_only(ec_q[k], i), 15));
+    sum(3, |k| q_15_high[k] * CLK32[2 * k] + q_15_low[k] * CLK32[2 * k + 1]) in BYTE2;
+
+    array::map(q_15_high, fixed_inside_32_block);
+    array::map(q_15_low, fixed_inside_32_block);
+
+    q0[15] = 2**16 * q_15_high[0] + q_15_low[0];
+    q1[15] = 2**16 * q_15_high[1] + q_15_low[1];
+    q2[15] = 2**16 * q_15_high[2] + q_15_low[2];
 
     /*******
     *
@@ -0,0 +1,96 @@
+use std::utils::fold_halves;
+use std::utils::force_bool;
+
+// Implements the Keccak-f[1600] permutation.
+machine KeccakF(LASTBLOCK, operation_id) {
+
+    // Applies the permutation to a state of 25 64-bit lanes, given as 50 32-bit words.
+    // Lane i consists of the words 2 * i (least significant) and 2 * i + 1,
+    // which matches the memory layout of a little-endian [u64; 25].
+    operation keccakf<0> input[0], input[1], input[2], input[3], input[4], input[5], input[6], input[7], input[8], input[9], input[10], input[11], input[12], input[13], input[14], input[15], input[16], input[17], input[18], input[19], input[20], input[21], input[22], input[23], input[24], input[25], input[26], input[27], input[28], input[29], input[30], input[31], input[32], input[33], input[34], input[35], input[36], input[37], input[38], input[39], input[40], input[41], input[42], input[43], input[44], input[45], input[46], input[47], input[48], input[49] -> output[0], output[1], output[2], output[3], output[4], output[5], output[6], output[7], output[8], output[9], output[10], output[11], output[12], output[13], output[14], output[15], output[16], output[17], output[18], output[19], output[20], output[21], output[22], output[23], output[24], output[25], output[26], output[27], output[28], output[29], output[30], output[31], output[32], output[33], output[34], output[35], output[36], output[37], output[38], output[39], output[40], output[41], output[42], output[43], output[44], output[45], output[46], output[47], output[48], output[49];
+
+    col witness operation_id;
+
+    // Each block computes one permutation, with one row per round.
+    // Row i holds the state before round i, and the last row holds the final state.
+    constant %rowsPerPermutation = 25;
+
+    pol constant FIRSTBLOCK(i) { match i % %rowsPerPermutation {
+        0 => 1,
+        _ => 0
+    } };
+    pol constant LASTBLOCK(i) { match i % %rowsPerPermutation {
+        %rowsPerPermutation - 1 => 1,
+        _ => 0
+    } };
+    // Like LASTBLOCK, but also 1 in the last row of the table
+    // Specified this way because we can't access the degree in the match statement
+    pol constant LAST = [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]* + [1];
+
+    // The bits of the round constants. All other bits are zero in every round.
+    pol constant RC_0 = [1, 0, 0, 0, 1, 1, 1, 1, 0, 0, 1, 0, 1, 1, 1, 1, 0, 0, 0, 0, 1, 0, 1, 0, 0]*;
+    pol constant RC_1 = [0, 1, 1, 0, 1, 0, 0, 0, 1, 0, 0, 1, 1, 1, 0, 1, 1, 0, 1, 1, 0, 0, 0, 0, 0]*;
+    pol constant RC_3 = [0, 0, 1, 0, 1, 0, 0, 1, 1, 1, 1, 1, 1, 1, 1, 0, 0, 0, 1, 1, 0, 0, 0, 1, 0]*;
+    pol constant RC_7 = [0, 1, 1, 0, 1, 0, 1, 0, 1, 1, 0, 0, 1, 1, 1, 0, 0, 1, 0, 0, 1, 1, 0, 0, 0]*;
+    pol constant RC_15 = [0, 1, 1, 1, 1, 0, 1, 1, 0, 0, 1, 0, 1, 0, 1, 1, 1, 0, 1, 0, 1, 1, 0, 1, 0]*;
+    pol constant RC_31 = [0, 0, 0, 1, 0, 1, 1, 0, 0, 0, 1, 1, 1, 0, 0, 0, 0, 0, 0, 1, 1, 0, 1, 1, 0]*;
+    pol constant RC_63 = [0, 0, 1, 1, 0, 0, 1, 1, 0, 0, 0, 0, 0, 1, 1, 1, 1, 1, 0, 1, 1, 1, 0, 1, 0]*;
+
+    // Rotation offsets of the lanes, indexed by x + 5 * y.
+    let ROTATION: int[] = [0, 1, 62, 28, 27, 36, 44, 6, 55, 20, 3, 10, 43, 25, 39, 41, 45, 15, 21, 8, 18, 2, 61, 56, 14];
+
+    // The state as bits: bit z of lane (x, y) is state[(x + 5 * y) * 64 + z]
+    pol commit state[1600];
+    // Parities of the columns of the state: c[x * 64 + z] is the XOR of a(x, y, z) over all y
+    pol commit c[320];
+    // The value that is XORed to each column in the theta step
+    pol commit d[320];
+
+    // The input and output words, repeated in every row of the block
+    pol commit input[50];
+    pol commit output[50];
+
+    let constraints: int, int, (int -> constr) -> constr[] = |start, end, f| fold_halves(start, end, |i| [f(i)], |acc, e| acc + e);
+    let sum: int, int, (int -> expr) -> expr = |start, end, f| fold_halves(start, end, f, |acc, e| acc + e);
+
+    constraints(0, 1600, |i| force_bool(state[i]));
+
+    let xor: expr, expr -> expr = |a, b| a + b - 2 * a * b;
+    let a: int, int, int -> expr = |x, y, z| state[(x + 5 * y) * 64 + z];
+    let word: int -> expr = |i| sum(0, 32, |k| 2**k * state[(i / 2) * 64 + (i % 2) * 32 + k]);
+
+    // Theta
+    constraints(0, 320, |i| c[i] = xor(xor(xor(xor(a(i / 64, 0, i % 64), a(i / 64, 1, i % 64)), a(i / 64, 2, i % 64)), a(i / 64, 3, i % 64)), a(i / 64, 4, i % 64)));
+    constraints(0, 320, |i| d[i] = xor(c[((i / 64 + 4) % 5) * 64 + i % 64], c[((i / 64 + 1) % 5) * 64 + (i + 63) % 64]));
+    let theta: int, int, int -> expr = |x, y, z| xor(a(x, y, z), d[x * 64 + z]);
+
+    // Rho and pi: lane (x, y) is rotated and moved to (y, 2 * x + 3 * y).
+    // Here, we compute lane (x, y) of the result from lane (3 * y + x, x) of the input.
+    let b: int, int, int -> expr = |x, y, z| theta((3 * y + x) % 5, x, (z + 64 - ROTATION[(3 * y + x) % 5 + 5 * x]) % 64);
+
+    // Chi
+    let chi: int, int, int -> expr = |x, y, z| xor(b(x, y, z), (1 - b((x + 1) % 5, y, z)) * b((x + 2) % 5, y, z));
+
+    // Iota
+    let iota: int, int, int -> expr = |x, y, z| if x + 5 * y == 0 {
+        match z {
+            0 => xor(chi(x, y, z), RC_0),
+            1 => xor(chi(x, y, z), RC_1),
+            3 => xor(chi(x, y, z), RC_3),
+            7 => xor(chi(x, y, z), RC_7),
+            15 => xor(chi(x, y, z), RC_15),
+            31 => xor(chi(x, y, z), RC_31),
+            63 => xor(chi(x, y, z), RC_63),
+            _ => chi(x, y, z)
+        }
+    } else {
+        chi(x, y, z)
+    };
+
+    let equal_unless_last = |a, b| (1 - LAST) * (a - b) = 0;
+    constraints(0, 1600, |i| equal_unless_last(state[i]', iota((i / 64) % 5, i / 320, i % 64)));
+
+    constraints(0, 50, |i| equal_unless_last(input[i], input[i]'));
+    constraints(0, 50, |i| FIRSTBLOCK * (input[i] - word(i)) = 0);
+    constraints(0, 50, |i| output[i] = word(i));
+}
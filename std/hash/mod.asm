@@ -1,2 +1,4 @@
+mod keccakf;
 mod poseidon_bn254;
-mod poseidon_gl;
\ No newline at end of file
+mod poseidon_gl;
+mod sha256;
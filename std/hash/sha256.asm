@@ -0,0 +1,109 @@
+use std::array;
+use std::utils::fold_halves;
+use std::utils::force_bool;
+
+// Implements the SHA-256 compression function.
+machine Sha256(LASTBLOCK, operation_id) {
+
+    // Compresses a message block into the state and returns the new state.
+    // The first 8 inputs are the state and the remaining 16 inputs are the block,
+    // all as 32-bit words. Padding and the conversion from bytes to big-endian words
+    // are left to the caller.
+    operation compress<0> input[0], input[1], input[2], input[3], input[4], input[5], input[6], input[7], input[8], input[9], input[10], input[11], input[12], input[13], input[14], input[15], input[16], input[17], input[18], input[19], input[20], input[21], input[22], input[23] -> output[0], output[1], output[2], output[3], output[4], output[5], output[6], output[7];
+
+    col witness operation_id;
+
+    // Each block computes one compression, with one row per round.
+    // Row t holds the working variables before round t, row 64 holds the working variables
+    // after the last round and row 65 holds their sum with the input state.
+    constant %rowsPerCompression = 66;
+
+    pol constant FIRSTBLOCK(i) { match i % %rowsPerCompression {
+        0 => 1,
+        _ => 0
+    } };
+    pol constant LASTBLOCK(i) { match i % %rowsPerCompression {
+        %rowsPerCompression - 1 => 1,
+        _ => 0
+    } };
+    // 1 in the rows that compute a round.
+    // Like all transition selectors, it is 0 in the last row of the table,
+    // so that the constraints do not wrap around.
+    pol constant ROUND = [1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 0]* + [0];
+    // 1 in the row that adds the working variables to the input state.
+    pol constant FINALIZE = [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0]* + [0];
+
+    // The round constants
+    pol constant K = [0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5, 0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174, 0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da, 0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967, 0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85, 0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070, 0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3, 0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2, 0, 0]*;
+
+    // The working variables a, b, ..., h as bits: bit k of the v-th variable is var[v * 32 + k]
+    pol commit var[256];
+    // The message schedule words W_t, ..., W_(t + 15) as bits: bit k of W_(t + j) is w[j * 32 + k]
+    pol commit w[512];
+    // The bits of the carries of the additions modulo 2**32
+    pol commit carry_a[3];
+    pol commit carry_e[3];
+    pol commit carry_w[2];
+    pol commit carry_final[8];
+
+    // The input and output words, repeated in every row of the block
+    pol commit input[24];
+    pol commit output[8];
+
+    let constraints: int, int, (int -> constr) -> constr[] = |start, end, f| fold_halves(start, end, |i| [f(i)], |acc, e| acc + e);
+    let sum: int, int, (int -> expr) -> expr = |start, end, f| fold_halves(start, end, f, |acc, e| acc + e);
+
+    constraints(0, 256, |i| force_bool(var[i]));
+    constraints(0, 512, |i| force_bool(w[i]));
+    array::map(carry_a, force_bool);
+    array::map(carry_e, force_bool);
+    array::map(carry_w, force_bool);
+    array::map(carry_final, force_bool);
+
+    // Bit k of a word, for its rotations and shifts to the right
+    let v: int, int -> expr = |i, k| var[i * 32 + k];
+    let rotr: int, int, int -> expr = |i, n, k| v(i, (k + n) % 32);
+    let w_rotr: int, int, int -> expr = |j, n, k| w[j * 32 + (k + n) % 32];
+    let w_shr: int, int, int -> expr = |j, n, k| if k + n < 32 { w[j * 32 + k + n] } else { 0 };
+
+    let xor3: expr, expr, expr -> expr = |x, y, z| x + y + z - 2 * (x * y + x * z + y * z) + 4 * x * y * z;
+    let word: (int -> expr) -> expr = |bit| sum(0, 32, |k| 2**k * bit(k));
+
+    // The functions of the rounds and the message schedule, bit by bit
+    let sigma_upper_0: int -> expr = |k| xor3(rotr(0, 2, k), rotr(0, 13, k), rotr(0, 22, k));
+    let sigma_upper_1: int -> expr = |k| xor3(rotr(4, 6, k), rotr(4, 11, k), rotr(4, 25, k));
+    let ch: int -> expr = |k| v(4, k) * v(5, k) + (1 - v(4, k)) * v(6, k);
+    let maj: int -> expr = |k| v(0, k) * v(1, k) + v(0, k) * v(2, k) + v(1, k) * v(2, k) - 2 * v(0, k) * v(1, k) * v(2, k);
+    let sigma_lower_0: int -> expr = |k| xor3(w_rotr(1, 7, k), w_rotr(1, 18, k), w_shr(1, 3, k));
+    let sigma_lower_1: int -> expr = |k| xor3(w_rotr(14, 17, k), w_rotr(14, 19, k), w_shr(14, 10, k));
+
+    let var_word: int -> expr = |i| word(|k| v(i, k));
+    let next_var_word: int -> expr = |i| word(|k| v(i, k)');
+    let w_word: int -> expr = |j| word(|k| w[j * 32 + k]);
+
+    // The round: T1 = h + Sigma1(e) + Ch(e, f, g) + K_t + W_t and T2 = Sigma0(a) + Maj(a, b, c).
+    // The new a is T1 + T2 and the new e is d + T1, both modulo 2**32.
+    let T1: expr = var_word(7) + word(sigma_upper_1) + word(ch) + K + w_word(0);
+    let T2: expr = word(sigma_upper_0) + word(maj);
+    ROUND * (next_var_word(0) + 2**32 * (carry_a[0] + 2 * carry_a[1] + 4 * carry_a[2]) - (T1 + T2)) = 0;
+    ROUND * (next_var_word(4) + 2**32 * (carry_e[0] + 2 * carry_e[1] + 4 * carry_e[2]) - (var_word(3) + T1)) = 0;
+    // The other working variables are moved by one position.
+    constraints(0, 32, |k| ROUND * (v(1, k)' - v(0, k)) = 0);
+    constraints(0, 32, |k| ROUND * (v(2, k)' - v(1, k)) = 0);
+    constraints(0, 32, |k| ROUND * (v(3, k)' - v(2, k)) = 0);
+    constraints(0, 32, |k| ROUND * (v(5, k)' - v(4, k)) = 0);
+    constraints(0, 32, |k| ROUND * (v(6, k)' - v(5, k)) = 0);
+    constraints(0, 32, |k| ROUND * (v(7, k)' - v(6, k)) = 0);
+
+    // The message schedule: W_(t + 16) = sigma1(W_(t + 14)) + W_(t + 9) + sigma0(W_(t + 1)) + W_t modulo 2**32
+    constraints(0, 480, |i| ROUND * (w[i]' - w[i + 32]) = 0);
+    ROUND * (word(|k| w[15 * 32 + k]') + 2**32 * (carry_w[0] + 2 * carry_w[1]) - (word(sigma_lower_1) + w_word(9) + word(sigma_lower_0) + w_word(0))) = 0;
+
+    // The new state is the sum of the input state and the working variables modulo 2**32.
+    constraints(0, 8, |i| FINALIZE * (next_var_word(i) + 2**32 * carry_final[i] - (input[i] + var_word(i))) = 0);
+
+    constraints(0, 24, |i| (ROUND + FINALIZE) * (input[i]' - input[i]) = 0);
+    constraints(0, 8, |i| FIRSTBLOCK * (input[i] - var_word(i)) = 0);
+    constraints(0, 16, |i| FIRSTBLOCK * (input[8 + i] - w_word(i)) = 0);
+    constraints(0, 8, |i| output[i] = var_word(i));
+}
@@ -14,6 +14,19 @@ let fold = |length, f, initial, folder|
 /// Evaluates to f(0) + f(1) + ... + f(length - 1).
 let sum = |length, f| fold(length, f, 0, |acc, e| (acc + e));
 
+/// Evaluates to f(start) + f(start + 1) + ... + f(end - 1), where start < end.
+/// Unlike `fold`, this function combines the halves of the range,
+/// so that the recursion depth is logarithmic in the length of the range.
+/// This avoids stack overflows during evaluation for long ranges.
+///
+/// The addition can be replaced by an arbitrary associative function `folder`.
+let fold_halves = |start, end, f, folder|
+    if end - start == 1 {
+        f(start)
+    } else {
+        folder(fold_halves(start, (start + end) / 2, f, folder), fold_halves((start + end) / 2, end, f, folder))
+    };
+
 /// Evaluates to a constraint that forces the witness column `c` to stay constant
 /// until `latch` is 1. In the row following the row where `latch` is 1,
 /// `c` is allowed to change.
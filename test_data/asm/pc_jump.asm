@@ -0,0 +1,25 @@
+machine Main {
+    degree 16;
+
+    reg pc[@pc];
+    reg X[<=];
+    reg A;
+
+    // Lets the prover bypass the normal PC update and jump to `target` instead.
+    col witness pc_jump_flag;
+    pc_jump_flag * (1 - pc_jump_flag) = 0;
+    let pc_jump_target = 4;
+    override_pc pc_jump_flag => pc_jump_target;
+
+    instr assert_zero X { X = 0 }
+    instr loop { pc' = pc }
+
+    function main {
+        A <=X= 1;
+        // Without the jump, the machine would be stuck here forever.
+        loop;
+        A <=X= 2;
+        assert_zero A - 2;
+        return;
+    }
+}
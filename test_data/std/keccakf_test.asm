@@ -0,0 +1,177 @@
+use std::hash::keccakf::KeccakF;
+
+machine Main {
+    degree 256;
+
+    reg pc[@pc];
+    reg X0[<=];
+    reg X1[<=];
+    reg S0;
+    reg S1;
+    reg S2;
+    reg S3;
+    reg S4;
+    reg S5;
+    reg S6;
+    reg S7;
+    reg S8;
+    reg S9;
+    reg S10;
+    reg S11;
+    reg S12;
+    reg S13;
+    reg S14;
+    reg S15;
+    reg S16;
+    reg S17;
+    reg S18;
+    reg S19;
+    reg S20;
+    reg S21;
+    reg S22;
+    reg S23;
+    reg S24;
+    reg S25;
+    reg S26;
+    reg S27;
+    reg S28;
+    reg S29;
+    reg S30;
+    reg S31;
+    reg S32;
+    reg S33;
+    reg S34;
+    reg S35;
+    reg S36;
+    reg S37;
+    reg S38;
+    reg S39;
+    reg S40;
+    reg S41;
+    reg S42;
+    reg S43;
+    reg S44;
+    reg S45;
+    reg S46;
+    reg S47;
+    reg S48;
+    reg S49;
+
+    KeccakF keccakf;
+
+    instr keccakf = keccakf.keccakf S0, S1, S2, S3, S4, S5, S6, S7, S8, S9, S10, S11, S12, S13, S14, S15, S16, S17, S18, S19, S20, S21, S22, S23, S24, S25, S26, S27, S28, S29, S30, S31, S32, S33, S34, S35, S36, S37, S38, S39, S40, S41, S42, S43, S44, S45, S46, S47, S48, S49 -> S0, S1, S2, S3, S4, S5, S6, S7, S8, S9, S10, S11, S12, S13, S14, S15, S16, S17, S18, S19, S20, S21, S22, S23, S24, S25, S26, S27, S28, S29, S30, S31, S32, S33, S34, S35, S36, S37, S38, S39, S40, S41, S42, S43, S44, S45, S46, S47, S48, S49;
+
+    instr assert_eq X0, X1 {
+        X0 = X1
+    }
+
+    function main {
+        // Keccak-f of the zero state
+        keccakf;
+        assert_eq S0, 1088544231;
+        assert_eq S1, 4045770617;
+        assert_eq S2, 868239242;
+        assert_eq S3, 2228604153;
+        assert_eq S4, 2790959598;
+        assert_eq S5, 3583518238;
+        assert_eq S6, 1870678349;
+        assert_eq S7, 3172288304;
+        assert_eq S8, 1649659991;
+        assert_eq S9, 2334674437;
+        assert_eq S10, 2140041172;
+        assert_eq S11, 4288128045;
+        assert_eq S12, 2756069316;
+        assert_eq S13, 2432624032;
+        assert_eq S14, 3591974518;
+        assert_eq S15, 2354829836;
+        assert_eq S16, 454624668;
+        assert_eq S17, 2905646839;
+        assert_eq S18, 3499097188;
+        assert_eq S19, 814963383;
+        assert_eq S20, 588764725;
+        assert_eq S21, 3948587327;
+        assert_eq S22, 225517827;
+        assert_eq S23, 2846287398;
+        assert_eq S24, 3687798111;
+        assert_eq S25, 2175106070;
+        assert_eq S26, 55035942;
+        assert_eq S27, 1136144845;
+        assert_eq S28, 296048287;
+        assert_eq S29, 32648986;
+        assert_eq S30, 567914081;
+        assert_eq S31, 98919258;
+        assert_eq S32, 2362011890;
+        assert_eq S33, 1690238706;
+        assert_eq S34, 2076468753;
+        assert_eq S35, 1630957717;
+        assert_eq S36, 1339035339;
+        assert_eq S37, 3095157333;
+        assert_eq S38, 483341000;
+        assert_eq S39, 2352933002;
+        assert_eq S40, 2923046420;
+        assert_eq S41, 2483845410;
+        assert_eq S42, 2730822116;
+        assert_eq S43, 406976804;
+        assert_eq S44, 3875825090;
+        assert_eq S45, 385168678;
+        assert_eq S46, 2133893435;
+        assert_eq S47, 1979073769;
+        assert_eq S48, 1559011913;
+        assert_eq S49, 3941728123;
+
+        // Applying it again to the result
+        keccakf;
+        assert_eq S0, 4184787772;
+        assert_eq S1, 761042253;
+        assert_eq S2, 1884796269;
+        assert_eq S3, 1781738704;
+        assert_eq S4, 1893165932;
+        assert_eq S5, 155028754;
+        assert_eq S6, 1432998036;
+        assert_eq S7, 2317408690;
+        assert_eq S8, 3857183062;
+        assert_eq S9, 1335644057;
+        assert_eq S10, 3664116536;
+        assert_eq S11, 4183275938;
+        assert_eq S12, 309702413;
+        assert_eq S13, 2239184302;
+        assert_eq S14, 3285717239;
+        assert_eq S15, 4210356807;
+        assert_eq S16, 4154099545;
+        assert_eq S17, 521903846;
+        assert_eq S18, 4002984997;
+        assert_eq S19, 3841903631;
+        assert_eq S20, 3117312161;
+        assert_eq S21, 1758355894;
+        assert_eq S22, 3129972559;
+        assert_eq S23, 3739903684;
+        assert_eq S24, 1857008117;
+        assert_eq S25, 868498819;
+        assert_eq S26, 656006105;
+        assert_eq S27, 3758511108;
+        assert_eq S28, 159584869;
+        assert_eq S29, 2096671216;
+        assert_eq S30, 3205973827;
+        assert_eq S31, 4250159526;
+        assert_eq S32, 3633924928;
+        assert_eq S33, 2547887411;
+        assert_eq S34, 1561061236;
+        assert_eq S35, 1223349756;
+        assert_eq S36, 1438101564;
+        assert_eq S37, 3820538094;
+        assert_eq S38, 1688093417;
+        assert_eq S39, 2443190894;
+        assert_eq S40, 3887783291;
+        assert_eq S41, 2416849193;
+        assert_eq S42, 4205038824;
+        assert_eq S43, 539664069;
+        assert_eq S44, 1310473654;
+        assert_eq S45, 1530135110;
+        assert_eq S46, 2756448345;
+        assert_eq S47, 1621053026;
+        assert_eq S48, 1787805532;
+        assert_eq S49, 550530258;
+
+        return;
+    }
+}
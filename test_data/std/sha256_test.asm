@@ -0,0 +1,108 @@
+use std::hash::sha256::Sha256;
+
+machine Main {
+    degree 256;
+
+    reg pc[@pc];
+    reg X0[<=];
+    reg X1[<=];
+    reg S0;
+    reg S1;
+    reg S2;
+    reg S3;
+    reg S4;
+    reg S5;
+    reg S6;
+    reg S7;
+    reg S8;
+    reg S9;
+    reg S10;
+    reg S11;
+    reg S12;
+    reg S13;
+    reg S14;
+    reg S15;
+    reg S16;
+    reg S17;
+    reg S18;
+    reg S19;
+    reg S20;
+    reg S21;
+    reg S22;
+    reg S23;
+
+    Sha256 sha256;
+
+    instr sha256_compress = sha256.compress S0, S1, S2, S3, S4, S5, S6, S7, S8, S9, S10, S11, S12, S13, S14, S15, S16, S17, S18, S19, S20, S21, S22, S23 -> S0, S1, S2, S3, S4, S5, S6, S7;
+
+    instr assert_eq X0, X1 {
+        X0 = X1
+    }
+
+    function main {
+        // The padded message "abc", compressed into the initial state.
+        // The result is the SHA-256 hash of "abc".
+        S0 <=X0= 1779033703;
+        S1 <=X0= 3144134277;
+        S2 <=X0= 1013904242;
+        S3 <=X0= 2773480762;
+        S4 <=X0= 1359893119;
+        S5 <=X0= 2600822924;
+        S6 <=X0= 528734635;
+        S7 <=X0= 1541459225;
+        S8 <=X0= 1633837952;
+        S9 <=X0= 0;
+        S10 <=X0= 0;
+        S11 <=X0= 0;
+        S12 <=X0= 0;
+        S13 <=X0= 0;
+        S14 <=X0= 0;
+        S15 <=X0= 0;
+        S16 <=X0= 0;
+        S17 <=X0= 0;
+        S18 <=X0= 0;
+        S19 <=X0= 0;
+        S20 <=X0= 0;
+        S21 <=X0= 0;
+        S22 <=X0= 0;
+        S23 <=X0= 24;
+        sha256_compress;
+        assert_eq S0, 3128432319;
+        assert_eq S1, 2399260650;
+        assert_eq S2, 1094795486;
+        assert_eq S3, 1571693091;
+        assert_eq S4, 2953011619;
+        assert_eq S5, 2518121116;
+        assert_eq S6, 3021012833;
+        assert_eq S7, 4060091821;
+
+        // Another block, compressed into the previous result
+        S8 <=X0= 3735928559;
+        S9 <=X0= 3752837619;
+        S10 <=X0= 3769746679;
+        S11 <=X0= 3786655739;
+        S12 <=X0= 3803564799;
+        S13 <=X0= 3820473859;
+        S14 <=X0= 3837382919;
+        S15 <=X0= 3854291979;
+        S16 <=X0= 3871201039;
+        S17 <=X0= 3888110099;
+        S18 <=X0= 3905019159;
+        S19 <=X0= 3921928219;
+        S20 <=X0= 3938837279;
+        S21 <=X0= 3955746339;
+        S22 <=X0= 3972655399;
+        S23 <=X0= 3989564459;
+        sha256_compress;
+        assert_eq S0, 2181025401;
+        assert_eq S1, 2346914375;
+        assert_eq S2, 23761965;
+        assert_eq S3, 1073976773;
+        assert_eq S4, 1518072478;
+        assert_eq S5, 1689930395;
+        assert_eq S6, 2726326272;
+        assert_eq S7, 10606389;
+
+        return;
+    }
+}